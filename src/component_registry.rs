@@ -0,0 +1,252 @@
+//! Every component the game knows about, listed once. The list drives world setup
+//! as well as saving and loading, so a component can't be registered without also
+//! being written to and read back from save files.
+
+use serde_json::Value;
+#[allow(deprecated)]
+use specs::error::NoError;
+use specs::saveload::{
+    DeserializeComponents, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+use specs::{World, WorldExt};
+
+use crate::components::*;
+
+macro_rules! component_registry {
+    ($( $type:ty ),* $(,)?) => {
+        /// The name of every registered component, in save file order.
+        pub const COMPONENT_NAMES: &[&str] = &[$( stringify!($type) ),*];
+
+        /// Registers every component, plus the marker that picks what gets saved.
+        pub fn register_components(ecs: &mut World) {
+            $( ecs.register::<$type>(); )*
+            ecs.register::<SimpleMarker<SerializeMe>>();
+            ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        }
+
+        /// Serializes the components of every marked entity, one value per storage.
+        #[allow(deprecated)]
+        pub fn save_components(ecs: &World) -> Vec<Value> {
+            let entities = ecs.entities();
+            let markers = ecs.read_storage::<SimpleMarker<SerializeMe>>();
+            vec![$(
+            SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+                &(ecs.read_storage::<$type>(),),
+                &entities,
+                &markers,
+                serde_json::value::Serializer,
+            )
+            .unwrap(),
+            )*]
+        }
+
+        /// Reads back the storages `save_components` serialized, one value per storage,
+        /// creating an entity for each marker.
+        #[allow(deprecated)]
+        pub fn load_components(ecs: &World, storages: &[Value]) -> Result<(), serde_json::Error> {
+            let entities = ecs.entities();
+            let mut markers = ecs.write_storage::<SimpleMarker<SerializeMe>>();
+            let mut allocator = ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>();
+            let mut storages = storages.iter();
+            $(
+            let storage = storages.next().ok_or_else(|| {
+                serde::de::Error::custom(concat!("missing storage for ", stringify!($type)))
+            })?;
+            DeserializeComponents::<NoError, _>::deserialize(
+                &mut (&mut ecs.write_storage::<$type>(),),
+                &entities,
+                &mut markers,
+                &mut allocator,
+                storage,
+            )?;
+            )*
+            Ok(())
+        }
+    };
+}
+
+component_registry!(
+    Position,
+    Renderable,
+    Player,
+    Viewshed,
+    Name,
+    BlocksTile,
+    WantsToMelee,
+    Item,
+    Consumable,
+    Ranged,
+    InflictsDamage,
+    AreaOfEffect,
+    Confusion,
+    ProvidesHealing,
+    InBackpack,
+    WantsToPickupItem,
+    WantsToUseItem,
+    WantsToDropItem,
+    SerializationHelper,
+    Equippable,
+    Equipped,
+    MeleePowerBonus,
+    DefenseBonus,
+    WantsToRemoveItem,
+    ParticleLifetime,
+    HungerClock,
+    ProvidesFood,
+    MagicMapper,
+    Hidden,
+    EntryTrigger,
+    EntityMoved,
+    SingleActivation,
+    Door,
+    BlocksVisibility,
+    Quips,
+    Attributes,
+    Skills,
+    Pools,
+    Wearable,
+    NaturalAttackDefense,
+    LootTable,
+    OtherLevelPosition,
+    DMSerializationHelper,
+    LightSource,
+    Initiative,
+    MyTurn,
+    Faction,
+    MoveMode,
+    WantsToApproach,
+    WantsToFlee,
+    Chasing,
+    EquipmentChanged,
+    Vendor,
+    TownPortal,
+    TeleportTo,
+    ApplyMove,
+    ApplyTeleport,
+    MagicItem,
+    ObfuscatedName,
+    IdentifiedItem,
+    SpawnParticleLine,
+    SpawnParticleBurst,
+    CursedItem,
+    ProvidesRemoveCurse,
+    ProvidesIdentification,
+    AttributeBonus,
+    Duration,
+    StatusEffect,
+    KnownSpells,
+    SpellTemplate,
+    ProvidesMana,
+    WantsToCastSpell,
+    TeachesSpell,
+    Slow,
+    DamageOverTime,
+    SpecialAbilities,
+    TileSize,
+    OnDeath,
+    AlwaysTargetsSelf,
+    Weapon,
+    Target,
+    WantsToShoot,
+    CharacterHistory,
+    Resistances,
+    StatusInfo,
+    HealingOverTime,
+    AppliesStatus,
+    Dispels,
+    StatusImmunities,
+);
+
+/// Lists the components declared in `source` that the registry doesn't know about.
+/// Anything it returns would exist in the world but vanish from every save.
+#[cfg(test)]
+fn unregistered_components(source: &str) -> Vec<String> {
+    let mut missing = Vec::new();
+    let mut derive: Option<String> = None;
+    let mut deriving_component = false;
+    for line in source.lines().map(str::trim) {
+        // Derives can be spread over several lines, so gather one up to its `)]`
+        if let Some(attribute) = &mut derive {
+            attribute.push_str(line);
+        } else if line.starts_with("#[derive(") {
+            derive = Some(line.to_string());
+        }
+        if let Some(attribute) = &derive {
+            if attribute.contains(")]") {
+                deriving_component |= attribute
+                    .split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .any(|word| word == "Component");
+                derive = None;
+            }
+            continue;
+        }
+        if line.starts_with("#[") || line.starts_with("///") {
+            continue;
+        }
+        let declaration = match line.strip_prefix("pub(") {
+            Some(rest) => rest.split_once(')').map_or(rest, |(_, rest)| rest).trim_start(),
+            None => line.strip_prefix("pub ").unwrap_or(line),
+        };
+        if let Some(rest) = declaration.strip_prefix("struct ") {
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if deriving_component && !COMPONENT_NAMES.contains(&name.as_str()) {
+                missing.push(name);
+            }
+        }
+        deriving_component = false;
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unregistered_components;
+
+    #[test]
+    fn every_component_is_in_the_registry() {
+        let missing = unregistered_components(include_str!("components.rs"));
+
+        assert!(missing.is_empty(), "not saved: {missing:?}");
+    }
+
+    #[test]
+    fn unregistered_components_are_reported() {
+        let source = "
+            #[derive(Component, Serialize, Deserialize, Clone)]
+            pub struct Position {}
+
+            #[derive(Component, ConvertSaveload, Clone)]
+            pub struct Forgotten {
+                pub owner: Entity,
+            }
+
+            #[derive(Serialize, Deserialize, Clone)]
+            pub struct NotAComponent {}
+
+            #[derive(
+                Component,
+                Serialize,
+                Deserialize,
+                Clone,
+            )]
+            /// Spread over several lines
+            pub struct LongDerive {}
+
+            #[derive(Component, Serialize, Deserialize, Clone)]
+            #[serde(default)]
+            pub(crate) struct CrateOnly {}
+        ";
+
+        assert_eq!(
+            unregistered_components(source),
+            vec![
+                "Forgotten".to_string(),
+                "LongDerive".to_string(),
+                "CrateOnly".to_string()
+            ]
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use crate::gamelog;
-use itertools::Itertools;
-use rltk::{to_cp437, FontCharType, Point, RGB};
+use crate::dice::DiceRoll;
+use crate::gamelog::{GameEvents, GameLog};
+use crate::gamesystem::{DEFAULT_CRIT_MULTIPLIER, DEFAULT_CRIT_RANGE, NORMAL_SPEED};
+use rltk::{FontCharType, Point, RGB};
 use serde::{Deserialize, Serialize};
 #[allow(deprecated)]
 use specs::error::NoError;
@@ -96,9 +97,81 @@ pub struct Ranged {
     pub range: i32,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
+/// What kind of harm a hit does, which decides who shrugs it off.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageType {
+    /// Starvation and the like, which nothing resists.
+    #[default]
+    Untyped,
+    Slashing,
+    Piercing,
+    Bludgeoning,
+    Fire,
+    Cold,
+    Lightning,
+    Poison,
+    Acid,
+}
+
+impl DamageType {
+    /// "fire damage", or just "damage" when it has no type.
+    pub fn noun(self) -> String {
+        match self {
+            Self::Untyped => "damage".to_string(),
+            _ => format!("{} damage", format!("{self:?}").to_lowercase()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageResponse {
+    Resistant,
+    Immune,
+    Vulnerable,
+}
+
+impl DamageResponse {
+    /// How several sources of resistance add up. Immunity beats everything, a
+    /// resistance and a vulnerability cancel out, and none of them stack.
+    pub fn combine(responses: impl IntoIterator<Item = Self>) -> Option<Self> {
+        let (mut resistant, mut vulnerable) = (false, false);
+        for response in responses {
+            match response {
+                Self::Immune => return Some(Self::Immune),
+                Self::Resistant => resistant = true,
+                Self::Vulnerable => vulnerable = true,
+            }
+        }
+        match (resistant, vulnerable) {
+            (true, false) => Some(Self::Resistant),
+            (false, true) => Some(Self::Vulnerable),
+            _ => None,
+        }
+    }
+
+    pub const fn apply(self, amount: i32) -> i32 {
+        match self {
+            Self::Resistant => amount / 2,
+            Self::Immune => 0,
+            Self::Vulnerable => amount * 2,
+        }
+    }
+}
+
+/// Damage types taken differently from usual. On a creature it's their nature,
+/// on armour it protects the wearer and on a status effect its target.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Resistances {
+    pub damage: HashMap<DamageType, DamageResponse>,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct InflictsDamage {
-    pub damage: i32,
+    pub damage: DiceRoll,
+    #[serde(default)]
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -119,6 +192,67 @@ pub struct StatusEffect {
     pub target: Entity,
 }
 
+/// What happens when a status is put on something already under it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StackingPolicy {
+    /// Starts its duration over.
+    #[default]
+    Refresh,
+    /// Adds a stack, up to its limit, and starts its duration over. Modifiers and
+    /// periodic effects count once per stack.
+    StackIntensity,
+    /// Does nothing until the first one wears off.
+    Unique,
+}
+
+/// How a status from the raws looks and stacks. Sits on the status entity with
+/// `StatusEffect`, `Duration`, `Name` and whatever components it modifies with.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusInfo {
+    pub glyph: FontCharType,
+    pub color: RGB,
+    pub stacking: StackingPolicy,
+    pub stacks: i32,
+    pub max_stacks: i32,
+    /// Dispels and immunities pick statuses out by these, such as "poison".
+    pub tags: Vec<String>,
+}
+
+impl StatusInfo {
+    /// How many times a status entity's modifiers count; statuses from before
+    /// stacking have no info and count once.
+    pub fn stacks_of(info: Option<&StatusInfo>) -> i32 {
+        info.map_or(1, |info| info.stacks)
+    }
+}
+
+/// Applies the status of this name from the raws.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AppliesStatus {
+    pub name: String,
+}
+
+/// Removes every status carrying this tag.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Dispels {
+    pub tag: String,
+}
+
+/// Status names and tags that can't be put on this entity.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusImmunities {
+    pub statuses: Vec<String>,
+}
+
+impl StatusImmunities {
+    pub fn blocks(&self, name: &str, tags: &[String]) -> bool {
+        self.statuses
+            .iter()
+            .any(|immune| immune == name || tags.contains(immune))
+    }
+}
+
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct SerializeMe;
 
@@ -130,8 +264,14 @@ pub struct SerializationHelper {
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct DMSerializationHelper {
     pub map: MasterDungeonMap,
-    pub log: Vec<Vec<gamelog::LogFragment>>,
-    pub events: HashMap<String, i32>,
+    pub log: GameLog,
+    pub events: GameEvents,
+    /// Saves from before runs were seeded don't have one, and carry on from 0.
+    #[serde(default)]
+    pub seed: u64,
+    /// Saves from before turns were counted across levels start again from 0.
+    #[serde(default)]
+    pub turns: i32,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
@@ -146,7 +286,7 @@ pub enum EquipmentSlot {
 }
 
 impl FromStr for EquipmentSlot {
-    type Err = !;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
@@ -157,7 +297,7 @@ impl FromStr for EquipmentSlot {
             "Feet" => Self::Feet,
             "Hands" => Self::Hands,
             "Melee" => Self::Melee,
-            _ => unreachable!("Invalid slot type"),
+            unknown => return Err(format!("unknown equipment slot \"{unknown}\"")),
         })
     }
 }
@@ -255,21 +395,56 @@ pub struct Attributes {
 }
 
 impl Attributes {
+    pub fn get_mut(&mut self, kind: AttributeKind) -> &mut Attribute {
+        match kind {
+            AttributeKind::Might => &mut self.might,
+            AttributeKind::Fitness => &mut self.fitness,
+            AttributeKind::Quickness => &mut self.quickness,
+            AttributeKind::Intelligence => &mut self.intelligence,
+        }
+    }
+
     pub fn get_max_carry_capacity(&self) -> u32 {
         f32::round((self.might.base + self.might.modifiers) as f32 * 15.0 / LBS_TO_KG_RATIO) as u32
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+/// Names one of the four attributes, for when the player gets to pick.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeKind {
+    Might,
+    Fitness,
+    Quickness,
+    Intelligence,
+}
+
+impl AttributeKind {
+    pub const ALL: [Self; 4] = [
+        Self::Might,
+        Self::Fitness,
+        Self::Quickness,
+        Self::Intelligence,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Skill {
     Melee,
     Defense,
     Magic,
 }
 
+impl Skill {
+    pub const ALL: [Self; 3] = [Self::Melee, Self::Defense, Self::Magic];
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Skills {
     pub skills: HashMap<Skill, i32>,
+    /// Uses counted towards each skill's next rank. Only the player's go up.
+    #[serde(default)]
+    pub practice: HashMap<Skill, i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -302,31 +477,72 @@ impl Default for WeaponAttribute {
     }
 }
 
-#[derive(Default, Component, Serialize, Deserialize, Clone)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Weapon {
     pub attribute: WeaponAttribute,
-    pub damage_n_dice: i32,
-    pub damage_die_type: i32,
-    pub damage_bonus: i32,
+    pub damage: DiceRoll,
     pub hit_bonus: i32,
     pub proc_chance: Option<f32>,
     pub proc_target: Option<String>,
     pub range: Option<i32>,
+    /// Above `NORMAL_SPEED` the weapon attacks quicker, below it slower.
+    #[serde(default = "normal_speed")]
+    pub speed: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// The lowest natural d20 roll that crits.
+    #[serde(default = "default_crit_range")]
+    pub crit_range: i32,
+    /// What a crit multiplies the damage dice by.
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: i32,
+    /// Points of the target's armour damage reduction it ignores.
+    #[serde(default)]
+    pub armor_penetration: i32,
+}
+
+fn default_crit_range() -> i32 {
+    DEFAULT_CRIT_RANGE
+}
+
+fn default_crit_multiplier() -> i32 {
+    DEFAULT_CRIT_MULTIPLIER
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            attribute: WeaponAttribute::default(),
+            damage: DiceRoll::default(),
+            hit_bonus: 0,
+            proc_chance: None,
+            proc_target: None,
+            range: None,
+            speed: NORMAL_SPEED,
+            damage_type: DamageType::default(),
+            crit_range: DEFAULT_CRIT_RANGE,
+            crit_multiplier: DEFAULT_CRIT_MULTIPLIER,
+            armor_penetration: 0,
+        }
+    }
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Wearable {
     pub armor_class: f32,
     pub slot: EquipmentSlot,
+    /// Damage taken off every melee hit on the wearer.
+    #[serde(default)]
+    pub damage_reduction: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NaturalAttack {
     pub name: String,
-    pub damage_n_dice: i32,
-    pub damage_die_type: i32,
-    pub damage_bonus: i32,
+    pub damage: DiceRoll,
     pub hit_bonus: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -353,9 +569,40 @@ pub struct LightSource {
     pub range: i32,
 }
 
+/// What an actor did with its turn, which decides how long until its next one.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Wait,
+    Move,
+    Melee { weapon_speed: i32 },
+    Shoot { weapon_speed: i32 },
+    Cast,
+    UseItem,
+    PickUp,
+    Drop,
+    Equip,
+}
+
+fn normal_speed() -> i32 {
+    NORMAL_SPEED
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Initiative {
+    /// Ticks until the next turn.
     pub current: i32,
+    /// How quickly the actor does everything; `NORMAL_SPEED` is ordinary.
+    #[serde(default = "normal_speed")]
+    pub speed: i32,
+    /// What the actor did with its last turn, until it's been charged for.
+    #[serde(default)]
+    pub spent: Option<ActionKind>,
+}
+
+impl Initiative {
+    pub fn spend(&mut self, action: ActionKind) {
+        self.spent = Some(action);
+    }
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -453,19 +700,6 @@ pub struct SpawnParticleLine {
     pub lifetime_ms: f32,
 }
 
-impl FromStr for SpawnParticleLine {
-    type Err = !;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens = s.split(';').collect_vec();
-        Ok(Self {
-            glyph: to_cp437(tokens[0].chars().next().unwrap()),
-            color: RGB::from_hex(tokens[1]).expect("Bad RGB"),
-            lifetime_ms: tokens[2].parse().unwrap(),
-        })
-    }
-}
-
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SpawnParticleBurst {
     pub glyph: FontCharType,
@@ -473,20 +707,6 @@ pub struct SpawnParticleBurst {
     pub lifetime_ms: f32,
 }
 
-impl FromStr for SpawnParticleBurst {
-    type Err = !;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens = s.split(';').collect_vec();
-
-        Ok(Self {
-            glyph: to_cp437(tokens[0].chars().next().unwrap()),
-            color: RGB::from_hex(tokens[1]).expect("Bad RGB"),
-            lifetime_ms: tokens[2].parse().unwrap(),
-        })
-    }
-}
-
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct CursedItem {}
 
@@ -544,6 +764,13 @@ pub struct Slow {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct DamageOverTime {
     pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct HealingOverTime {
+    pub healing: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -581,6 +808,35 @@ pub struct WantsToShoot {
     pub target: Entity,
 }
 
+/// What the player picked on a level up screen.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LevelUpChoice {
+    pub attribute: AttributeKind,
+    pub skill: Skill,
+    /// The name of a perk from the `perks` raws.
+    pub perk: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LevelUpRecord {
+    pub level: i32,
+    pub choice: LevelUpChoice,
+}
+
+/// The player's level ups so far, for the stats at the end of a run.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CharacterHistory {
+    pub level_ups: Vec<LevelUpRecord>,
+}
+
+impl CharacterHistory {
+    pub fn has_perk(&self, name: &str) -> bool {
+        self.level_ups
+            .iter()
+            .any(|record| record.choice.perk.as_deref() == Some(name))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ParticleAnimation {
     pub step_time: f32,
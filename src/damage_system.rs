@@ -1,15 +1,18 @@
-use rltk::Point;
+use std::sync::Arc;
+
+use rltk::{Point, RandomNumberGenerator};
 use specs::{Join, World, WorldExt};
 
 use crate::components::{
     AreaOfEffect, Equipped, InBackpack, LootTable, Name, OnDeath, Player, Pools, Position,
 };
 use crate::effects::{add_effect, aoe_tiles, EffectType, Targets};
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 use crate::map::Map;
 use crate::player::RunState;
-use crate::raws::rawmaster::{find_spell_entity, get_item_drop, spawn_named_item, SpawnType, RAWS};
-use crate::rng::roll_dice;
+use crate::raws::rawmaster::{
+    find_spell_entity, get_item_drop, spawn_named_item, RawMaster, SpawnType,
+};
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead = Vec::new();
@@ -29,7 +32,7 @@ pub fn delete_the_dead(ecs: &mut World) {
                             gamelog::Logger::new()
                                 .npc_name(&victim_name.name)
                                 .append("is dead")
-                                .log();
+                                .log(&mut ecs.write_resource::<GameLog>());
                         }
                         dead.push(entity);
                     }
@@ -42,6 +45,7 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    let raws = ecs.fetch::<Arc<RawMaster>>().clone();
     let mut to_spawn = Vec::new();
     {
         let mut to_drop = Vec::new();
@@ -68,7 +72,11 @@ pub fn delete_the_dead(ecs: &mut World) {
             }
 
             if let Some(table) = loot_table.get(*victim) {
-                let drop_finder = get_item_drop(&RAWS.lock().unwrap(), &table.table);
+                let drop_finder = get_item_drop(
+                    &raws,
+                    &mut ecs.write_resource::<RandomNumberGenerator>(),
+                    &table.table,
+                );
                 if let Some(tag) = drop_finder {
                     if let Some(pos) = pos {
                         to_spawn.push((tag, pos.clone()));
@@ -89,7 +97,7 @@ pub fn delete_the_dead(ecs: &mut World) {
     {
         for drop in &to_spawn {
             spawn_named_item(
-                &RAWS.lock().unwrap(),
+                &raws,
                 ecs,
                 &drop.0,
                 SpawnType::AtPosition {
@@ -104,7 +112,11 @@ pub fn delete_the_dead(ecs: &mut World) {
         let death_effects = ecs.read_storage::<OnDeath>();
         if let Some(death_effect) = death_effects.get(*victim) {
             for effect in &death_effect.abilities {
-                if roll_dice(1, 100) <= (effect.chance * 100.0) as i32 {
+                if ecs
+                    .write_resource::<RandomNumberGenerator>()
+                    .roll_dice(1, 100)
+                    <= (effect.chance * 100.0) as i32
+                {
                     let map = ecs.fetch::<Map>();
                     if let Some(pos) = ecs.read_storage::<Position>().get(*victim) {
                         let spell_entity = find_spell_entity(ecs, &effect.spell).unwrap();
@@ -121,6 +133,7 @@ pub fn delete_the_dead(ecs: &mut World) {
                             }
                         };
                         add_effect(
+                            ecs,
                             None,
                             EffectType::SpellUse {
                                 spell: find_spell_entity(ecs, &effect.spell).unwrap(),
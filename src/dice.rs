@@ -0,0 +1,854 @@
+//! Dice expressions, as the raws write damage and gold: any number of dice and
+//! constants added or subtracted (`2d6+1d4+3`), dice that keep only their highest
+//! or lowest few (`4d6kh3`, `2d20kl1`), and a floor or ceiling on the total
+//! (`1d20min5`, `3d6max12`). Spaces are ignored.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rltk::RandomNumberGenerator;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The most dice one term can roll.
+const MAX_DICE: i32 = 100;
+/// The most sides a die can have.
+const MAX_SIDES: i32 = 1000;
+/// The largest constant, floor or ceiling.
+const MAX_CONSTANT: i32 = 1_000_000;
+/// The most dice and constants one expression can add up.
+const MAX_TERMS: i32 = 20;
+/// How much work `expected` may put into the exact chance of every total before
+/// it estimates the average from sample rolls instead.
+const MAX_EXACT_STEPS: u64 = 1_000_000;
+/// Rolls taken to estimate the average of a roll too big to work out exactly.
+const SAMPLE_ROLLS: i32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keep {
+    Highest(i32),
+    Lowest(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    Dice {
+        count: i32,
+        sides: i32,
+        keep: Option<Keep>,
+    },
+    Constant(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+/// A parsed dice expression. The default is one that always rolls 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiceRoll {
+    pub terms: Vec<(Sign, Term)>,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+/// Why a dice expression didn't parse. Positions count characters from the
+/// start of the expression, spaces included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiceError {
+    Empty,
+    Expected {
+        what: &'static str,
+        at: usize,
+    },
+    Unexpected {
+        found: char,
+        at: usize,
+    },
+    TooLarge {
+        what: &'static str,
+        limit: i32,
+        at: usize,
+    },
+    NoSides {
+        at: usize,
+    },
+    KeepOutOfRange {
+        keep: i32,
+        count: i32,
+        at: usize,
+    },
+    Repeated {
+        what: &'static str,
+        at: usize,
+    },
+    MinAboveMax {
+        min: i32,
+        max: i32,
+    },
+}
+
+impl fmt::Display for DiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "there's nothing to roll"),
+            Self::Expected { what, at } => write!(f, "expected {what} at position {at}"),
+            Self::Unexpected { found, at } => {
+                write!(f, "unexpected '{found}' at position {at}")
+            }
+            Self::TooLarge { what, limit, at } => {
+                write!(f, "{what} at position {at} is over the limit of {limit}")
+            }
+            Self::NoSides { at } => write!(f, "a die at position {at} has no sides"),
+            Self::KeepOutOfRange { keep, count, at } => {
+                write!(f, "can't keep {keep} of {count} dice at position {at}")
+            }
+            Self::Repeated { what, at } => write!(f, "a second {what} at position {at}"),
+            Self::MinAboveMax { min, max } => {
+                write!(f, "the minimum {min} is above the maximum {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiceError {}
+
+struct Parser<'a> {
+    chars: Vec<(usize, char)>,
+    next: usize,
+    text: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| !c.is_whitespace())
+                .collect(),
+            next: 0,
+            text,
+        }
+    }
+
+    /// The position of the next character, or the end of the text.
+    fn at(&self) -> usize {
+        self.chars
+            .get(self.next)
+            .map_or(self.text.chars().count(), |&(at, _)| at)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.next).map(|&(_, c)| c)
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        let matches = word
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.next + i).map(|&(_, n)| n) == Some(c));
+        if matches {
+            self.next += word.chars().count();
+        }
+        matches
+    }
+
+    fn number(&mut self, what: &'static str, limit: i32) -> Result<Option<i32>, DiceError> {
+        let at = self.at();
+        let mut value: Option<i64> = None;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            let next = value.unwrap_or(0) * 10 + i64::from(digit);
+            if next > i64::from(limit) {
+                return Err(DiceError::TooLarge { what, limit, at });
+            }
+            value = Some(next);
+            self.next += 1;
+        }
+        Ok(value.map(|v| v as i32))
+    }
+
+    fn required(&mut self, what: &'static str, limit: i32) -> Result<i32, DiceError> {
+        let at = self.at();
+        self.number(what, limit)?
+            .ok_or(DiceError::Expected { what, at })
+    }
+
+    fn term(&mut self) -> Result<Term, DiceError> {
+        let at = self.at();
+        let number = self.number("a constant", MAX_CONSTANT)?;
+        if !self.eat("d") {
+            return number.map(Term::Constant).ok_or(DiceError::Expected {
+                what: "a number or dice",
+                at,
+            });
+        }
+        let count = number.unwrap_or(1);
+        if count > MAX_DICE {
+            return Err(DiceError::TooLarge {
+                what: "a dice count",
+                limit: MAX_DICE,
+                at,
+            });
+        }
+        let sides_at = self.at();
+        let sides = self.required("a number of sides", MAX_SIDES)?;
+        if sides == 0 {
+            return Err(DiceError::NoSides { at: sides_at });
+        }
+        let keep_at = self.at();
+        let keep = if self.eat("kh") {
+            Some(Keep::Highest(self.required("how many to keep", MAX_DICE)?))
+        } else if self.eat("kl") {
+            Some(Keep::Lowest(self.required("how many to keep", MAX_DICE)?))
+        } else {
+            None
+        };
+        if let Some(Keep::Highest(keep) | Keep::Lowest(keep)) = keep {
+            if keep == 0 || keep > count {
+                return Err(DiceError::KeepOutOfRange {
+                    keep,
+                    count,
+                    at: keep_at,
+                });
+            }
+        }
+        Ok(Term::Dice { count, sides, keep })
+    }
+
+    fn expression(&mut self) -> Result<DiceRoll, DiceError> {
+        if self.chars.is_empty() {
+            return Err(DiceError::Empty);
+        }
+        let mut roll = DiceRoll::default();
+        let mut sign = if self.eat("-") {
+            Sign::Minus
+        } else {
+            self.eat("+");
+            Sign::Plus
+        };
+        loop {
+            let at = self.at();
+            if roll.terms.len() == MAX_TERMS as usize {
+                return Err(DiceError::TooLarge {
+                    what: "the number of terms",
+                    limit: MAX_TERMS,
+                    at,
+                });
+            }
+            roll.terms.push((sign, self.term()?));
+            sign = if self.eat("+") {
+                Sign::Plus
+            } else if self.eat("-") {
+                Sign::Minus
+            } else {
+                break;
+            };
+        }
+        loop {
+            let at = self.at();
+            if self.eat("min") {
+                if roll.min.is_some() {
+                    return Err(DiceError::Repeated { what: "min", at });
+                }
+                roll.min = Some(self.required("a minimum", MAX_CONSTANT)?);
+            } else if self.eat("max") {
+                if roll.max.is_some() {
+                    return Err(DiceError::Repeated { what: "max", at });
+                }
+                roll.max = Some(self.required("a maximum", MAX_CONSTANT)?);
+            } else {
+                break;
+            }
+        }
+        if let Some(&(at, found)) = self.chars.get(self.next) {
+            return Err(DiceError::Unexpected { found, at });
+        }
+        if let (Some(min), Some(max)) = (roll.min, roll.max) {
+            if min > max {
+                return Err(DiceError::MinAboveMax { min, max });
+            }
+        }
+        Ok(roll)
+    }
+}
+
+impl FromStr for DiceRoll {
+    type Err = DiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).expression()
+    }
+}
+
+impl fmt::Display for DiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            write!(f, "0")?;
+        }
+        for (i, (sign, term)) in self.terms.iter().enumerate() {
+            match (i, sign) {
+                (_, Sign::Minus) => write!(f, "-")?,
+                (0, Sign::Plus) => {}
+                (_, Sign::Plus) => write!(f, "+")?,
+            }
+            match term {
+                Term::Constant(value) => write!(f, "{value}")?,
+                Term::Dice { count, sides, keep } => {
+                    write!(f, "{count}d{sides}")?;
+                    match keep {
+                        Some(Keep::Highest(keep)) => write!(f, "kh{keep}")?,
+                        Some(Keep::Lowest(keep)) => write!(f, "kl{keep}")?,
+                        None => {}
+                    }
+                }
+            }
+        }
+        if let Some(min) = self.min {
+            write!(f, "min{min}")?;
+        }
+        if let Some(max) = self.max {
+            write!(f, "max{max}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for DiceRoll {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Reads an expression, or a plain number as a constant.
+impl<'de> Deserialize<'de> for DiceRoll {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DiceVisitor;
+
+        impl<'de> Visitor<'de> for DiceVisitor {
+            type Value = DiceRoll;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a dice expression such as \"2d6+1\", or a number")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<DiceRoll, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<DiceRoll, E> {
+                i32::try_from(value)
+                    .map(DiceRoll::constant)
+                    .map_err(|_| E::custom(format!("{value} is too large to roll")))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<DiceRoll, E> {
+                i32::try_from(value)
+                    .map(DiceRoll::constant)
+                    .map_err(|_| E::custom(format!("{value} is too large to roll")))
+            }
+        }
+
+        deserializer.deserialize_any(DiceVisitor)
+    }
+}
+
+impl DiceRoll {
+    /// `count` dice with `sides` sides, added up.
+    pub fn dice(count: i32, sides: i32) -> Self {
+        Self {
+            terms: vec![(
+                Sign::Plus,
+                Term::Dice {
+                    count,
+                    sides,
+                    keep: None,
+                },
+            )],
+            ..Default::default()
+        }
+    }
+
+    pub fn constant(value: i32) -> Self {
+        Self {
+            terms: vec![(Sign::Plus, Term::Constant(value))],
+            ..Default::default()
+        }
+    }
+
+    /// The same roll with `bonus` added to its constant, dropping the constant
+    /// when that leaves it at 0.
+    pub fn plus(mut self, bonus: i32) -> Self {
+        let constant = self
+            .terms
+            .iter()
+            .map(|(sign, term)| match (sign, term) {
+                (Sign::Plus, Term::Constant(value)) => *value,
+                (Sign::Minus, Term::Constant(value)) => -value,
+                _ => 0,
+            })
+            .fold(bonus, i32::saturating_add);
+        self.terms
+            .retain(|(_, term)| !matches!(term, Term::Constant(_)));
+        match constant {
+            0 if !self.terms.is_empty() => {}
+            c if c < 0 && !self.terms.is_empty() => {
+                self.terms.push((Sign::Minus, Term::Constant(-c)))
+            }
+            c => self.terms.push((Sign::Plus, Term::Constant(c))),
+        }
+        self
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> i32 {
+        let total = self
+            .terms
+            .iter()
+            .map(|(sign, term)| {
+                let value = match *term {
+                    Term::Constant(value) => value,
+                    Term::Dice {
+                        count,
+                        sides,
+                        keep: None,
+                    } => rng.roll_dice(count, sides),
+                    Term::Dice {
+                        count,
+                        sides,
+                        keep: Some(keep),
+                    } => {
+                        let mut rolls: Vec<i32> =
+                            (0..count).map(|_| rng.roll_dice(1, sides)).collect();
+                        rolls.sort_unstable();
+                        match keep {
+                            Keep::Highest(keep) => rolls.iter().rev().take(keep as usize).sum(),
+                            Keep::Lowest(keep) => rolls.iter().take(keep as usize).sum(),
+                        }
+                    }
+                };
+                match sign {
+                    Sign::Plus => value,
+                    Sign::Minus => -value,
+                }
+            })
+            .fold(0, i32::saturating_add);
+        self.clamp(total)
+    }
+
+    fn clamp(&self, total: i32) -> i32 {
+        let total = self.min.map_or(total, |min| total.max(min));
+        self.max.map_or(total, |max| total.min(max))
+    }
+
+    /// The lowest and highest totals the roll can come to.
+    pub fn range(&self) -> (i32, i32) {
+        let (low, high) = self
+            .terms
+            .iter()
+            .map(|(sign, term)| {
+                let (low, high) = term_range(term);
+                match sign {
+                    Sign::Plus => (low, high),
+                    Sign::Minus => (-high, -low),
+                }
+            })
+            .fold((0, 0), |(low, high), (l, h)| {
+                (low.saturating_add(l), high.saturating_add(h))
+            });
+        (self.clamp(low), self.clamp(high))
+    }
+
+    /// The average total over many rolls.
+    pub fn expected(&self) -> f32 {
+        if self.min.is_none() && self.max.is_none() {
+            return self
+                .terms
+                .iter()
+                .map(|(sign, term)| match sign {
+                    Sign::Plus => term_mean(term),
+                    Sign::Minus => -term_mean(term),
+                })
+                .sum::<f64>() as f32;
+        }
+        // A clamp moves the average by however much of the roll it cuts off, so
+        // that needs the chance of every total.
+        if self.exact_steps() > MAX_EXACT_STEPS {
+            return self.sampled_mean();
+        }
+        let distribution = self
+            .terms
+            .iter()
+            .map(|(sign, term)| {
+                let distribution = term_distribution(term);
+                match sign {
+                    Sign::Plus => distribution,
+                    Sign::Minus => distribution.negated(),
+                }
+            })
+            .fold(Distribution::constant(0), |total, d| total.add(&d));
+        distribution
+            .chances
+            .iter()
+            .enumerate()
+            .map(|(i, chance)| f64::from(self.clamp(distribution.lowest + i as i32)) * chance)
+            .sum::<f64>() as f32
+    }
+
+    /// Roughly how many steps working out the chance of every total takes: building
+    /// each term's chances, then adding them to the running total.
+    fn exact_steps(&self) -> u64 {
+        let mut steps: u64 = 0;
+        let mut totals: u64 = 1;
+        for (_, term) in &self.terms {
+            let (low, high) = term_range(term);
+            let width = (high - low) as u64 + 1;
+            if let Term::Dice { count, sides, keep } = *term {
+                let dice = count as u64 + 1;
+                let most = (kept(count, keep) * sides) as u64 + 1;
+                steps = steps.saturating_add(sides as u64 * dice * dice * most);
+            }
+            steps = steps.saturating_add(totals * width);
+            totals += width - 1;
+        }
+        steps
+    }
+
+    /// The average of a roll too big to work out exactly, from a fixed run of
+    /// sample rolls so it reads the same every time it's shown.
+    fn sampled_mean(&self) -> f32 {
+        let mut rng = RandomNumberGenerator::seeded(0);
+        let total: f64 = (0..SAMPLE_ROLLS)
+            .map(|_| f64::from(self.roll(&mut rng)))
+            .sum();
+        (total / f64::from(SAMPLE_ROLLS)) as f32
+    }
+
+    /// The average and range, as tooltips show them: `avg 7 (2–12)`, or just the
+    /// number for a roll that always comes to the same total.
+    pub fn summary(&self) -> String {
+        let (low, high) = self.range();
+        if low == high {
+            return low.to_string();
+        }
+        let expected = self.expected();
+        if (expected - expected.round()).abs() < 0.05 {
+            format!("avg {} ({low}–{high})", expected.round())
+        } else {
+            format!("avg {expected:.1} ({low}–{high})")
+        }
+    }
+}
+
+fn kept(count: i32, keep: Option<Keep>) -> i32 {
+    match keep {
+        Some(Keep::Highest(keep) | Keep::Lowest(keep)) => keep,
+        None => count,
+    }
+}
+
+fn term_range(term: &Term) -> (i32, i32) {
+    match *term {
+        Term::Constant(value) => (value, value),
+        Term::Dice { count, sides, keep } => {
+            let kept = kept(count, keep);
+            (kept, kept * sides)
+        }
+    }
+}
+
+fn binomial(n: i32, k: i32) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * f64::from(n - i) / f64::from(i + 1))
+}
+
+fn term_mean(term: &Term) -> f64 {
+    match *term {
+        Term::Constant(value) => f64::from(value),
+        Term::Dice {
+            count,
+            sides,
+            keep: None,
+        } => f64::from(count) * f64::from(sides + 1) / 2.0,
+        Term::Dice {
+            count,
+            sides,
+            keep: Some(keep),
+        } => {
+            // The i-th highest die is at least x when at least i dice are
+            let highest = |keep: i32| -> f64 {
+                let mut total = 0.0;
+                for x in 1..=sides {
+                    let p = f64::from(sides - x + 1) / f64::from(sides);
+                    let mut ways = 1.0;
+                    let exactly: Vec<f64> = (0..=count)
+                        .map(|j| {
+                            if j > 0 {
+                                ways *= f64::from(count - j + 1) / f64::from(j);
+                            }
+                            ways * p.powi(j) * (1.0 - p).powi(count - j)
+                        })
+                        .collect();
+                    let mut at_least = 0.0;
+                    for j in (1..=count).rev() {
+                        at_least += exactly[j as usize];
+                        if j <= keep {
+                            total += at_least;
+                        }
+                    }
+                }
+                total
+            };
+            match keep {
+                Keep::Highest(keep) => highest(keep),
+                // Flipping every die turns the lowest into the highest
+                Keep::Lowest(keep) => f64::from(keep * (sides + 1)) - highest(keep),
+            }
+        }
+    }
+}
+
+/// The chance of each total from `lowest` upwards.
+struct Distribution {
+    lowest: i32,
+    chances: Vec<f64>,
+}
+
+impl Distribution {
+    fn constant(value: i32) -> Self {
+        Self {
+            lowest: value,
+            chances: vec![1.0],
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        let mut chances = vec![0.0; self.chances.len() + other.chances.len() - 1];
+        for (i, a) in self.chances.iter().enumerate() {
+            for (j, b) in other.chances.iter().enumerate() {
+                chances[i + j] += a * b;
+            }
+        }
+        Self {
+            lowest: self.lowest + other.lowest,
+            chances,
+        }
+    }
+
+    fn negated(mut self) -> Self {
+        self.lowest = -(self.lowest + self.chances.len() as i32 - 1);
+        self.chances.reverse();
+        self
+    }
+}
+
+fn term_distribution(term: &Term) -> Distribution {
+    let Term::Dice { count, sides, keep } = *term else {
+        return Distribution::constant(term_range(term).0);
+    };
+    let kept = kept(count, keep);
+    // Hands out the dice one face at a time, starting with the faces that are
+    // kept first, so the first `kept` dice handed out are the ones that count.
+    // `ways[used][total]` counts the rolls of `used` dice that come to `total`.
+    let faces: Vec<i32> = match keep {
+        Some(Keep::Lowest(_)) => (1..=sides).collect(),
+        _ => (1..=sides).rev().collect(),
+    };
+    let most = (kept * sides) as usize;
+    let mut ways = vec![vec![0.0; most + 1]; count as usize + 1];
+    ways[0][0] = 1.0;
+    for face in faces {
+        let mut next = vec![vec![0.0; most + 1]; count as usize + 1];
+        for used in 0..=count {
+            for total in 0..=most {
+                let w = ways[used as usize][total];
+                if w == 0.0 {
+                    continue;
+                }
+                for showing in 0..=count - used {
+                    let counted = showing.min((kept - used).max(0));
+                    next[(used + showing) as usize][total + (counted * face) as usize] +=
+                        w * binomial(count - used, showing);
+                }
+            }
+        }
+        ways = next;
+    }
+    let rolls = f64::from(sides).powi(count);
+    Distribution {
+        lowest: kept,
+        chances: ways[count as usize][kept as usize..]
+            .iter()
+            .map(|w| w / rolls)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rltk::RandomNumberGenerator;
+
+    use super::{DiceError, DiceRoll, Keep, Sign, Term, MAX_CONSTANT};
+
+    fn parse(text: &str) -> DiceRoll {
+        text.parse()
+            .unwrap_or_else(|e| panic!("{text} didn't parse: {e}"))
+    }
+
+    #[test]
+    fn expressions_read_back_as_written() {
+        for text in [
+            "1d8",
+            "2d6+1d4+3",
+            "1d8-1",
+            "4d6kh3",
+            "2d20kl1",
+            "1d20min5max18",
+            "7",
+            "-2+1d4",
+        ] {
+            assert_eq!(parse(text).to_string(), text);
+        }
+        assert_eq!(parse(" 2d6 + 1 min 3 ").to_string(), "2d6+1min3");
+        assert_eq!(parse("d6").to_string(), "1d6");
+    }
+
+    #[test]
+    fn terms_keep_their_signs() {
+        let roll = parse("3d6kh2-1d4+2");
+
+        assert_eq!(
+            roll.terms,
+            vec![
+                (
+                    Sign::Plus,
+                    Term::Dice {
+                        count: 3,
+                        sides: 6,
+                        keep: Some(Keep::Highest(2))
+                    }
+                ),
+                (
+                    Sign::Minus,
+                    Term::Dice {
+                        count: 1,
+                        sides: 4,
+                        keep: None
+                    }
+                ),
+                (Sign::Plus, Term::Constant(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn mistakes_are_reported_where_they_are() {
+        let error = |text: &str| text.parse::<DiceRoll>().unwrap_err();
+
+        assert_eq!(error(""), DiceError::Empty);
+        assert_eq!(
+            error("1d"),
+            DiceError::Expected {
+                what: "a number of sides",
+                at: 2
+            }
+        );
+        assert_eq!(error("2d6*2"), DiceError::Unexpected { found: '*', at: 3 });
+        assert_eq!(error("1d0"), DiceError::NoSides { at: 2 });
+        assert_eq!(
+            error("2d6kh3"),
+            DiceError::KeepOutOfRange {
+                keep: 3,
+                count: 2,
+                at: 3
+            }
+        );
+        assert_eq!(
+            error("1d20min9max3"),
+            DiceError::MinAboveMax { min: 9, max: 3 }
+        );
+        assert!(matches!(error("1000d6"), DiceError::TooLarge { .. }));
+        assert_eq!(error("1d6 x").to_string(), "unexpected 'x' at position 4");
+    }
+
+    #[test]
+    fn ranges_and_averages() {
+        assert_eq!(parse("2d6").range(), (2, 12));
+        assert_eq!(parse("2d6").summary(), "avg 7 (2–12)");
+        assert_eq!(parse("1d8-1").summary(), "avg 3.5 (0–7)");
+        assert_eq!(parse("1d20-1d4").range(), (-3, 19));
+        assert_eq!(parse("5").summary(), "5");
+        // Counted over all 216 ways three d6 can land
+        assert!((parse("3d6kh2").expected() - 1827.0 / 216.0).abs() < 1e-4);
+        assert!((parse("2d20kl1").expected() - 7.175).abs() < 1e-4);
+        // Every roll under 3 counts as 3: (3 + 3 + 3 + 4 + 5 + 6) / 6
+        assert!((parse("1d6min3").expected() - 4.0).abs() < 1e-4);
+        assert_eq!(parse("1d6min3max5").range(), (3, 5));
+    }
+
+    #[test]
+    fn rolls_stay_in_range() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        for text in ["2d6+1d4+3", "4d6kh3", "2d20kl1-2", "1d20min5max18"] {
+            let roll = parse(text);
+            let (low, high) = roll.range();
+            for _ in 0..200 {
+                let total = roll.roll(&mut rng);
+                assert!((low..=high).contains(&total), "{text} rolled {total}");
+            }
+        }
+    }
+
+    #[test]
+    fn huge_rolls_are_estimated_instead_of_worked_out() {
+        let clamped = parse("100d1000min1");
+        assert!((clamped.expected() - 50_050.0).abs() < 1000.0);
+        assert!(clamped.summary().ends_with("(100–100000)"));
+
+        let kept = parse("100d1000kh50");
+        let kept_clamped = parse("100d1000kh50min1");
+        assert!((kept.expected() - kept_clamped.expected()).abs() < 1000.0);
+    }
+
+    #[test]
+    fn long_expressions_are_refused_and_totals_never_overflow() {
+        let longest = vec!["1000000"; 20].join("+");
+        let too_long = vec!["1000000"; 21].join("+");
+
+        assert_eq!(parse(&longest).range(), (20_000_000, 20_000_000));
+        assert!(matches!(
+            too_long.parse::<DiceRoll>(),
+            Err(DiceError::TooLarge {
+                what: "the number of terms",
+                ..
+            })
+        ));
+
+        let built = DiceRoll {
+            terms: vec![(Sign::Plus, Term::Constant(MAX_CONSTANT)); 3000],
+            min: None,
+            max: None,
+        };
+        let mut rng = RandomNumberGenerator::seeded(1);
+        assert_eq!(built.range(), (i32::MAX, i32::MAX));
+        assert_eq!(built.roll(&mut rng), i32::MAX);
+        assert_eq!(built.plus(1).range(), (i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn bonuses_fold_into_the_constant() {
+        assert_eq!(parse("1d8").plus(2).to_string(), "1d8+2");
+        assert_eq!(parse("1d8-1").plus(1).to_string(), "1d8");
+        assert_eq!(parse("1d8+1").plus(-3).to_string(), "1d8-2");
+    }
+
+    #[test]
+    fn numbers_and_strings_both_deserialize() {
+        let number: DiceRoll = serde_json::from_str("6").unwrap();
+        let text: DiceRoll = serde_json::from_str("\"2d4+1\"").unwrap();
+
+        assert_eq!(number, DiceRoll::constant(6));
+        assert_eq!(serde_json::to_string(&text).unwrap(), "\"2d4+1\"");
+        assert!(serde_json::from_str::<DiceRoll>("\"2d\"").is_err());
+    }
+}
@@ -1,66 +1,141 @@
-use rltk::{to_cp437, Point, BLACK, BLUE, GOLD, GREEN, MAGENTA, ORANGE};
-use specs::saveload::{MarkedBuilder, SimpleMarker};
-use specs::{Builder, Entity, World, WorldExt};
+use rltk::{to_cp437, Point, BLACK, BLUE, GOLD, GRAY, GREEN, MAGENTA, ORANGE};
+use specs::{Entity, Join, World, WorldExt};
 
 use crate::components::{
-    Attributes, Confusion, DamageOverTime, Duration, EquipmentChanged, Name, Player, Pools,
-    SerializeMe, Skills, Slow, StatusEffect,
+    DamageResponse, DamageType, Equipped, Name, Player, Pools, Resistances, StatusEffect,
 };
 use crate::effects::targeting::entity_position;
 use crate::effects::{add_effect, EffectSpawner, EffectType, Targets};
-use crate::gamelog;
-use crate::gamesystem::{mana_at_level, player_hp_at_level};
+use crate::gamelog::{self, GameEvents, GameLog};
+use crate::level_up::PendingLevelUps;
 use crate::map::Map;
-use crate::rng::roll_dice;
+
+/// How `target` takes damage of `damage_type`, from its own nature, the armour
+/// it wears and the status effects it's under.
+pub fn damage_response(
+    ecs: &World,
+    target: Entity,
+    damage_type: DamageType,
+) -> Option<DamageResponse> {
+    if damage_type == DamageType::Untyped {
+        return None;
+    }
+    let resistances = ecs.read_storage::<Resistances>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let worn = (&equipped, &resistances)
+        .join()
+        .filter(|(equipped, _)| equipped.owner == target)
+        .map(|(_, resistances)| resistances);
+    let under = (&statuses, &resistances)
+        .join()
+        .filter(|(status, _)| status.target == target)
+        .map(|(_, resistances)| resistances);
+    DamageResponse::combine(
+        resistances
+            .get(target)
+            .into_iter()
+            .chain(worn)
+            .chain(under)
+            .filter_map(|resistances| resistances.damage.get(&damage_type).copied()),
+    )
+}
+
+fn log_damage_response(
+    ecs: &World,
+    target: Entity,
+    damage_type: DamageType,
+    response: DamageResponse,
+) {
+    let names = ecs.read_storage::<Name>();
+    let Some(name) = names.get(target) else {
+        return;
+    };
+    let (color, text) = match response {
+        DamageResponse::Resistant => (GRAY, format!("resists the {}.", damage_type.noun())),
+        DamageResponse::Immune => (GRAY, format!("is immune to {}!", damage_type.noun())),
+        DamageResponse::Vulnerable => (ORANGE, format!("is vulnerable to {}!", damage_type.noun())),
+    };
+    gamelog::Logger::new()
+        .npc_name(&name.name)
+        .color(color)
+        .append(text)
+        .log(&mut ecs.write_resource::<GameLog>());
+}
 
 pub fn inflict_damage(ecs: &World, damage: &EffectSpawner, target: Entity) {
-    let mut pools = ecs.write_storage::<Pools>();
-    let player_entity = ecs.fetch::<Entity>();
-    if let Some(pool) = pools.get_mut(target) {
-        if pool.god_mode {
+    if let Some(creator) = damage.creator {
+        if creator == target {
             return;
         }
+    }
+    let EffectType::Damage {
+        amount,
+        damage_type,
+    } = damage.effect_type
+    else {
+        return;
+    };
+    if ecs
+        .read_storage::<Pools>()
+        .get(target)
+        .map_or(true, |pool| pool.god_mode)
+    {
+        return;
+    }
 
-        if let Some(creator) = damage.creator {
-            if creator == target {
-                return;
-            }
-        }
-
-        if let EffectType::Damage { amount } = damage.effect_type {
-            pool.hit_points.current -= amount;
+    let response = damage_response(ecs, target, damage_type);
+    let amount = response.map_or(amount, |response| response.apply(amount));
+    if let Some(response) = response {
+        log_damage_response(ecs, target, damage_type, response);
+    }
+    if amount < 1 {
+        return;
+    }
 
-            add_effect(None, EffectType::Bloodstain, Targets::Single { target });
-            add_effect(
-                None,
-                EffectType::Particle {
-                    glyph: to_cp437('‼'),
-                    fg: ORANGE.into(),
-                    bg: BLACK.into(),
-                    lifespan: 200.0,
-                },
-                Targets::Single { target },
-            );
+    let mut pools = ecs.write_storage::<Pools>();
+    let player_entity = ecs.fetch::<Entity>();
+    let pool = pools.get_mut(target).unwrap();
+    pool.hit_points.current -= amount;
 
-            if target == *player_entity {
-                gamelog::record_event("Damage Taken", amount);
-            }
+    add_effect(
+        ecs,
+        None,
+        EffectType::Bloodstain,
+        Targets::Single { target },
+    );
+    add_effect(
+        ecs,
+        None,
+        EffectType::Particle {
+            glyph: to_cp437('‼'),
+            fg: ORANGE.into(),
+            bg: BLACK.into(),
+            lifespan: 200.0,
+        },
+        Targets::Single { target },
+    );
 
-            if let Some(creator) = damage.creator {
-                if creator == *player_entity {
-                    gamelog::record_event("Damage Inflicted", amount);
-                }
-            }
+    if target == *player_entity {
+        ecs.write_resource::<GameEvents>()
+            .record("Damage Taken", amount);
+    }
 
-            if pool.hit_points.current < 1 {
-                add_effect(
-                    damage.creator,
-                    EffectType::EntityDeath,
-                    Targets::Single { target },
-                );
-            }
+    if let Some(creator) = damage.creator {
+        if creator == *player_entity {
+            ecs.write_resource::<GameEvents>()
+                .record("Damage Inflicted", amount);
         }
     }
+
+    if pool.hit_points.current < 1 {
+        add_effect(
+            ecs,
+            damage.creator,
+            EffectType::EntityDeath,
+            Targets::Single { target },
+        );
+    }
 }
 
 pub fn bloodstain(ecs: &World, tile_idx: i32) {
@@ -73,10 +148,11 @@ pub fn death(ecs: &World, effect: &EffectSpawner, target: Entity) {
     let mut gold_gain = 0.0_f32;
 
     let mut pools = ecs.write_storage::<Pools>();
-    let mut attributes = ecs.write_storage::<Attributes>();
 
     if let Some(pos) = entity_position(ecs, target) {
-        crate::spatial::remove_entity(target, pos as usize);
+        ecs.fetch_mut::<Map>()
+            .spatial
+            .remove_entity(target, pos as usize);
     }
 
     if let Some(source) = effect.creator {
@@ -88,7 +164,6 @@ pub fn death(ecs: &World, effect: &EffectSpawner, target: Entity) {
 
             if xp_gain != 0 || gold_gain != 0.0 {
                 let player_stats = pools.get_mut(source).unwrap();
-                let player_attributes = attributes.get_mut(source).unwrap();
                 player_stats.xp += xp_gain;
                 player_stats.gold += gold_gain;
                 if player_stats.xp >= player_stats.level * 1000 {
@@ -98,68 +173,17 @@ pub fn death(ecs: &World, effect: &EffectSpawner, target: Entity) {
                         .color(MAGENTA)
                         .append("Congratulations, you are now level")
                         .append(format!("{}", player_stats.level))
-                        .log();
-
-                    let attr_to_boost = roll_dice(1, 4);
-                    match attr_to_boost {
-                        1 => {
-                            player_attributes.might.base += 1;
-                            gamelog::Logger::new()
-                                .color(GREEN)
-                                .append("You feel stronger!")
-                                .log();
-                        }
-                        2 => {
-                            player_attributes.fitness.base += 1;
-                            gamelog::Logger::new()
-                                .color(GREEN)
-                                .append("You feel healthier!")
-                                .log();
-                        }
-                        3 => {
-                            player_attributes.quickness.base += 1;
-                            gamelog::Logger::new()
-                                .color(GREEN)
-                                .append("You feel quicker!")
-                                .log();
-                        }
-                        4 => {
-                            player_attributes.intelligence.base += 1;
-                            gamelog::Logger::new()
-                                .color(GREEN)
-                                .append("You feel smarter!")
-                                .log();
-                        }
-                        _ => unreachable!(),
-                    }
+                        .log(&mut ecs.write_resource::<GameLog>());
 
-                    let mut skills = ecs.write_storage::<Skills>();
-                    let player_skills = skills.get_mut(*ecs.fetch::<Entity>()).unwrap();
-                    for sk in &mut player_skills.skills {
-                        *sk.1 += 1;
-                    }
-
-                    ecs.write_storage::<EquipmentChanged>()
-                        .insert(*ecs.fetch::<Entity>(), EquipmentChanged {})
-                        .expect("Insert failed");
-
-                    player_stats.hit_points.max = player_hp_at_level(
-                        player_attributes.fitness.base + player_attributes.fitness.modifiers,
-                        player_stats.level,
-                    );
-                    player_stats.hit_points.current = player_stats.hit_points.max;
-                    player_stats.mana.max = mana_at_level(
-                        player_attributes.intelligence.base
-                            + player_attributes.intelligence.modifiers,
-                        player_stats.level,
-                    );
-                    player_stats.mana.current = player_stats.mana.max;
+                    // The player picks what improves once the turn has played out
+                    ecs.write_resource::<PendingLevelUps>().0 += 1;
 
                     let player_pos = ecs.fetch::<Point>();
                     let map = ecs.fetch_mut::<Map>();
                     for i in 0..10 {
                         if player_pos.y - i > 1 {
                             add_effect(
+                                ecs,
                                 None,
                                 EffectType::Particle {
                                     glyph: to_cp437('░'),
@@ -186,6 +210,7 @@ pub fn heal_damage(ecs: &World, heal: &EffectSpawner, target: Entity) {
             pool.hit_points.current =
                 i32::min(pool.hit_points.max, pool.hit_points.current + amount);
             add_effect(
+                ecs,
                 None,
                 EffectType::Particle {
                     glyph: to_cp437('‼'),
@@ -199,47 +224,13 @@ pub fn heal_damage(ecs: &World, heal: &EffectSpawner, target: Entity) {
     }
 }
 
-pub fn add_confusion(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::Confusion { turns } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(Confusion {})
-            .with(Duration { turns: *turns })
-            .with(Name {
-                name: "Confusion".into(),
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-    }
-}
-
-pub fn attribute_effect(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::AttributeEffect {
-        bonus,
-        name,
-        duration,
-    } = &effect.effect_type
-    {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(bonus.clone())
-            .with(Duration { turns: *duration })
-            .with(Name { name: name.clone() })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-
-        ecs.write_storage::<EquipmentChanged>()
-            .insert(target, EquipmentChanged {})
-            .expect("Insert failed");
-    }
-}
-
 pub fn restore_mana(ecs: &World, mana: &EffectSpawner, target: Entity) {
     let mut pools = ecs.write_storage::<Pools>();
     if let Some(pool) = pools.get_mut(target) {
         if let EffectType::Mana { amount } = mana.effect_type {
             pool.mana.current = i32::min(pool.mana.max, pool.mana.current + amount);
             add_effect(
+                ecs,
                 None,
                 EffectType::Particle {
                     glyph: to_cp437('‼'),
@@ -253,38 +244,74 @@ pub fn restore_mana(ecs: &World, mana: &EffectSpawner, target: Entity) {
     }
 }
 
-pub fn slow(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::Slow { inititive_penalty } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(Slow {
-                initiative_penalty: *inititive_penalty,
-            })
-            .with(Duration { turns: 5 })
-            .with(if *inititive_penalty > 0.0 {
-                Name {
-                    name: "Slowed".into(),
-                }
-            } else {
-                Name {
-                    name: "Hasted".into(),
-                }
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-    }
-}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::components::{DamageResponse, DamageType};
+    use crate::effects::{add_effect, run_effects_queue, EffectType, Targets};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn damage_types_meet_resistances_immunities_and_vulnerabilities() {
+        let mut scenario = Scenario::new(
+            "
+            #######
+            #@....#
+            #######
+            ",
+        );
+        let player = scenario.player();
+        let cube = scenario.spawn("Gelatinous Cube", 3, 1);
+        let golem = scenario.spawn("Rock Golem", 5, 1);
+        let hit = |scenario: &mut Scenario, target, amount, damage_type| {
+            add_effect(
+                &scenario.ecs,
+                None,
+                EffectType::Damage {
+                    amount,
+                    damage_type,
+                },
+                Targets::Single { target },
+            );
+            run_effects_queue(&mut scenario.ecs);
+            scenario.hit_points(target)
+        };
+
+        let cube_hp = scenario.hit_points(cube);
+        assert_eq!(hit(&mut scenario, cube, 4, DamageType::Acid), cube_hp);
+        assert!(scenario.log_contains("is immune to acid damage!"));
+        assert_eq!(hit(&mut scenario, cube, 4, DamageType::Fire), cube_hp - 4);
+
+        let golem_hp = scenario.hit_points(golem);
+        assert_eq!(
+            hit(&mut scenario, golem, 4, DamageType::Slashing),
+            golem_hp - 2
+        );
+        assert_eq!(
+            hit(&mut scenario, golem, 2, DamageType::Bludgeoning),
+            golem_hp - 6
+        );
 
-pub fn damage_over_time(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::DamageOverTime { damage } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(DamageOverTime { damage: *damage })
-            .with(Duration { turns: 5 })
-            .with(Name {
-                name: "Damage Over Time".into(),
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
+        // Armour resists for its wearer, and a status effect can undo that
+        scenario.equip("Dwarf-Steel Shirt", player);
+        let player_hp = scenario.hit_points(player);
+        assert_eq!(
+            hit(&mut scenario, player, 4, DamageType::Fire),
+            player_hp - 2
+        );
+        add_effect(
+            &scenario.ecs,
+            None,
+            EffectType::Resistances {
+                damage: HashMap::from([(DamageType::Fire, DamageResponse::Vulnerable)]),
+            },
+            Targets::Single { target: player },
+        );
+        run_effects_queue(&mut scenario.ecs);
+        assert_eq!(
+            hit(&mut scenario, player, 4, DamageType::Fire),
+            player_hp - 6
+        );
     }
 }
@@ -1,31 +1,30 @@
-use std::collections::{HashSet, VecDeque};
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use lazy_static::lazy_static;
 use rltk::{FontCharType, Point, RGB};
-use specs::{Entity, World};
+use specs::{Entity, World, WorldExt};
 
+pub use status::add_status;
 pub use targeting::*;
 
-use crate::components::AttributeBonus;
+use crate::components::{AttributeBonus, DamageResponse, DamageType, Skill};
+use crate::map::Map;
 
 mod damage;
 mod hunger;
 mod movement;
 mod particles;
+mod status;
 mod targeting;
+mod training;
 mod triggers;
 
-lazy_static! {
-    pub static ref EFFECT_QUEUE: Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
-}
-
 pub enum EffectType {
     Bloodstain,
     EntityDeath,
     WellFed,
     Damage {
         amount: i32,
+        damage_type: DamageType,
     },
     Healing {
         amount: i32,
@@ -50,6 +49,23 @@ pub enum EffectType {
     },
     DamageOverTime {
         damage: i32,
+        damage_type: DamageType,
+    },
+    /// A spell of resisting (or being weak to) some damage types for a while.
+    Resistances {
+        damage: HashMap<DamageType, DamageResponse>,
+    },
+    /// A status from the raws, by name.
+    Status {
+        name: String,
+    },
+    /// Ends the statuses carrying this tag.
+    Dispel {
+        tag: String,
+    },
+    /// One use of a skill, towards its next rank.
+    TrainSkill {
+        skill: Skill,
     },
     TeleportTo {
         x: i32,
@@ -92,18 +108,33 @@ pub struct EffectSpawner {
     dedupe: HashSet<Entity>,
 }
 
-pub fn add_effect(creator: Option<Entity>, effect_type: EffectType, targets: Targets) {
-    EFFECT_QUEUE.lock().unwrap().push_back(EffectSpawner {
-        creator,
-        effect_type,
-        targets,
-        dedupe: HashSet::new(),
-    });
+/// Effects waiting to be applied. Systems push onto it and `run_effects_queue`
+/// drains it once they have all run.
+#[derive(Default)]
+pub struct EffectQueue {
+    queue: VecDeque<EffectSpawner>,
+}
+
+impl EffectQueue {
+    pub fn add(&mut self, creator: Option<Entity>, effect_type: EffectType, targets: Targets) {
+        self.queue.push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+            dedupe: HashSet::new(),
+        });
+    }
+}
+
+/// Queues an effect on the world's `EffectQueue`, for code that holds the whole world.
+pub fn add_effect(ecs: &World, creator: Option<Entity>, effect_type: EffectType, targets: Targets) {
+    ecs.write_resource::<EffectQueue>()
+        .add(creator, effect_type, targets);
 }
 
 pub fn run_effects_queue(ecs: &mut World) {
     loop {
-        let effect = EFFECT_QUEUE.lock().unwrap().pop_front();
+        let effect = ecs.write_resource::<EffectQueue>().queue.pop_front();
         if let Some(mut effect) = effect {
             target_applicator(ecs, &mut effect);
         } else {
@@ -151,19 +182,26 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
         EffectType::EntityDeath => damage::death(ecs, effect, target),
         EffectType::WellFed => hunger::well_fed(ecs, effect, target),
         EffectType::Healing { .. } => damage::heal_damage(ecs, effect, target),
-        EffectType::Confusion { .. } => damage::add_confusion(ecs, effect, target),
+        EffectType::Confusion { .. }
+        | EffectType::AttributeEffect { .. }
+        | EffectType::Slow { .. }
+        | EffectType::DamageOverTime { .. }
+        | EffectType::Resistances { .. }
+        | EffectType::Status { .. } => status::apply_status(ecs, effect, target),
+        EffectType::Dispel { .. } => status::dispel(ecs, effect, target),
         EffectType::TeleportTo { .. } => movement::apply_teleport(ecs, effect, target),
-        EffectType::AttributeEffect { .. } => damage::attribute_effect(ecs, effect, target),
         EffectType::Mana { .. } => damage::restore_mana(ecs, effect, target),
-        EffectType::Slow { .. } => damage::slow(ecs, effect, target),
-        EffectType::DamageOverTime { .. } => damage::damage_over_time(ecs, effect, target),
+        EffectType::TrainSkill { .. } => training::train_skill(ecs, effect, target),
         _ => {}
     }
 }
 
 fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx: i32) {
     if tile_effect_hits_entities(&effect.effect_type) {
-        let content = crate::spatial::get_tile_content_clone(tile_idx as usize);
+        let content = ecs
+            .fetch::<Map>()
+            .spatial
+            .get_tile_content_clone(tile_idx as usize);
         content
             .iter()
             .for_each(|entity| affect_entity(ecs, effect, *entity));
@@ -189,5 +227,8 @@ const fn tile_effect_hits_entities(effect: &EffectType) -> bool {
             | EffectType::AttributeEffect { .. }
             | EffectType::Slow { .. }
             | EffectType::DamageOverTime { .. }
+            | EffectType::Resistances { .. }
+            | EffectType::Status { .. }
+            | EffectType::Dispel { .. }
     )
 }
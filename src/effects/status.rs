@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rltk::{to_cp437, CYAN, GRAY, RGB};
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use specs::{Builder, Entity, Join, World, WorldExt};
+
+use crate::components::{
+    AttributeBonus, AttributeKind, Confusion, DamageOverTime, DamageResponse, DamageType, Duration,
+    EquipmentChanged, HealingOverTime, Name, Resistances, SerializeMe, Slow, StackingPolicy,
+    StatusEffect, StatusImmunities, StatusInfo,
+};
+use crate::effects::{EffectSpawner, EffectType};
+use crate::gamelog::{self, GameLog};
+use crate::raws::rawmaster::RawMaster;
+use crate::raws::Status;
+
+/// The raws status called `name`, or a plain one if the raws don't have it.
+fn raws_status(ecs: &World, name: &str, duration: i32) -> Status {
+    ecs.fetch::<Arc<RawMaster>>()
+        .status(name)
+        .cloned()
+        .unwrap_or_else(|| Status::plain(name, duration))
+}
+
+/// The raws status a damage over time effect of `damage_type` stands for. Physical
+/// and untyped harm bleeds.
+const fn damage_over_time_status(damage_type: DamageType) -> &'static str {
+    match damage_type {
+        DamageType::Poison => "Poisoned",
+        DamageType::Fire => "Burning",
+        DamageType::Acid => "Corroding",
+        DamageType::Cold => "Frostbitten",
+        DamageType::Lightning => "Shocked",
+        DamageType::Untyped
+        | DamageType::Slashing
+        | DamageType::Piercing
+        | DamageType::Bludgeoning => "Bleeding",
+    }
+}
+
+/// Names a resistance status after what it does, e.g. "Resist Fire, Weak Cold".
+fn resistances_name(damage: &HashMap<DamageType, DamageResponse>) -> String {
+    let mut parts: Vec<String> = damage
+        .iter()
+        .map(|(damage_type, response)| {
+            let response = match response {
+                DamageResponse::Resistant => "Resist",
+                DamageResponse::Immune => "Immune",
+                DamageResponse::Vulnerable => "Weak",
+            };
+            format!("{response} {damage_type:?}")
+        })
+        .collect();
+    parts.sort();
+    parts.join(", ")
+}
+
+/// The status an effect puts on its target. Effects that carry their own numbers,
+/// such as `confusion` or `slow`, take everything else from the raws status they
+/// stand for.
+fn status_for(ecs: &World, effect_type: &EffectType) -> Option<Status> {
+    let status = match effect_type {
+        EffectType::Status { name } => ecs.fetch::<Arc<RawMaster>>().status(name)?.clone(),
+        EffectType::Confusion { turns } => {
+            let mut status = raws_status(ecs, "Confused", *turns);
+            status.duration = *turns;
+            status.modifiers.skips_turn = true;
+            status
+        }
+        EffectType::Slow { inititive_penalty } => {
+            let name = if *inititive_penalty > 0.0 {
+                "Slowed"
+            } else {
+                "Hasted"
+            };
+            let mut status = raws_status(ecs, name, 5);
+            status.modifiers.initiative_penalty = *inititive_penalty;
+            status
+        }
+        EffectType::DamageOverTime {
+            damage,
+            damage_type,
+        } => {
+            let mut status = raws_status(ecs, damage_over_time_status(*damage_type), 5);
+            status.periodic.damage = *damage;
+            status.periodic.damage_type = *damage_type;
+            status
+        }
+        EffectType::Resistances { damage } => {
+            let mut status = Status::plain(&resistances_name(damage), 10);
+            status.tags.push("magic".to_string());
+            status.modifiers.resistances = damage.clone();
+            status
+        }
+        EffectType::AttributeEffect {
+            bonus,
+            name,
+            duration,
+        } => {
+            let mut status = Status::plain(name, *duration);
+            status.tags.push("magic".to_string());
+            status.modifiers.attributes = [
+                (AttributeKind::Might, bonus.might),
+                (AttributeKind::Fitness, bonus.fitness),
+                (AttributeKind::Quickness, bonus.quickness),
+                (AttributeKind::Intelligence, bonus.intelligence),
+            ]
+            .into_iter()
+            .filter_map(|(kind, bonus)| Some((kind, bonus?)))
+            .collect();
+            status
+        }
+        _ => return None,
+    };
+    Some(status)
+}
+
+fn log_status(ecs: &World, target: Entity, color: (u8, u8, u8), text: String) {
+    let names = ecs.read_storage::<Name>();
+    let Some(name) = names.get(target) else {
+        return;
+    };
+    gamelog::Logger::new()
+        .npc_name(&name.name)
+        .color(color)
+        .append(text)
+        .log(&mut ecs.write_resource::<GameLog>());
+}
+
+pub fn apply_status(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let Some(status) = status_for(ecs, &effect.effect_type) {
+        add_status(ecs, target, &status);
+    }
+}
+
+/// Puts `status` on `target`, unless it's immune, following the status's
+/// stacking policy if it's already under it.
+pub fn add_status(ecs: &mut World, target: Entity, status: &Status) {
+    if ecs
+        .read_storage::<StatusImmunities>()
+        .get(target)
+        .is_some_and(|immunities| immunities.blocks(&status.name, &status.tags))
+    {
+        log_status(
+            ecs,
+            target,
+            GRAY,
+            format!("is unaffected by {}.", status.name),
+        );
+        return;
+    }
+
+    let existing = (
+        &ecs.entities(),
+        &ecs.read_storage::<StatusEffect>(),
+        &ecs.read_storage::<StatusInfo>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .find(|(_, effect, _, name)| effect.target == target && name.name == status.name)
+        .map(|(entity, ..)| entity);
+
+    if let Some(existing) = existing {
+        let mut infos = ecs.write_storage::<StatusInfo>();
+        let info = infos.get_mut(existing).unwrap();
+        match info.stacking {
+            StackingPolicy::Unique => return,
+            StackingPolicy::Refresh => {}
+            StackingPolicy::StackIntensity => {
+                info.stacks = i32::min(info.stacks + 1, info.max_stacks);
+            }
+        }
+        if let Some(duration) = ecs.write_storage::<Duration>().get_mut(existing) {
+            duration.turns = status.duration;
+        }
+    } else {
+        spawn_status(ecs, target, status);
+    }
+
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(target, EquipmentChanged {})
+        .expect("Insert failed");
+}
+
+fn spawn_status(ecs: &mut World, target: Entity, status: &Status) {
+    let mut eb = ecs
+        .create_entity()
+        .with(StatusEffect { target })
+        .with(Duration {
+            turns: status.duration,
+        })
+        .with(Name {
+            name: status.name.clone(),
+        })
+        .with(StatusInfo {
+            glyph: to_cp437(status.glyph),
+            color: RGB::from_hex(&status.color).expect("Bad RGB"),
+            stacking: status.stacking,
+            stacks: 1,
+            max_stacks: status.max_stacks,
+            tags: status.tags.clone(),
+        });
+
+    let periodic = &status.periodic;
+    if periodic.damage > 0 {
+        eb = eb.with(DamageOverTime {
+            damage: periodic.damage,
+            damage_type: periodic.damage_type,
+        });
+    }
+    if periodic.healing > 0 {
+        eb = eb.with(HealingOverTime {
+            healing: periodic.healing,
+        });
+    }
+
+    let modifiers = &status.modifiers;
+    if !modifiers.attributes.is_empty() {
+        let attribute = |kind: AttributeKind| modifiers.attributes.get(&kind).copied();
+        eb = eb.with(AttributeBonus {
+            might: attribute(AttributeKind::Might),
+            fitness: attribute(AttributeKind::Fitness),
+            quickness: attribute(AttributeKind::Quickness),
+            intelligence: attribute(AttributeKind::Intelligence),
+        });
+    }
+    if modifiers.initiative_penalty != 0.0 {
+        eb = eb.with(Slow {
+            initiative_penalty: modifiers.initiative_penalty,
+        });
+    }
+    if !modifiers.resistances.is_empty() {
+        eb = eb.with(Resistances {
+            damage: modifiers.resistances.clone(),
+        });
+    }
+    if modifiers.skips_turn {
+        eb = eb.with(Confusion {});
+    }
+
+    eb.marked::<SimpleMarker<SerializeMe>>().build();
+}
+
+pub fn dispel(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    let EffectType::Dispel { tag } = &effect.effect_type else {
+        return;
+    };
+    let dispelled: Vec<(Entity, String)> = (
+        &ecs.entities(),
+        &ecs.read_storage::<StatusEffect>(),
+        &ecs.read_storage::<StatusInfo>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .filter(|(_, status, info, _)| status.target == target && info.tags.contains(tag))
+        .map(|(entity, _, _, name)| (entity, name.name.clone()))
+        .collect();
+    if dispelled.is_empty() {
+        return;
+    }
+
+    for (entity, name) in dispelled {
+        log_status(ecs, target, CYAN, format!("is freed of {name}."));
+        ecs.entities().delete(entity).expect("Delete failed");
+    }
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(target, EquipmentChanged {})
+        .expect("Insert failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::{Join, WorldExt};
+
+    use crate::components::{DamageType, Duration, Name, StatusEffect, StatusInfo};
+    use crate::effects::{add_effect, run_effects_queue, EffectType, Targets};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn statuses_stack_by_their_policy_and_respect_immunities_and_dispels() {
+        let mut scenario = Scenario::new(
+            "
+            #######
+            #@.#..#
+            #######
+            ",
+        );
+        let player = scenario.player();
+        let golem = scenario.spawn("Rock Golem", 5, 1);
+        let apply = |scenario: &mut Scenario, target, effect_type| {
+            add_effect(&scenario.ecs, None, effect_type, Targets::Single { target });
+            run_effects_queue(&mut scenario.ecs);
+        };
+        let poison = || EffectType::Status {
+            name: "Poisoned".to_string(),
+        };
+        // (stacks, turns left) of the status called `name` on `target`
+        let status = |scenario: &Scenario, target, name: &str| {
+            let statuses = scenario.ecs.read_storage::<StatusEffect>();
+            let infos = scenario.ecs.read_storage::<StatusInfo>();
+            let durations = scenario.ecs.read_storage::<Duration>();
+            let names = scenario.ecs.read_storage::<Name>();
+            (&statuses, &infos, &durations, &names)
+                .join()
+                .find(|(status, _, _, status_name)| status.target == target && status_name.name == name)
+                .map(|(_, info, duration, _)| (info.stacks, duration.turns))
+        };
+
+        // Poison stacks up to its limit, and hurts once per stack
+        for _ in 0..4 {
+            apply(&mut scenario, player, poison());
+        }
+        assert_eq!(status(&scenario, player, "Poisoned"), Some((3, 5)));
+        let hp = scenario.hit_points(player);
+        scenario.run_turn();
+        assert_eq!(scenario.hit_points(player), hp - 3);
+
+        // The player starts hungover; drinking again starts it over
+        let (_, hangover_turns) = status(&scenario, player, "Hangover").unwrap();
+        assert!(hangover_turns < 10);
+        apply(
+            &mut scenario,
+            player,
+            EffectType::Status {
+                name: "Hangover".to_string(),
+            },
+        );
+        assert_eq!(status(&scenario, player, "Hangover"), Some((1, 10)));
+
+        // A unique status can't be renewed while it lasts
+        let regenerate = || EffectType::Status {
+            name: "Regenerating".to_string(),
+        };
+        apply(&mut scenario, player, regenerate());
+        scenario.run_turn();
+        apply(&mut scenario, player, regenerate());
+        assert_eq!(status(&scenario, player, "Regenerating"), Some((1, 7)));
+
+        apply(&mut scenario, golem, poison());
+        assert_eq!(status(&scenario, golem, "Poisoned"), None);
+        assert!(scenario.log_contains("is unaffected by Poisoned."));
+
+        apply(
+            &mut scenario,
+            player,
+            EffectType::Dispel {
+                tag: "poison".to_string(),
+            },
+        );
+        assert_eq!(status(&scenario, player, "Poisoned"), None);
+        assert!(status(&scenario, player, "Regenerating").is_some());
+        assert!(scenario.log_contains("is freed of Poisoned."));
+    }
+
+    #[test]
+    fn damage_over_time_is_named_after_its_damage_type() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        for damage_type in [DamageType::Fire, DamageType::Slashing] {
+            add_effect(
+                &scenario.ecs,
+                None,
+                EffectType::DamageOverTime {
+                    damage: 1,
+                    damage_type,
+                },
+                Targets::Single { target: player },
+            );
+        }
+        run_effects_queue(&mut scenario.ecs);
+        let statuses_on = |scenario: &Scenario| -> Vec<String> {
+            let statuses = scenario.ecs.read_storage::<StatusEffect>();
+            let names = scenario.ecs.read_storage::<Name>();
+            (&statuses, &names)
+                .join()
+                .filter(|(status, _)| status.target == player)
+                .map(|(_, name)| name.name.clone())
+                .collect()
+        };
+        assert!(statuses_on(&scenario).contains(&"Burning".to_string()));
+        assert!(statuses_on(&scenario).contains(&"Bleeding".to_string()));
+
+        add_effect(
+            &scenario.ecs,
+            None,
+            EffectType::Dispel {
+                tag: "wound".to_string(),
+            },
+            Targets::Single { target: player },
+        );
+        run_effects_queue(&mut scenario.ecs);
+
+        assert!(statuses_on(&scenario).contains(&"Burning".to_string()));
+        assert!(!statuses_on(&scenario).contains(&"Bleeding".to_string()));
+    }
+}
@@ -0,0 +1,99 @@
+use rltk::CYAN;
+use specs::{Entity, World, WorldExt};
+
+use crate::components::Skills;
+use crate::effects::{EffectSpawner, EffectType};
+use crate::gamelog::{self, GameLog};
+use crate::gamesystem::{practice_to_next_rank, MAX_PRACTICED_SKILL_RANK};
+
+/// Counts a use of a skill towards its next rank. Only the player learns from
+/// practice; mobs are as good as their raws say.
+pub fn train_skill(ecs: &World, effect: &EffectSpawner, target: Entity) {
+    if target != *ecs.fetch::<Entity>() {
+        return;
+    }
+    let EffectType::TrainSkill { skill } = effect.effect_type else {
+        return;
+    };
+    let mut skills = ecs.write_storage::<Skills>();
+    let Some(skills) = skills.get_mut(target) else {
+        return;
+    };
+
+    let rank = skills.skills.get(&skill).copied().unwrap_or(0);
+    if rank >= MAX_PRACTICED_SKILL_RANK {
+        return;
+    }
+    let practice = skills.practice.entry(skill).or_insert(0);
+    *practice += 1;
+    if *practice >= practice_to_next_rank(rank) {
+        *practice = 0;
+        skills.skills.insert(skill, rank + 1);
+        gamelog::Logger::new()
+            .color(CYAN)
+            .append(format!(
+                "Practice pays off: your {skill:?} skill rises to {}.",
+                rank + 1
+            ))
+            .log(&mut ecs.write_resource::<GameLog>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use crate::components::{Skill, Skills};
+    use crate::effects::{add_effect, run_effects_queue, EffectType, Targets};
+    use crate::gamesystem::practice_to_next_rank;
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn skills_rank_up_with_practice_and_slow_down() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        let rat = scenario.spawn("Rat", 3, 1);
+        let practice = |scenario: &mut Scenario, target, uses| {
+            for _ in 0..uses {
+                add_effect(
+                    &scenario.ecs,
+                    Some(target),
+                    EffectType::TrainSkill {
+                        skill: Skill::Magic,
+                    },
+                    Targets::Single { target },
+                );
+            }
+            run_effects_queue(&mut scenario.ecs);
+        };
+        let magic = |scenario: &Scenario, entity| {
+            let skills = scenario.ecs.read_storage::<Skills>();
+            let skills = skills.get(entity).unwrap();
+            (
+                skills.skills[&Skill::Magic],
+                skills.practice.get(&Skill::Magic).copied().unwrap_or(0),
+            )
+        };
+        let rat_magic = magic(&scenario, rat);
+
+        practice(&mut scenario, player, practice_to_next_rank(1) - 1);
+        assert_eq!(magic(&scenario, player), (1, practice_to_next_rank(1) - 1));
+        practice(&mut scenario, player, 1);
+        assert_eq!(magic(&scenario, player), (2, 0));
+        assert!(scenario.log_contains("your Magic skill rises to 2"));
+        assert!(practice_to_next_rank(2) > practice_to_next_rank(1));
+
+        practice(&mut scenario, rat, 100);
+        assert_eq!(
+            magic(&scenario, rat),
+            rat_magic,
+            "only the player practices"
+        );
+    }
+}
@@ -1,17 +1,17 @@
-use rltk::{Point, BLACK};
+use rltk::{Point, RandomNumberGenerator, BLACK};
 use specs::{Entity, World, WorldExt};
 
 use crate::components::{
-    AlwaysTargetsSelf, AreaOfEffect, AttributeBonus, Confusion, Consumable, DamageOverTime,
-    Duration, Hidden, InflictsDamage, KnownSpell, KnownSpells, MagicMapper, Name, Pools, Position,
-    ProvidesFood, ProvidesHealing, ProvidesIdentification, ProvidesMana, ProvidesRemoveCurse,
-    SingleActivation, Slow, SpawnParticleBurst, SpawnParticleLine, SpellTemplate, TeachesSpell,
-    TeleportTo, TownPortal,
+    AlwaysTargetsSelf, AppliesStatus, AreaOfEffect, AttributeBonus, Confusion, Consumable,
+    DamageOverTime, Dispels, Duration, Hidden, InflictsDamage, KnownSpell, KnownSpells,
+    MagicMapper, Name, Pools, Position, ProvidesFood, ProvidesHealing, ProvidesIdentification,
+    ProvidesMana, ProvidesRemoveCurse, Resistances, SingleActivation, Slow, SpawnParticleBurst,
+    SpawnParticleLine, SpellTemplate, TeachesSpell, TeleportTo, TownPortal,
 };
 use crate::effects::targeting::entity_position;
 
 use crate::effects::{add_effect, aoe_tiles, find_item_position, EffectType, Targets};
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 use crate::map::Map;
 use crate::player::RunState;
 use crate::raws::rawmaster::find_spell_entity;
@@ -22,7 +22,7 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
             gamelog::Logger::new()
                 .item_name(&ecs.read_storage::<Name>().get(item).unwrap().name)
                 .append("is out of charges!")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
             return;
         } else {
             c.charges -= 1;
@@ -45,6 +45,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(part) = ecs.read_storage::<SpawnParticleBurst>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::Particle {
                 glyph: part.glyph,
@@ -73,12 +74,12 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
     }
 
     if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
-        add_effect(creator, EffectType::WellFed, targets.clone());
+        add_effect(ecs, creator, EffectType::WellFed, targets.clone());
         let names = ecs.read_storage::<Name>();
         gamelog::Logger::new()
             .append("You eat the")
             .item_name(&names.get(entity).unwrap().name)
-            .log();
+            .log(&mut ecs.write_resource::<GameLog>());
         did_something = true;
     }
 
@@ -86,7 +87,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
         let mut runstate = ecs.fetch_mut::<RunState>();
         gamelog::Logger::new()
             .append("The map is revealed to you!")
-            .log();
+            .log(&mut ecs.write_resource::<GameLog>());
         *runstate = RunState::MagicMapReveal { row: 0 };
         did_something = true;
     }
@@ -116,11 +117,11 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
         if map.depth == 1 {
             gamelog::Logger::new()
                 .append("You are already in town, so the scroll does nothing.")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
         } else {
             gamelog::Logger::new()
                 .append("You are teleported back to town!")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
             let mut runstate = ecs.fetch_mut::<RunState>();
             *runstate = RunState::TownPortal;
             did_something = true;
@@ -129,6 +130,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(heal) = ecs.read_storage::<ProvidesHealing>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::Healing {
                 amount: heal.heal_amount,
@@ -140,6 +142,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(mana) = ecs.read_storage::<ProvidesMana>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::Mana {
                 amount: mana.mana_amount,
@@ -150,10 +153,15 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
     }
 
     if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
+        let amount = damage
+            .damage
+            .roll(&mut ecs.write_resource::<RandomNumberGenerator>());
         add_effect(
+            ecs,
             creator,
             EffectType::Damage {
-                amount: damage.damage,
+                amount,
+                damage_type: damage.damage_type,
             },
             targets.clone(),
         );
@@ -163,6 +171,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
     if let Some(_confusion) = ecs.read_storage::<Confusion>().get(entity) {
         if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
             add_effect(
+                ecs,
                 creator,
                 EffectType::Confusion {
                     turns: duration.turns,
@@ -175,6 +184,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(teleport) = ecs.read_storage::<TeleportTo>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::TeleportTo {
                 x: teleport.x,
@@ -189,6 +199,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(attr) = ecs.read_storage::<AttributeBonus>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::AttributeEffect {
                 bonus: attr.clone(),
@@ -224,6 +235,7 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(slow) = ecs.read_storage::<Slow>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::Slow {
                 inititive_penalty: slow.initiative_penalty,
@@ -235,9 +247,47 @@ fn event_trigger(creator: Option<Entity>, entity: Entity, targets: &Targets, ecs
 
     if let Some(damage) = ecs.read_storage::<DamageOverTime>().get(entity) {
         add_effect(
+            ecs,
             creator,
             EffectType::DamageOverTime {
                 damage: damage.damage,
+                damage_type: damage.damage_type,
+            },
+            targets.clone(),
+        );
+        did_something = true;
+    }
+
+    if let Some(resistances) = ecs.read_storage::<Resistances>().get(entity) {
+        add_effect(
+            ecs,
+            creator,
+            EffectType::Resistances {
+                damage: resistances.damage.clone(),
+            },
+            targets.clone(),
+        );
+        did_something = true;
+    }
+
+    if let Some(status) = ecs.read_storage::<AppliesStatus>().get(entity) {
+        add_effect(
+            ecs,
+            creator,
+            EffectType::Status {
+                name: status.name.clone(),
+            },
+            targets.clone(),
+        );
+        did_something = true;
+    }
+
+    if let Some(dispel) = ecs.read_storage::<Dispels>().get(entity) {
+        add_effect(
+            ecs,
+            creator,
+            EffectType::Dispel {
+                tag: dispel.tag.clone(),
             },
             targets.clone(),
         );
@@ -269,6 +319,7 @@ fn spawn_line_particles(ecs: &World, start: i32, end: i32, part: &SpawnParticleL
     let line = rltk::line2d_bresenham(start_pt, end_pt);
     for pt in &line {
         add_effect(
+            ecs,
             None,
             EffectType::Particle {
                 glyph: part.glyph,
@@ -1,5 +1,4 @@
-use crate::gamelog::logstore::append_entry;
-use crate::gamelog::LogFragment;
+use crate::gamelog::{GameLog, LogFragment};
 use rltk::{CYAN, RED, RGB, WHITE, YELLOW};
 
 pub struct Logger {
@@ -28,8 +27,8 @@ impl Logger {
         self
     }
 
-    pub fn log(self) {
-        append_entry(self.fragments);
+    pub fn log(self, log: &mut GameLog) {
+        log.append_entry(self.fragments);
     }
 
     pub fn npc_name(mut self, text: impl ToString) -> Self {
@@ -1,43 +1,24 @@
-use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
-lazy_static! {
-    static ref EVENTS: Mutex<HashMap<String, i32>> = Mutex::new(HashMap::new());
-}
 
-pub fn clear_events() {
-    EVENTS.lock().unwrap().clear();
+/// Running totals of things worth reporting at the end of a run, such as turns
+/// played or damage taken.
+#[derive(Default, Serialize, Deserialize, Clone)]
+#[serde(transparent)]
+pub struct GameEvents {
+    counts: HashMap<String, i32>,
 }
 
-pub fn record_event(event: impl ToString, n: i32) {
-    let event_name = event.to_string();
-    let mut events_lock = EVENTS.lock();
-    let events = events_lock.as_mut().unwrap();
-    if let Some(e) = events.get_mut(&event_name) {
-        *e += n;
-    } else {
-        events.insert(event_name, n);
+impl GameEvents {
+    pub fn clear(&mut self) {
+        self.counts.clear();
     }
-}
 
-pub fn get_event_count(event: impl ToString) -> i32 {
-    let event_name = event.to_string();
-    let events_lock = EVENTS.lock();
-    let events = events_lock.unwrap();
-    if let Some(e) = events.get(&event_name) {
-        *e
-    } else {
-        0
+    pub fn record(&mut self, event: impl ToString, n: i32) {
+        *self.counts.entry(event.to_string()).or_insert(0) += n;
     }
-}
-
-pub fn clone_events() -> HashMap<String, i32> {
-    EVENTS.lock().unwrap().clone()
-}
 
-pub fn load_events(events: HashMap<String, i32>) {
-    EVENTS.lock().unwrap().clear();
-    for (k, v) in &events {
-        EVENTS.lock().unwrap().insert(k.to_string(), *v);
+    pub fn count(&self, event: impl ToString) -> i32 {
+        self.counts.get(&event.to_string()).copied().unwrap_or(0)
     }
 }
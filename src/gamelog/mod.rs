@@ -4,7 +4,7 @@ mod logstore;
 
 pub use builder::*;
 pub use events::*;
-pub use logstore::{clear_log, clone_log, print_log, restore_log};
+pub use logstore::GameLog;
 
 use rltk::RGB;
 use serde::{Deserialize, Serialize};
@@ -1,14 +1,24 @@
-use std::str::FromStr;
-
-use lazy_static::lazy_static;
-use regex::Regex;
-
-use crate::components::{Skill, Skills};
+use crate::components::{ActionKind, Skill, Skills};
 
 pub const fn attr_bonus(value: i32) -> i32 {
     (value - 10) / 2
 }
 
+/// How low and high character creation lets an attribute go.
+pub const MIN_BOUGHT_ATTRIBUTE: i32 = 8;
+pub const MAX_BOUGHT_ATTRIBUTE: i32 = 18;
+
+/// The points it takes to buy an attribute up to `value` from one below: one
+/// each until the attribute gives a bonus, then the bonus it gives.
+pub const fn attr_point_cost(value: i32) -> i32 {
+    let bonus = attr_bonus(value);
+    if bonus > 1 {
+        bonus
+    } else {
+        1
+    }
+}
+
 pub const fn player_hp_per_level(fitness: i32) -> i32 {
     15 + attr_bonus(fitness)
 }
@@ -31,6 +41,16 @@ pub fn mana_at_level(intelligence: i32, level: i32) -> i32 {
     mana_per_level(intelligence) * level
 }
 
+/// The highest rank practice alone takes a skill to.
+pub const MAX_PRACTICED_SKILL_RANK: i32 = 10;
+
+/// How many uses it takes to practice a skill from `rank` to the next. Each
+/// rank takes longer than the last.
+pub const fn practice_to_next_rank(rank: i32) -> i32 {
+    let next = if rank < 0 { 1 } else { rank + 1 };
+    5 * next * next
+}
+
 pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
     if skills.skills.contains_key(&skill) {
         skills.skills[&skill]
@@ -39,38 +59,47 @@ pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
     }
 }
 
-pub struct DiceRoll {
-    pub n_dice: i32,
-    pub die_type: i32,
-    pub die_bonus: i32,
-}
+/// The speed of an ordinary actor, and of a weapon that's neither quick nor
+/// clumsy.
+pub const NORMAL_SPEED: i32 = 100;
 
-impl FromStr for DiceRoll {
-    type Err = !;
+/// The lowest natural d20 roll that crits, for weapons that don't say.
+pub const DEFAULT_CRIT_RANGE: i32 = 20;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            static ref DICE_REGEX: Regex = Regex::new(r"(\d+)d(\d+)([\+\-]\d+)?").unwrap();
-        }
-        let mut n_dice = 1;
-        let mut die_type = 4;
-        let mut die_bonus = 0;
-        for cap in DICE_REGEX.captures_iter(s) {
-            if let Some(group) = cap.get(1) {
-                n_dice = group.as_str().parse::<i32>().expect("Not a digit");
-            }
-            if let Some(group) = cap.get(2) {
-                die_type = group.as_str().parse::<i32>().expect("Not a digit");
-            }
-            if let Some(group) = cap.get(3) {
-                die_bonus = group.as_str().parse::<i32>().expect("Not a digit");
-            }
-        }
+/// What a crit multiplies the damage dice by, for weapons that don't say.
+pub const DEFAULT_CRIT_MULTIPLIER: i32 = 2;
 
-        Ok(Self {
-            n_dice,
-            die_type,
-            die_bonus,
-        })
+/// What's left of a melee hit once the target's armour has absorbed its share.
+/// Armour penetration cancels damage reduction point for point, but never adds
+/// damage of its own.
+pub fn reduce_damage(damage: i32, damage_reduction: i32, armor_penetration: i32) -> i32 {
+    i32::max(
+        0,
+        damage - i32::max(0, damage_reduction - armor_penetration),
+    )
+}
+
+/// How many ticks an action takes at normal speed.
+pub const fn action_cost(action: ActionKind) -> i32 {
+    match action {
+        ActionKind::Drop => 4,
+        ActionKind::PickUp => 6,
+        ActionKind::Wait | ActionKind::Move | ActionKind::Melee { .. } | ActionKind::UseItem => 10,
+        ActionKind::Shoot { .. } | ActionKind::Cast => 12,
+        ActionKind::Equip => 15,
     }
 }
+
+/// Ticks until an actor's next turn after `action`. The cost is scaled by the
+/// actor's speed, and the weapon's when attacking, then quickness takes ticks
+/// off and armour, `Slow` and haste (a negative penalty) put them on.
+pub fn action_time(action: ActionKind, speed: i32, quickness_bonus: i32, penalty: f32) -> i32 {
+    let speed = match action {
+        ActionKind::Melee { weapon_speed } | ActionKind::Shoot { weapon_speed } => {
+            speed * weapon_speed / NORMAL_SPEED
+        }
+        _ => speed,
+    };
+    let time = action_cost(action) * NORMAL_SPEED / i32::max(1, speed);
+    i32::max(1, time - quickness_bonus + f32::floor(penalty) as i32)
+}
@@ -0,0 +1,251 @@
+use rltk::{
+    BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, VirtualKeyCode, BLACK, CYAN, GRAY, GREEN,
+    RGB, WHITE, YELLOW,
+};
+
+use crate::gamesystem::{
+    attr_bonus, attr_point_cost, mana_at_level, player_hp_at_level, MAX_BOUGHT_ATTRIBUTE,
+    MIN_BOUGHT_ATTRIBUTE,
+};
+use crate::raws::{ClassAttributes, PlayerClass};
+use crate::spawner::NewCharacter;
+
+const MAX_NAME_LENGTH: usize = 16;
+const ATTRIBUTE_NAMES: [&str; 4] = ["Might", "Fitness", "Quickness", "Intelligence"];
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CharacterMenuResult {
+    NoResponse,
+    Cancel,
+    Done,
+}
+
+/// A character being made. The class's own attributes are where the points
+/// start: lowering one frees points to raise another.
+#[derive(Clone, Debug)]
+pub struct CharacterSheet {
+    pub class: String,
+    pub name: String,
+    /// Might, fitness, quickness and intelligence, in that order.
+    pub attributes: [i32; 4],
+    pub points: i32,
+    pub selected: usize,
+}
+
+impl CharacterSheet {
+    pub fn new(class: &PlayerClass) -> Self {
+        let attributes = class.attributes;
+        Self {
+            class: class.name.clone(),
+            name: String::new(),
+            attributes: [
+                attributes.might,
+                attributes.fitness,
+                attributes.quickness,
+                attributes.intelligence,
+            ],
+            points: 0,
+            selected: 0,
+        }
+    }
+
+    /// Buys a point of attribute `index`, if there are points for it.
+    pub fn raise(&mut self, index: usize) -> bool {
+        let next = self.attributes[index] + 1;
+        let cost = attr_point_cost(next);
+        if next > MAX_BOUGHT_ATTRIBUTE || cost > self.points {
+            return false;
+        }
+        self.attributes[index] = next;
+        self.points -= cost;
+        true
+    }
+
+    /// Sells a point of attribute `index` back for what it cost.
+    pub fn lower(&mut self, index: usize) -> bool {
+        let value = self.attributes[index];
+        if value <= MIN_BOUGHT_ATTRIBUTE {
+            return false;
+        }
+        self.attributes[index] = value - 1;
+        self.points += attr_point_cost(value);
+        true
+    }
+
+    pub fn character(&self) -> NewCharacter {
+        let [might, fitness, quickness, intelligence] = self.attributes;
+        NewCharacter {
+            name: self.name.trim().to_string(),
+            class: self.class.clone(),
+            attributes: ClassAttributes {
+                might,
+                fitness,
+                quickness,
+                intelligence,
+            },
+        }
+    }
+}
+
+fn edit_name(sheet: &mut CharacterSheet, ctx: &Rltk, key: VirtualKeyCode) {
+    match key {
+        VirtualKeyCode::Back => {
+            sheet.name.pop();
+        }
+        VirtualKeyCode::Space if !sheet.name.is_empty() => sheet.name.push(' '),
+        _ => {
+            let letter = rltk::letter_to_option(key);
+            if (0..26).contains(&letter) && sheet.name.len() < MAX_NAME_LENGTH {
+                let ch = (b'a' + letter as u8) as char;
+                let ch = if ctx.shift || sheet.name.is_empty() || sheet.name.ends_with(' ') {
+                    ch.to_ascii_uppercase()
+                } else {
+                    ch
+                };
+                sheet.name.push(ch);
+            }
+        }
+    }
+}
+
+/// Names the character and spreads their attribute points. Typing edits the
+/// name, the arrows pick and adjust an attribute.
+pub fn character_menu(sheet: &mut CharacterSheet, ctx: &Rltk) -> CharacterMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let white = ColorPair::new(RGB::named(WHITE), RGB::named(BLACK));
+    let yellow = ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK));
+    let gray = ColorPair::new(RGB::named(GRAY), RGB::named(BLACK));
+
+    draw_batch.draw_box(Rect::with_size(15, 12, 50, 20), white);
+    draw_batch.print_color(
+        Point::new(18, 12),
+        format!("Create a {}", sheet.class),
+        yellow,
+    );
+    draw_batch.print_color(
+        Point::new(18, 32),
+        "ENTER to begin, ESCAPE to go back",
+        yellow,
+    );
+
+    draw_batch.print_color(Point::new(18, 14), "Name:", white);
+    draw_batch.print_color(
+        Point::new(31, 14),
+        format!("{}_", sheet.name),
+        ColorPair::new(RGB::named(CYAN), RGB::named(BLACK)),
+    );
+
+    for (i, (label, value)) in ATTRIBUTE_NAMES.iter().zip(sheet.attributes).enumerate() {
+        let y = 16 + i as i32;
+        let colors = if i == sheet.selected { yellow } else { white };
+        let marker = if i == sheet.selected { '>' } else { ' ' };
+        draw_batch.print_color(Point::new(17, y), format!("{marker}{label}:"), colors);
+        draw_batch.print_color(
+            Point::new(31, y),
+            format!("{value:>2} ({:+})", attr_bonus(value)),
+            colors,
+        );
+    }
+
+    draw_batch.print_color(
+        Point::new(18, 21),
+        format!("Points left: {}", sheet.points),
+        ColorPair::new(RGB::named(GREEN), RGB::named(BLACK)),
+    );
+    let [_, fitness, _, intelligence] = sheet.attributes;
+    draw_batch.print_color(
+        Point::new(18, 23),
+        format!(
+            "Hit points: {}   Mana: {}",
+            player_hp_at_level(fitness, 1),
+            mana_at_level(intelligence, 1)
+        ),
+        white,
+    );
+    draw_batch.print_color(
+        Point::new(18, 25),
+        "Type a name. UP/DOWN picks an attribute,",
+        gray,
+    );
+    draw_batch.print_color(
+        Point::new(18, 26),
+        "LEFT/RIGHT sells or buys a point of it.",
+        gray,
+    );
+
+    draw_batch.submit(6000).expect("Draw batch failed");
+
+    match ctx.key {
+        None => CharacterMenuResult::NoResponse,
+        Some(VirtualKeyCode::Escape) => CharacterMenuResult::Cancel,
+        Some(VirtualKeyCode::Return) if !sheet.name.trim().is_empty() => CharacterMenuResult::Done,
+        Some(VirtualKeyCode::Up) => {
+            sheet.selected = (sheet.selected + ATTRIBUTE_NAMES.len() - 1) % ATTRIBUTE_NAMES.len();
+            CharacterMenuResult::NoResponse
+        }
+        Some(VirtualKeyCode::Down) => {
+            sheet.selected = (sheet.selected + 1) % ATTRIBUTE_NAMES.len();
+            CharacterMenuResult::NoResponse
+        }
+        Some(VirtualKeyCode::Left) => {
+            sheet.lower(sheet.selected);
+            CharacterMenuResult::NoResponse
+        }
+        Some(VirtualKeyCode::Right) => {
+            sheet.raise(sheet.selected);
+            CharacterMenuResult::NoResponse
+        }
+        Some(key) => {
+            edit_name(sheet, ctx, key);
+            CharacterMenuResult::NoResponse
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use super::CharacterSheet;
+    use crate::components::{Attributes, Name};
+    use crate::test_support::{shared_raws, Scenario};
+
+    #[test]
+    fn created_characters_spend_points_and_keep_their_name() {
+        let raws = shared_raws();
+        let mut sheet = CharacterSheet::new(raws.class("Mage").unwrap());
+        // Mgt 9, Fit 10, Qck 12, Int 15, with nothing left over
+        assert!(!sheet.raise(1));
+        assert!(sheet.lower(0));
+        assert!(!sheet.lower(0), "attributes can't be sold below 8");
+        assert!(sheet.raise(1));
+        assert!(sheet.lower(3));
+        assert_eq!(sheet.points, 2, "intelligence 15 cost two points");
+        assert!(sheet.raise(2));
+        assert_eq!(sheet.points, 1);
+        sheet.name = "Morgana".to_string();
+
+        let scenario = Scenario::with_character(
+            "
+            ####
+            #@.#
+            ####
+            ",
+            &sheet.character(),
+        );
+        let player = scenario.player();
+
+        let names = scenario.ecs.read_storage::<Name>();
+        assert_eq!(names.get(player).unwrap().name, "Morgana");
+        let attributes = scenario.ecs.read_storage::<Attributes>();
+        let attributes = attributes.get(player).unwrap();
+        let bases = [
+            attributes.might.base,
+            attributes.fitness.base,
+            attributes.quickness.base,
+            attributes.intelligence.base,
+        ];
+        assert_eq!(bases, [8, 11, 13, 14]);
+        assert!(scenario.has_item(player, "Mana Potion"));
+    }
+}
@@ -3,8 +3,9 @@ use crate::State;
 use rltk::{
     to_cp437, BTerm as Rltk, ColorPair, DrawBatch, Point, VirtualKeyCode, BLACK, RGB, YELLOW,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Eq, PartialEq, Copy, Clone)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum CheatMenuResult {
     NoResponse,
     Cancel,
@@ -12,11 +13,12 @@ pub enum CheatMenuResult {
     Heal,
     Reveal,
     GodMode,
+    ReloadRaws,
 }
 
 pub fn show_cheat_mode(_gs: &mut State, ctx: &Rltk) -> CheatMenuResult {
     let mut draw_batch = DrawBatch::new();
-    let count = 4;
+    let count = 5;
     let y = 25 - (count / 2);
     menu_box(&mut draw_batch, 15, y, count + 3, "Cheating!");
     draw_batch.print_color(
@@ -41,6 +43,13 @@ pub fn show_cheat_mode(_gs: &mut State, ctx: &Rltk) -> CheatMenuResult {
         to_cp437('G'),
         "God Mode (No Death)",
     );
+    menu_option(
+        &mut draw_batch,
+        17,
+        y + 4,
+        to_cp437('L'),
+        "Reload raws from disk",
+    );
 
     draw_batch.submit(6000).expect("Draw batch failed");
 
@@ -50,6 +59,7 @@ pub fn show_cheat_mode(_gs: &mut State, ctx: &Rltk) -> CheatMenuResult {
             VirtualKeyCode::H => CheatMenuResult::Heal,
             VirtualKeyCode::R => CheatMenuResult::Reveal,
             VirtualKeyCode::G => CheatMenuResult::GodMode,
+            VirtualKeyCode::L => CheatMenuResult::ReloadRaws,
             VirtualKeyCode::Escape => CheatMenuResult::Cancel,
             _ => CheatMenuResult::NoResponse,
         })
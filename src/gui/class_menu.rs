@@ -0,0 +1,99 @@
+use itertools::Itertools;
+use rltk::{
+    to_cp437, BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, VirtualKeyCode, BLACK, GRAY, RGB,
+    WHITE, YELLOW,
+};
+
+use crate::gui::menu_option;
+use crate::raws::PlayerClass;
+use crate::State;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ClassMenuResult {
+    NoResponse,
+    Cancel,
+    Selected(usize),
+}
+
+fn describe_stats(class: &PlayerClass) -> String {
+    let attributes = &class.attributes;
+    let mut stats = format!(
+        "Mgt {} Fit {} Qck {} Int {}",
+        attributes.might, attributes.fitness, attributes.quickness, attributes.intelligence
+    );
+    for (skill, rank) in class.skills.iter().sorted() {
+        stats += &format!(", {skill} {rank}");
+    }
+    if !class.spells.is_empty() {
+        stats += &format!(", knows {}", class.spells.join(", "));
+    }
+    stats
+}
+
+fn describe_kit(class: &PlayerClass) -> String {
+    let mut kit = class.equipped.iter().chain(&class.carried).join(", ");
+    if class.gold.range() != (0, 0) {
+        kit += &format!(", {} gold", class.gold);
+    }
+    kit
+}
+
+/// Lists the classes in the raws, with what each starts with. A letter starts a
+/// new game as that class.
+pub fn class_menu(gs: &State, ctx: &Rltk) -> ClassMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let classes = gs.raws.classes();
+    let count = classes.len() as i32;
+    let mut y = 25 - (count * 2);
+
+    draw_batch.draw_box(
+        Rect::with_size(2, y - 2, 76, count * 4 + 2),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(5, y - 2),
+        "Choose a Class",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(5, y + count * 4 - 1),
+        "ESCAPE to cancel",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+
+    for (j, class) in classes.iter().enumerate() {
+        menu_option(
+            &mut draw_batch,
+            4,
+            y,
+            to_cp437('a') + j as rltk::FontCharType,
+            format!("{}: {}", class.name, class.description),
+        );
+        draw_batch.print_color(
+            Point::new(8, y + 1),
+            describe_stats(class),
+            ColorPair::new(RGB::named(GRAY), RGB::named(BLACK)),
+        );
+        draw_batch.print_color(
+            Point::new(8, y + 2),
+            describe_kit(class),
+            ColorPair::new(RGB::named(GRAY), RGB::named(BLACK)),
+        );
+        y += 4;
+    }
+
+    draw_batch.submit(6000).expect("Draw batch failed");
+
+    match ctx.key {
+        None => ClassMenuResult::NoResponse,
+        Some(VirtualKeyCode::Escape) => ClassMenuResult::Cancel,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection < 0 || selection >= count {
+                ClassMenuResult::NoResponse
+            } else {
+                ClassMenuResult::Selected(selection as usize)
+            }
+        }
+    }
+}
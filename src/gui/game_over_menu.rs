@@ -1,17 +1,42 @@
-use rltk::{BTerm as Rltk, ColorPair, DrawBatch, RGB};
+use rltk::{BTerm as Rltk, ColorPair, DrawBatch, VirtualKeyCode, RGB};
+
+use crate::components::LevelUpRecord;
+use crate::gamelog::GameEvents;
 
 #[derive(PartialEq, Eq, Copy, Clone)]
 pub enum GameOverResult {
     NoSelection,
     QuitToMenu,
+    RecoverAutosave,
+}
+
+fn describe_level_up(record: &LevelUpRecord) -> String {
+    let choice = &record.choice;
+    let mut line = format!(
+        "Level {}: {:?}, {:?}",
+        record.level, choice.attribute, choice.skill
+    );
+    if let Some(perk) = &choice.perk {
+        line += &format!(", {perk}");
+    }
+    line
 }
 
-pub fn game_over(ctx: &Rltk) -> GameOverResult {
+/// `name` is the player's and `level_ups` what they picked on the way.
+/// `can_recover` offers to go back to the run's last autosave.
+pub fn game_over(
+    ctx: &Rltk,
+    name: &str,
+    level_ups: &[LevelUpRecord],
+    seed: u64,
+    events: &GameEvents,
+    can_recover: bool,
+) -> GameOverResult {
     let mut draw_batch = DrawBatch::new();
 
     draw_batch.print_color_centered(
         15,
-        "Your journey has ended!",
+        &format!("Your journey has ended, {name}!"),
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
     );
     draw_batch.print_color_centered(
@@ -27,17 +52,14 @@ pub fn game_over(ctx: &Rltk) -> GameOverResult {
 
     draw_batch.print_color_centered(
         19,
-        &format!(
-            "You lived for {} turns.",
-            crate::gamelog::get_event_count("Turn")
-        ),
+        &format!("You lived for {} turns.", events.count("Turn")),
         ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
     );
     draw_batch.print_color_centered(
         20,
         &format!(
             "You suffered {} points of damage.",
-            crate::gamelog::get_event_count("Damage Taken")
+            events.count("Damage Taken")
         ),
         ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)),
     );
@@ -45,21 +67,43 @@ pub fn game_over(ctx: &Rltk) -> GameOverResult {
         21,
         &format!(
             "You inflicted {} points of damage.",
-            crate::gamelog::get_event_count("Damage Inflicted")
+            events.count("Damage Inflicted")
         ),
         ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)),
     );
+    let mut y = 22;
+    for record in level_ups {
+        draw_batch.print_color_centered(
+            y,
+            describe_level_up(record),
+            ColorPair::new(RGB::named(rltk::GREEN), RGB::named(rltk::BLACK)),
+        );
+        y += 1;
+    }
+    draw_batch.print_color_centered(
+        y,
+        &format!("Seed: {seed}"),
+        ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)),
+    );
 
     draw_batch.print_color_centered(
-        23,
+        y + 1,
         "Press any key to return to the menu.",
         ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK)),
     );
+    if can_recover {
+        draw_batch.print_color_centered(
+            y + 2,
+            "Press R to go back to your last autosave.",
+            ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK)),
+        );
+    }
 
     draw_batch.submit(6000).expect("Batched draw failed");
 
     match ctx.key {
         None => GameOverResult::NoSelection,
+        Some(VirtualKeyCode::R) if can_recover => GameOverResult::RecoverAutosave,
         Some(_) => GameOverResult::QuitToMenu,
     }
 }
@@ -1,15 +1,17 @@
 use rltk::{to_cp437, BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, BLACK, RGB};
 use specs::{Entity, Join, World, WorldExt};
-use std::cmp::Ordering;
 
 use crate::components::{
     Attribute, Attributes, Consumable, Duration, Equipped, HungerClock, HungerState, InBackpack,
-    KnownSpells, Name, Pools, StatusEffect, Weapon,
+    InflictsDamage, KnownSpells, Name, Pools, Skill, Skills, StatusEffect, StatusInfo, Weapon,
 };
-use crate::gamelog;
+use crate::gamelog::GameLog;
+use crate::gamesystem::{practice_to_next_rank, MAX_PRACTICED_SKILL_RANK};
 
 use crate::gui::{draw_tooltips, get_item_color, get_item_display_name};
 use crate::map::Map;
+use crate::raws::rawmaster::find_spell_entity;
+use crate::rng::RunSeed;
 
 fn draw_attribute(name: &str, attribute: &Attribute, y: i32, draw_batch: &mut DrawBatch) {
     let black = RGB::named(BLACK);
@@ -199,12 +201,49 @@ fn initiative_weight(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &En
         &format!("Gold: {:.1}", player_pools.gold),
         ColorPair::new(RGB::named(rltk::GOLD), black),
     );
+    draw_batch.print_color(
+        Point::new(50, 12),
+        &format!("Seed: {}", ecs.fetch::<RunSeed>().0),
+        ColorPair::new(RGB::named(rltk::GRAY), black),
+    );
 }
 
-fn equipped(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) -> i32 {
+/// Each skill's rank, with a bar for the practice towards the next.
+fn skills(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) -> i32 {
     let black = RGB::named(rltk::BLACK);
-    let yellow = RGB::named(rltk::YELLOW);
+    let attr_gray: RGB = RGB::from_hex("#CCCCCC").expect("Oops");
+    let skills = ecs.read_storage::<Skills>();
+    let skills = skills.get(*player_entity).unwrap();
     let mut y = 13;
+    for skill in Skill::ALL {
+        let rank = skills.skills.get(&skill).copied().unwrap_or(0);
+        draw_batch.print_color(
+            Point::new(50, y),
+            &format!("{skill:?}:"),
+            ColorPair::new(attr_gray, black),
+        );
+        draw_batch.print_color(
+            Point::new(61, y),
+            &format!("{rank}"),
+            ColorPair::new(RGB::named(rltk::WHITE), black),
+        );
+        if rank < MAX_PRACTICED_SKILL_RANK {
+            draw_batch.bar_horizontal(
+                Point::new(64, y),
+                14,
+                skills.practice.get(&skill).copied().unwrap_or(0),
+                practice_to_next_rank(rank),
+                ColorPair::new(RGB::named(rltk::CYAN), black),
+            );
+        }
+        y += 1;
+    }
+    y + 1
+}
+
+fn equipped(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity, mut y: i32) -> i32 {
+    let black = RGB::named(rltk::BLACK);
+    let yellow = RGB::named(rltk::YELLOW);
     let entities = ecs.entities();
     let equipped = ecs.read_storage::<Equipped>();
     let weapon = ecs.read_storage::<Weapon>();
@@ -219,20 +258,12 @@ fn equipped(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) ->
             y += 1;
 
             if let Some(weapon) = weapon.get(entity) {
-                let mut weapon_info = match weapon.damage_bonus.cmp(&0) {
-                    Ordering::Less => format!(
-                        "┤ {} ({}d{}{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus
-                    ),
-                    Ordering::Equal => format!(
-                        "┤ {} ({}d{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type
-                    ),
-                    Ordering::Greater => format!(
-                        "┤ {} ({}d{}+{})",
-                        &name, weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus
-                    ),
-                };
+                let mut weapon_info = format!(
+                    "┤ {} ({}, {})",
+                    &name,
+                    weapon.damage,
+                    weapon.damage.summary()
+                );
 
                 if let Some(range) = weapon.range {
                     weapon_info += &format!(" (range: {range}, F to fire, V cycle targets)");
@@ -281,6 +312,7 @@ fn spells(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity, mut y
     let black = RGB::named(rltk::BLACK);
     let blue = RGB::named(rltk::CYAN);
     let known_spells_storage = ecs.read_storage::<KnownSpells>();
+    let inflicts_damage = ecs.read_storage::<InflictsDamage>();
     let known_spells = &known_spells_storage.get(*player_entity).unwrap().spells;
     let mut index = 1;
     for spell in known_spells {
@@ -296,6 +328,17 @@ fn spells(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity, mut y
         );
         index += 1;
         y += 1;
+        let damage = find_spell_entity(ecs, &spell.display_name)
+            .and_then(|spell| inflicts_damage.get(spell))
+            .map(|d| format!("{} {}", d.damage.summary(), d.damage_type.noun()));
+        if let Some(damage) = damage {
+            draw_batch.print_color(
+                Point::new(55, y),
+                &damage,
+                ColorPair::new(RGB::named(rltk::GRAY), black),
+            );
+            y += 1;
+        }
     }
     y
 }
@@ -334,18 +377,34 @@ fn status(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) {
     let statuses = ecs.read_storage::<StatusEffect>();
     let durations = ecs.read_storage::<Duration>();
     let names = ecs.read_storage::<Name>();
-    for (status, duration, name) in (&statuses, &durations, &names).join() {
+    let infos = ecs.read_storage::<StatusInfo>();
+    for (entity, status, duration, name) in (&ecs.entities(), &statuses, &durations, &names).join()
+    {
         if status.target == *player_entity {
+            let info = infos.get(entity);
+            let (glyph, fg) = info.map_or((to_cp437('*'), RGB::named(rltk::RED)), |info| {
+                (info.glyph, info.color)
+            });
+            let colors = ColorPair::new(fg, RGB::named(rltk::BLACK));
+            draw_batch.set(Point::new(50, y), colors, glyph);
             draw_batch.print_color(
-                Point::new(50, y),
-                &format!("{} ({})", name.name, duration.turns),
-                ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)),
+                Point::new(52, y),
+                &status_label(&name.name, info, duration.turns),
+                colors,
             );
             y -= 1;
         }
     }
 }
 
+/// A status as the HUD and tooltips show it, e.g. "Poisoned x2 (4)".
+pub fn status_label(name: &str, info: Option<&StatusInfo>, turns: i32) -> String {
+    match info {
+        Some(info) if info.stacks > 1 => format!("{name} x{} ({turns})", info.stacks),
+        _ => format!("{name} ({turns})"),
+    }
+}
+
 pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     let mut draw_batch = DrawBatch::new();
     let player_entity = ecs.fetch::<Entity>();
@@ -355,11 +414,12 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     draw_stats(ecs, &mut draw_batch, &player_entity);
     draw_attributes(ecs, &mut draw_batch, &player_entity);
     initiative_weight(ecs, &mut draw_batch, &player_entity);
-    let mut y = equipped(ecs, &mut draw_batch, &player_entity);
+    let y = skills(ecs, &mut draw_batch, &player_entity);
+    let mut y = equipped(ecs, &mut draw_batch, &player_entity, y);
     y += consumables(ecs, &mut draw_batch, &player_entity, y);
     spells(ecs, &mut draw_batch, &player_entity, y);
     status(ecs, &mut draw_batch, &player_entity);
-    gamelog::print_log(
+    ecs.fetch::<GameLog>().print(
         &mut rltk::BACKEND_INTERNAL.lock().consoles[1].console,
         Point::new(1, 23),
     );
@@ -23,6 +23,17 @@ pub fn get_item_color(ecs: &World, item: Entity) -> RGB {
     RGB::from_f32(1.0, 1.0, 1.0)
 }
 
+/// Whether the player knows what the item is: it's mundane, or its kind has been
+/// identified.
+pub fn is_item_identified(ecs: &World, item: Entity) -> bool {
+    ecs.read_storage::<MagicItem>().get(item).is_none()
+        || ecs.read_storage::<Name>().get(item).map_or(false, |name| {
+            ecs.fetch::<MasterDungeonMap>()
+                .identified_items
+                .contains(&name.name)
+        })
+}
+
 pub fn get_item_display_name(ecs: &World, item: Entity) -> String {
     ecs.read_storage::<Name>()
         .get(item)
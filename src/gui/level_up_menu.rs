@@ -0,0 +1,169 @@
+use rltk::{
+    to_cp437, BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, VirtualKeyCode, BLACK, GRAY, GREEN,
+    RGB, WHITE, YELLOW,
+};
+use specs::{Entity, WorldExt};
+
+use crate::components::{AttributeKind, Attributes, LevelUpChoice, Pools, Skill, Skills};
+use crate::gui::menu_option;
+use crate::level_up::perks_on_offer;
+use crate::State;
+
+pub enum LevelUpMenuResult {
+    NoResponse,
+    Done(LevelUpChoice),
+}
+
+/// What's ticked on the level up screen so far.
+#[derive(Default, Clone, Debug)]
+pub struct LevelUpPicks {
+    pub attribute: Option<AttributeKind>,
+    pub skill: Option<Skill>,
+    pub perk: Option<String>,
+}
+
+impl LevelUpPicks {
+    fn choice(&self) -> Option<LevelUpChoice> {
+        Some(LevelUpChoice {
+            attribute: self.attribute?,
+            skill: self.skill?,
+            perk: self.perk.clone(),
+        })
+    }
+}
+
+/// A lettered line, with a tick in front when it's picked.
+fn pick_option(draw_batch: &mut DrawBatch, y: i32, index: usize, text: String, picked: bool) {
+    if picked {
+        draw_batch.print_color(
+            Point::new(11, y),
+            "*",
+            ColorPair::new(RGB::named(GREEN), RGB::named(BLACK)),
+        );
+    }
+    menu_option(
+        draw_batch,
+        12,
+        y,
+        to_cp437('a') + index as rltk::FontCharType,
+        text,
+    );
+}
+
+/// Asks for an attribute and a skill to raise, and optionally a perk. Letters
+/// tick a line, ENTER takes the picks once there's an attribute and a skill.
+/// There's no backing out: the level is already gained.
+pub fn level_up_menu(gs: &State, ctx: &Rltk, picks: &mut LevelUpPicks) -> LevelUpMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let player = *gs.ecs.fetch::<Entity>();
+    let level = gs.ecs.read_storage::<Pools>().get(player).unwrap().level;
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let attributes = attributes.get(player).unwrap();
+    let skills = gs.ecs.read_storage::<Skills>();
+    let skills = skills.get(player).unwrap();
+    let perks = perks_on_offer(&gs.raws, &gs.ecs);
+
+    let yellow = ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK));
+    let gray = ColorPair::new(RGB::named(GRAY), RGB::named(BLACK));
+    let perk_lines = if perks.is_empty() {
+        0
+    } else {
+        perks.len() as i32 + 2
+    };
+    let height = 10 + perk_lines;
+    let mut y = 25 - height / 2;
+    draw_batch.draw_box(
+        Rect::with_size(10, y - 2, 60, height + 2),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(13, y - 2),
+        format!("You are now level {level}!"),
+        yellow,
+    );
+    draw_batch.print_color(Point::new(13, y + height), "ENTER to confirm", yellow);
+
+    draw_batch.print_color(Point::new(12, y), "Raise an attribute:", gray);
+    y += 1;
+    let bases = [
+        attributes.might.base,
+        attributes.fitness.base,
+        attributes.quickness.base,
+        attributes.intelligence.base,
+    ];
+    for (i, (attribute, base)) in AttributeKind::ALL.iter().zip(bases).enumerate() {
+        let picked = picks.attribute == Some(*attribute);
+        pick_option(
+            &mut draw_batch,
+            y,
+            i,
+            format!("{attribute:?} ({base})"),
+            picked,
+        );
+        y += 1;
+    }
+
+    y += 1;
+    draw_batch.print_color(Point::new(12, y), "Train a skill:", gray);
+    y += 1;
+    for (i, skill) in Skill::ALL.iter().enumerate() {
+        let rank = skills.skills.get(skill).copied().unwrap_or(0);
+        let picked = picks.skill == Some(*skill);
+        let index = AttributeKind::ALL.len() + i;
+        pick_option(
+            &mut draw_batch,
+            y,
+            index,
+            format!("{skill:?} ({rank})"),
+            picked,
+        );
+        y += 1;
+    }
+
+    if !perks.is_empty() {
+        y += 1;
+        draw_batch.print_color(Point::new(12, y), "Take a perk, if you like:", gray);
+        y += 1;
+        for (i, perk) in perks.iter().enumerate() {
+            let picked = picks.perk.as_deref() == Some(perk.name.as_str());
+            let index = AttributeKind::ALL.len() + Skill::ALL.len() + i;
+            let text = format!("{}: {}", perk.name, perk.description);
+            pick_option(&mut draw_batch, y, index, text, picked);
+            y += 1;
+        }
+    }
+
+    draw_batch.submit(6000).expect("Draw batch failed");
+
+    match ctx.key {
+        None => LevelUpMenuResult::NoResponse,
+        Some(VirtualKeyCode::Return) => match picks.choice() {
+            Some(choice) => {
+                *picks = LevelUpPicks::default();
+                LevelUpMenuResult::Done(choice)
+            }
+            None => LevelUpMenuResult::NoResponse,
+        },
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection >= 0 {
+                let selection = selection as usize;
+                let skill = selection.checked_sub(AttributeKind::ALL.len());
+                let perk = skill.and_then(|skill| skill.checked_sub(Skill::ALL.len()));
+                if let Some(attribute) = AttributeKind::ALL.get(selection) {
+                    picks.attribute = Some(*attribute);
+                } else if let Some(skill) = skill.and_then(|skill| Skill::ALL.get(skill)) {
+                    picks.skill = Some(*skill);
+                } else if let Some(perk) = perk.and_then(|perk| perks.get(perk)) {
+                    // Picking the ticked perk again unticks it
+                    if picks.perk.as_deref() == Some(perk.name.as_str()) {
+                        picks.perk = None;
+                    } else {
+                        picks.perk = Some(perk.name.clone());
+                    }
+                }
+            }
+            LevelUpMenuResult::NoResponse
+        }
+    }
+}
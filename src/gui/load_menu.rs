@@ -0,0 +1,134 @@
+use rltk::{
+    to_cp437, BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, VirtualKeyCode, BLACK, GRAY, RGB,
+    WHITE, YELLOW,
+};
+
+use crate::gui::menu_option;
+use crate::saveload_system::{format_timestamp, SlotSummary};
+use crate::State;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LoadMenuResult {
+    NoResponse,
+    Cancel,
+    Load(usize),
+    Delete(usize),
+}
+
+fn describe_slot(slot: &SlotSummary) -> String {
+    match &slot.header {
+        None => format!("{:<8} (unreadable)", slot.name),
+        Some(header) => {
+            let meta = &header.metadata;
+            format!(
+                "{:<8} Level {}, {} (depth {}), {} turns, {}",
+                slot.name,
+                meta.level,
+                meta.map_name,
+                meta.depth,
+                meta.turns,
+                format_timestamp(meta.timestamp)
+            )
+        }
+    }
+}
+
+/// Lists the save slots `gs` last read. A letter loads that slot and the same
+/// letter with SHIFT held deletes it.
+pub fn load_game_menu(gs: &State, ctx: &Rltk) -> LoadMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let slots = &gs.save_slots;
+    let count = slots.len() as i32;
+    let mut y = 25 - (count / 2);
+
+    draw_batch.draw_box(
+        Rect::with_size(2, y - 2, 76, count + 3),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(5, y - 2),
+        "Load Game",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(5, y + count + 1),
+        "ESCAPE to cancel, SHIFT+letter to delete",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+
+    for (j, slot) in slots.iter().enumerate() {
+        menu_option(
+            &mut draw_batch,
+            4,
+            y,
+            to_cp437('a') + j as rltk::FontCharType,
+            describe_slot(slot),
+        );
+        y += 1;
+    }
+    if slots.is_empty() {
+        draw_batch.print_color(
+            Point::new(5, y),
+            "No saved games",
+            ColorPair::new(RGB::named(GRAY), RGB::named(BLACK)),
+        );
+    }
+
+    draw_batch.submit(6000).expect("Draw batch failed");
+
+    match ctx.key {
+        None => LoadMenuResult::NoResponse,
+        Some(VirtualKeyCode::Escape) => LoadMenuResult::Cancel,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection < 0 || selection >= count {
+                LoadMenuResult::NoResponse
+            } else if ctx.shift {
+                LoadMenuResult::Delete(selection as usize)
+            } else {
+                LoadMenuResult::Load(selection as usize)
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RecoveryPromptResult {
+    NoResponse,
+    Recover,
+    Dismiss,
+}
+
+/// Offers to pick the run in `slot` back up after the game stopped without
+/// returning to the menu.
+pub fn recovery_prompt(slot: &SlotSummary, ctx: &Rltk) -> RecoveryPromptResult {
+    let mut draw_batch = DrawBatch::new();
+
+    draw_batch.draw_box(
+        Rect::with_size(2, 20, 76, 6),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+    draw_batch.print_color_centered(
+        21,
+        "The game didn't shut down cleanly last time.",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+    draw_batch.print_color_centered(
+        22,
+        describe_slot(slot),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+    draw_batch.print_color_centered(
+        24,
+        "Recover the last autosave? (Y/N)",
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+
+    draw_batch.submit(6000).expect("Draw batch failed");
+
+    match ctx.key {
+        Some(VirtualKeyCode::Y) => RecoveryPromptResult::Recover,
+        Some(VirtualKeyCode::N | VirtualKeyCode::Escape) => RecoveryPromptResult::Dismiss,
+        _ => RecoveryPromptResult::NoResponse,
+    }
+}
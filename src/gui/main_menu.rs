@@ -1,5 +1,5 @@
 use crate::gui::MainMenuResult::{NoSelection, Selected};
-use crate::gui::MainMenuSelection::{LoadGame, NewGame, Quit};
+use crate::gui::MainMenuSelection::{DeathMode, LoadGame, NewGame, Quit};
 use crate::player::RunState;
 use crate::player::RunState::MainMenu;
 use crate::rex_assets::RexAssets;
@@ -10,6 +10,7 @@ use rltk::{ColorPair, DrawBatch, Rect, Rltk, VirtualKeyCode, BLACK, RGB, WHEAT};
 pub enum MainMenuSelection {
     NewGame,
     LoadGame,
+    DeathMode,
     Quit,
 }
 
@@ -22,7 +23,7 @@ pub enum MainMenuResult {
 pub fn main_menu(gs: &State, ctx: &mut Rltk) -> MainMenuResult {
     let mut draw_batch = DrawBatch::new();
 
-    let save_exists = saveload_system::does_save_exist();
+    let save_exists = saveload_system::any_slot_exists();
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
     ctx.render_xp_sprite(&assets.menu, 0, 0);
@@ -85,6 +86,19 @@ pub fn main_menu(gs: &State, ctx: &mut Rltk) -> MainMenuResult {
             y += 1;
         }
 
+        let death_mode = format!("Death: {}", gs.options.death_mode.label());
+        let death_mode_color = if selection == MainMenuSelection::DeathMode {
+            rltk::MAGENTA
+        } else {
+            rltk::WHITE
+        };
+        draw_batch.print_color_centered(
+            y,
+            &death_mode,
+            ColorPair::new(RGB::named(death_mode_color), RGB::named(rltk::BLACK)),
+        );
+        y += 1;
+
         if selection == MainMenuSelection::Quit {
             draw_batch.print_color_centered(
                 y,
@@ -99,6 +113,14 @@ pub fn main_menu(gs: &State, ctx: &mut Rltk) -> MainMenuResult {
             );
         }
 
+        if let Some(error) = &gs.load_error {
+            draw_batch.print_color_centered(
+                29,
+                error,
+                ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)),
+            );
+        }
+
         draw_batch.submit(6000).expect("Batched draw failed");
 
         match ctx.key {
@@ -118,7 +140,8 @@ pub fn main_menu(gs: &State, ctx: &mut Rltk) -> MainMenuResult {
                     match selection {
                         NewGame => newselection = Quit,
                         LoadGame => newselection = NewGame,
-                        Quit => newselection = LoadGame,
+                        DeathMode => newselection = LoadGame,
+                        Quit => newselection = DeathMode,
                     }
                     if newselection == LoadGame && !save_exists {
                         newselection = NewGame;
@@ -131,11 +154,12 @@ pub fn main_menu(gs: &State, ctx: &mut Rltk) -> MainMenuResult {
                     let mut newselection;
                     match selection {
                         NewGame => newselection = LoadGame,
-                        LoadGame => newselection = Quit,
+                        LoadGame => newselection = DeathMode,
+                        DeathMode => newselection = Quit,
                         Quit => newselection = NewGame,
                     }
                     if newselection == LoadGame && !save_exists {
-                        newselection = Quit;
+                        newselection = DeathMode;
                     }
                     return NoSelection {
                         selected: newselection,
@@ -1,10 +1,14 @@
+mod character_menu;
 mod cheat_menu;
+mod class_menu;
 mod drop_item_menu;
 mod game_over_menu;
 mod hud;
 mod identify_menu;
 mod inventory_menu;
 mod item_render;
+mod level_up_menu;
+mod load_menu;
 mod main_menu;
 mod menus;
 mod ranged_target;
@@ -13,7 +17,9 @@ mod remove_item_menu;
 mod tooltips;
 mod vendor_menu;
 
+pub use character_menu::*;
 pub use cheat_menu::*;
+pub use class_menu::*;
 pub use drop_item_menu::*;
 pub use game_over_menu::*;
 pub use hud::*;
@@ -21,6 +27,8 @@ pub use identify_menu::*;
 pub use inventory_menu::*;
 pub use inventory_menu::*;
 pub use item_render::*;
+pub use level_up_menu::*;
+pub use load_menu::*;
 pub use main_menu::*;
 pub use menus::*;
 pub use ranged_target::*;
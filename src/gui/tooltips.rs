@@ -1,5 +1,8 @@
-use crate::components::{Attributes, Duration, Hidden, Name, Pools, StatusEffect};
-use crate::gui::get_item_display_name;
+use crate::components::{
+    Attributes, DamageResponse, Duration, Hidden, InflictsDamage, Name, Pools, Resistances,
+    StatusEffect, StatusInfo, Weapon, Wearable,
+};
+use crate::gui::{get_item_display_name, is_item_identified, status_label};
 use crate::map::camera::get_screen_bounds;
 use crate::map::Map;
 use rltk::{to_cp437, Algorithm2D, BTerm as Rltk, ColorPair, DrawBatch, Point, Rect, BLACK, RGB};
@@ -13,6 +16,10 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let hidden = ecs.read_storage::<Hidden>();
     let attributes = ecs.read_storage::<Attributes>();
     let pools = ecs.read_storage::<Pools>();
+    let weapons = ecs.read_storage::<Weapon>();
+    let inflicts_damage = ecs.read_storage::<InflictsDamage>();
+    let resistances = ecs.read_storage::<Resistances>();
+    let wearables = ecs.read_storage::<Wearable>();
 
     let mouse_pos = ctx.mouse_pos();
     let mut mouse_map_pos = mouse_pos;
@@ -41,7 +48,7 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     }
 
     let mut tip_boxes: Vec<Tooltip> = Vec::new();
-    crate::spatial::for_each_tile_content(mouse_idx, |entity| {
+    map.spatial.for_each_tile_content(mouse_idx, |entity| {
         if hidden.get(entity).is_some() {
             return;
         }
@@ -89,12 +96,70 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
             tip.add(format!("Level: {}", stat.level));
         }
 
+        // Damage, unless that would give away an unidentified item
+        if is_item_identified(ecs, entity) {
+            if let Some(weapon) = weapons.get(entity) {
+                tip.add(format!(
+                    "Damage: {}, {}",
+                    weapon.damage,
+                    weapon.damage.summary()
+                ));
+                tip.add(format!("Deals {}", weapon.damage_type.noun()));
+                let crit_range = if weapon.crit_range < 20 {
+                    format!("{}-20", weapon.crit_range)
+                } else {
+                    "20".to_string()
+                };
+                tip.add(format!(
+                    "Crits on {crit_range}, x{}",
+                    weapon.crit_multiplier
+                ));
+                if weapon.armor_penetration > 0 {
+                    tip.add(format!("Armour penetration {}", weapon.armor_penetration));
+                }
+            }
+            if let Some(wearable) = wearables.get(entity) {
+                if wearable.damage_reduction > 0 {
+                    tip.add(format!("Damage reduction {}", wearable.damage_reduction));
+                }
+            }
+            if let Some(damage) = inflicts_damage.get(entity) {
+                tip.add(format!("Damage: {}", damage.damage.summary()));
+                tip.add(format!("Deals {}", damage.damage_type.noun()));
+            }
+        }
+
+        // What a creature shrugs off, or can't; worn items say what they ward
+        if let Some(resistances) = resistances.get(entity) {
+            let mut lines: Vec<String> = resistances
+                .damage
+                .iter()
+                .map(|(damage_type, response)| {
+                    let response = match response {
+                        DamageResponse::Resistant => "Resists",
+                        DamageResponse::Immune => "Immune to",
+                        DamageResponse::Vulnerable => "Vulnerable to",
+                    };
+                    format!("{response} {}", damage_type.noun())
+                })
+                .collect();
+            lines.sort();
+            lines.into_iter().for_each(|line| tip.add(line));
+        }
+
         let status = ecs.read_storage::<StatusEffect>();
         let durations = ecs.read_storage::<Duration>();
         let names = ecs.read_storage::<Name>();
-        for (status, duration, name) in (&status, &durations, &names).join() {
+        let infos = ecs.read_storage::<StatusInfo>();
+        for (status_entity, status, duration, name) in
+            (&ecs.entities(), &status, &durations, &names).join()
+        {
             if status.target == entity {
-                tip.add(format!("{} ({})", name.name, duration.turns));
+                tip.add(status_label(
+                    &name.name,
+                    infos.get(status_entity),
+                    duration.turns,
+                ));
             }
         }
 
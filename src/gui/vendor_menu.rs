@@ -1,7 +1,7 @@
 use crate::components::{InBackpack, Item, Name, Vendor};
 use crate::gui::{get_item_color, get_item_display_name, menu_box};
 use crate::player::VendorMode;
-use crate::raws::rawmaster::{get_vendor_items, RAWS};
+use crate::raws::rawmaster::{get_vendor_items, RawMaster};
 use crate::State;
 use rltk::{BTerm as Rltk, ColorPair, DrawBatch, Point, VirtualKeyCode, BLACK, RGB, YELLOW};
 use specs::{Entity, Join, WorldExt};
@@ -134,7 +134,7 @@ fn vendor_buy_menu(
     let vendors = gs.ecs.read_storage::<Vendor>();
     let inventory = get_vendor_items(
         &vendors.get(vendor).unwrap().categories,
-        &RAWS.lock().unwrap(),
+        &gs.ecs.fetch::<Arc<RawMaster>>(),
     );
     let count = inventory.len();
 
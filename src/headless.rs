@@ -0,0 +1,177 @@
+use std::fmt;
+
+use rltk::RandomNumberGenerator;
+use specs::{Entity, WorldExt};
+
+use crate::components::{AttributeKind, LevelUpChoice, Pools, Skill};
+use crate::map::Map;
+use crate::player::{PlayerCommand, RunState};
+use crate::replay::Decision;
+use crate::rng::RunSeed;
+use crate::systems::particle_system;
+use crate::State;
+
+/// Particles still need to age without a renderer, so pretend every tick is a 60 fps frame.
+const FRAME_TIME_MS: f32 = 1000.0 / 60.0;
+const DEFAULT_TURNS: i32 = 1000;
+
+/// Decides what the player does whenever the game waits on them.
+pub trait ActionSource {
+    /// Called on `AwaitingInput` and on every menu state, with the same decisions a
+    /// player makes through the keyboard. `None` leaves the game waiting.
+    fn next_action(&mut self, gs: &State, runstate: RunState) -> Option<Decision>;
+}
+
+/// Wanders in random directions and backs out of every menu it stumbles into,
+/// picking at random when it goes up a level.
+/// Rolls its own dice so the game's generator sees the same rolls in a replay.
+pub struct RandomWalker {
+    rng: RandomNumberGenerator,
+}
+
+impl RandomWalker {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: RandomNumberGenerator::seeded(seed),
+        }
+    }
+}
+
+impl ActionSource for RandomWalker {
+    fn next_action(&mut self, _gs: &State, runstate: RunState) -> Option<Decision> {
+        if runstate == RunState::LevelUp {
+            let attribute = self.rng.random_slice_entry(&AttributeKind::ALL).unwrap();
+            let skill = self.rng.random_slice_entry(&Skill::ALL).unwrap();
+            return Some(Decision::LevelUp(LevelUpChoice {
+                attribute: *attribute,
+                skill: *skill,
+                perk: None,
+            }));
+        }
+        if runstate != RunState::AwaitingInput {
+            return Some(Decision::Cancel);
+        }
+
+        let command = match self.rng.roll_dice(1, 9) {
+            1 => PlayerCommand::Move { dx: -1, dy: 0 },
+            2 => PlayerCommand::Move { dx: 1, dy: 0 },
+            3 => PlayerCommand::Move { dx: 0, dy: -1 },
+            4 => PlayerCommand::Move { dx: 0, dy: 1 },
+            5 => PlayerCommand::Move { dx: -1, dy: -1 },
+            6 => PlayerCommand::Move { dx: 1, dy: -1 },
+            7 => PlayerCommand::Move { dx: -1, dy: 1 },
+            8 => PlayerCommand::Move { dx: 1, dy: 1 },
+            _ => PlayerCommand::Skip,
+        };
+        Some(Decision::Command(command))
+    }
+}
+
+pub struct Report {
+    pub seed: u64,
+    pub turns: i32,
+    pub depth: i32,
+    pub level: i32,
+    pub hit_points: i32,
+    pub dead: bool,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed: {}, turns: {}, depth: {}, level: {}, hp: {}, {}",
+            self.seed,
+            self.turns,
+            self.depth,
+            self.level,
+            self.hit_points,
+            if self.dead { "dead" } else { "alive" }
+        )
+    }
+}
+
+pub fn turns_from_args(args: &[String]) -> Option<i32> {
+    let flag = args.iter().position(|arg| arg == "--headless")?;
+    Some(
+        args.get(flag + 1)
+            .and_then(|turns| turns.parse().ok())
+            .unwrap_or(DEFAULT_TURNS),
+    )
+}
+
+/// Reads the seed given with `--seed N`, if any.
+pub fn seed_from_args(args: &[String]) -> Option<u64> {
+    let flag = args.iter().position(|arg| arg == "--seed")?;
+    args.get(flag + 1)?.parse().ok()
+}
+
+/// Advances the game by one frame without rendering anything.
+pub fn tick(gs: &mut State, source: &mut dyn ActionSource) -> RunState {
+    let mut newrunstate = *gs.ecs.fetch::<RunState>();
+    particle_system::update_particles(&mut gs.ecs, FRAME_TIME_MS);
+
+    match newrunstate {
+        RunState::AwaitingInput
+        | RunState::ShowInventory
+        | RunState::ShowDropItem
+        | RunState::ShowTargeting { .. }
+        | RunState::ShowRemoveItem
+        | RunState::ShowCheatMenu
+        | RunState::ShowVendor { .. }
+        | RunState::ShowRemoveCurse
+        | RunState::ShowIdentify
+        | RunState::LevelUp => {
+            let decision = gs.take_decision(|gs| source.next_action(gs, newrunstate));
+            if let Some(decision) = decision {
+                newrunstate = gs.apply_decision(newrunstate, decision);
+            }
+        }
+        RunState::MainMenu { .. }
+        | RunState::ChooseClass
+        | RunState::CreateCharacter
+        | RunState::ShowLoadGame
+        | RunState::RecoveryPrompt
+        | RunState::GameOver
+        | RunState::SaveGame => {}
+        _ => newrunstate = gs.advance_simulation(newrunstate),
+    }
+    gs.commit_runstate(newrunstate);
+
+    *gs.ecs.fetch::<RunState>()
+}
+
+/// Plays the game the state was set up with until `max_turns` player turns have
+/// passed, the player dies, the action source leaves the game or a replay ends.
+pub fn run(gs: &mut State, source: &mut dyn ActionSource, max_turns: i32) -> Report {
+    *gs.ecs.write_resource::<RunState>() = RunState::PreRun;
+
+    let mut turns = 0;
+    while turns < max_turns {
+        let previous = *gs.ecs.fetch::<RunState>();
+        let runstate = tick(gs, source);
+        if previous == RunState::AwaitingInput && runstate != RunState::AwaitingInput {
+            turns += 1;
+        }
+
+        if matches!(
+            runstate,
+            RunState::GameOver | RunState::MainMenu { .. } | RunState::SaveGame
+        ) || gs.replay_verified().is_some()
+        {
+            break;
+        }
+    }
+
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let player_pools = pools.get(player_entity);
+    Report {
+        seed: gs.ecs.fetch::<RunSeed>().0,
+        turns,
+        depth: gs.ecs.fetch::<Map>().depth,
+        level: player_pools.map_or(0, |p| p.level),
+        hit_points: player_pools.map_or(0, |p| p.hit_points.current),
+        dead: *gs.ecs.fetch::<RunState>() == RunState::GameOver,
+    }
+}
@@ -0,0 +1,220 @@
+//! What the player can pick when they go up a level, and what picking it does.
+
+use std::sync::Arc;
+
+use rltk::{GREEN, MAGENTA};
+use specs::{Entity, World, WorldExt};
+
+use crate::components::{
+    AttributeKind, Attributes, CharacterHistory, EquipmentChanged, KnownSpells, LevelUpChoice,
+    LevelUpRecord, Pools, Skill, Skills,
+};
+use crate::gamelog::{self, GameLog};
+use crate::gamesystem::{mana_at_level, player_hp_at_level};
+use crate::player::RunState;
+use crate::raws::rawmaster::RawMaster;
+use crate::raws::Perk;
+
+/// Levels the player has gained whose picks are still to be made. The level up
+/// screen waits for the turn they were gained in to play out.
+#[derive(Default)]
+pub struct PendingLevelUps(pub i32);
+
+/// The state to carry on in once the systems have settled on `runstate`: the level
+/// up screen when it's the player's turn and they have a level to spend.
+pub fn after_turn(ecs: &World, runstate: RunState) -> RunState {
+    let mut pending = ecs.write_resource::<PendingLevelUps>();
+    if runstate == RunState::AwaitingInput && pending.0 > 0 {
+        pending.0 -= 1;
+        RunState::LevelUp
+    } else {
+        runstate
+    }
+}
+
+/// The perks the player can take at their current level: those they're high
+/// enough level for and haven't taken already.
+pub fn perks_on_offer<'a>(raws: &'a RawMaster, ecs: &World) -> Vec<&'a Perk> {
+    let player = *ecs.fetch::<Entity>();
+    let level = ecs.read_storage::<Pools>().get(player).unwrap().level;
+    let histories = ecs.read_storage::<CharacterHistory>();
+    let history = histories.get(player);
+    raws.perks()
+        .iter()
+        .filter(|perk| perk.min_level <= level)
+        .filter(|perk| !history.is_some_and(|history| history.has_perk(&perk.name)))
+        .collect()
+}
+
+fn attribute_message(attribute: AttributeKind) -> &'static str {
+    match attribute {
+        AttributeKind::Might => "You feel stronger!",
+        AttributeKind::Fitness => "You feel healthier!",
+        AttributeKind::Quickness => "You feel quicker!",
+        AttributeKind::Intelligence => "You feel smarter!",
+    }
+}
+
+fn add_skill(skills: &mut Skills, skill: Skill, ranks: i32) {
+    *skills.skills.entry(skill).or_insert(0) += ranks;
+}
+
+/// Raises what the player picked on the level up screen, tops up their pools at
+/// the new level and writes the picks into their history.
+pub fn apply_level_up(ecs: &World, choice: &LevelUpChoice) {
+    let player = *ecs.fetch::<Entity>();
+    let raws = ecs.fetch::<Arc<RawMaster>>().clone();
+    let perk = choice.perk.as_deref().and_then(|name| raws.perk(name));
+
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let player_attributes = attributes.get_mut(player).unwrap();
+    let mut skills = ecs.write_storage::<Skills>();
+    let player_skills = skills.get_mut(player).unwrap();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    player_attributes.get_mut(choice.attribute).base += 1;
+    gamelog::Logger::new()
+        .color(GREEN)
+        .append(attribute_message(choice.attribute))
+        .log(&mut log);
+    add_skill(player_skills, choice.skill, 1);
+    gamelog::Logger::new()
+        .color(GREEN)
+        .append(format!("Your {:?} skill improves.", choice.skill))
+        .log(&mut log);
+
+    if let Some(perk) = perk {
+        for (attribute, points) in &perk.attributes {
+            player_attributes.get_mut(*attribute).base += points;
+        }
+        for (skill, ranks) in &perk.skills {
+            add_skill(player_skills, *skill, *ranks);
+        }
+        let mut known_spells = ecs.write_storage::<KnownSpells>();
+        if let Some(known) = known_spells.get_mut(player) {
+            for spell in perk
+                .spells
+                .iter()
+                .filter_map(|spell| raws.known_spell(spell))
+            {
+                if !known
+                    .spells
+                    .iter()
+                    .any(|known| known.display_name == spell.display_name)
+                {
+                    known.spells.push(spell);
+                }
+            }
+        }
+        gamelog::Logger::new()
+            .color(MAGENTA)
+            .append(format!("You take the {} perk.", perk.name))
+            .log(&mut log);
+    }
+
+    let mut pools = ecs.write_storage::<Pools>();
+    let player_pools = pools.get_mut(player).unwrap();
+    player_pools.hit_points.max = player_hp_at_level(
+        player_attributes.fitness.base + player_attributes.fitness.modifiers,
+        player_pools.level,
+    );
+    player_pools.hit_points.current = player_pools.hit_points.max;
+    player_pools.mana.max = mana_at_level(
+        player_attributes.intelligence.base + player_attributes.intelligence.modifiers,
+        player_pools.level,
+    );
+    player_pools.mana.current = player_pools.mana.max;
+
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player, EquipmentChanged {})
+        .expect("Insert failed");
+
+    // Saves from before histories were kept have a player without one
+    ecs.write_storage::<CharacterHistory>()
+        .entry(player)
+        .expect("Player entity is dead")
+        .or_insert_with(CharacterHistory::default)
+        .level_ups
+        .push(LevelUpRecord {
+            level: player_pools.level,
+            choice: choice.clone(),
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use super::{apply_level_up, perks_on_offer, PendingLevelUps};
+    use crate::components::{
+        AttributeKind, Attributes, CharacterHistory, LevelUpChoice, Pools, Skill, Skills,
+    };
+    use crate::effects::{add_effect, run_effects_queue, EffectType, Targets};
+    use crate::player::RunState;
+    use crate::test_support::{shared_raws, Scenario};
+
+    #[test]
+    fn level_ups_wait_for_the_players_picks() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        let rat = scenario.spawn("Rat", 3, 1);
+        scenario
+            .ecs
+            .write_storage::<Pools>()
+            .get_mut(player)
+            .unwrap()
+            .xp = 999;
+
+        add_effect(
+            &scenario.ecs,
+            Some(player),
+            EffectType::EntityDeath,
+            Targets::Single { target: rat },
+        );
+        run_effects_queue(&mut scenario.ecs);
+
+        // The turn the level was gained in plays out before the screen comes up
+        assert!(*scenario.ecs.fetch::<RunState>() != RunState::LevelUp);
+        assert_eq!(scenario.ecs.fetch::<PendingLevelUps>().0, 1);
+        scenario.run_turn();
+        assert!(*scenario.ecs.fetch::<RunState>() == RunState::LevelUp);
+        assert_eq!(scenario.ecs.fetch::<PendingLevelUps>().0, 0);
+        assert_eq!(scenario.pools(player).level, 2);
+        let raws = shared_raws();
+        let offered = |scenario: &Scenario| -> Vec<String> {
+            perks_on_offer(&raws, &scenario.ecs)
+                .iter()
+                .map(|perk| perk.name.clone())
+                .collect()
+        };
+        assert!(offered(&scenario).contains(&"Brawler".to_string()));
+        assert!(!offered(&scenario).contains(&"Hedge Magic".to_string()));
+
+        let choice = LevelUpChoice {
+            attribute: AttributeKind::Might,
+            skill: Skill::Magic,
+            perk: Some("Brawler".to_string()),
+        };
+        apply_level_up(&scenario.ecs, &choice);
+
+        let attributes = scenario.ecs.read_storage::<Attributes>();
+        assert_eq!(attributes.get(player).unwrap().might.base, 16);
+        let skills = scenario.ecs.read_storage::<Skills>();
+        let skills = &skills.get(player).unwrap().skills;
+        assert_eq!(skills[&Skill::Melee], 3);
+        assert_eq!(skills[&Skill::Magic], 2);
+        assert_eq!(scenario.hit_points(player), scenario.max_hit_points(player));
+        let histories = scenario.ecs.read_storage::<CharacterHistory>();
+        let history = histories.get(player).unwrap();
+        assert_eq!(history.level_ups.len(), 1);
+        assert_eq!(history.level_ups[0].level, 2);
+        assert_eq!(history.level_ups[0].choice, choice);
+        assert!(!offered(&scenario).contains(&"Brawler".to_string()));
+    }
+}
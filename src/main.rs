@@ -1,65 +1,67 @@
 #![feature(never_type)]
 
-use rltk::{GameState, Point, CYAN};
+use std::sync::Arc;
+
+use rltk::{GameState, Point, RandomNumberGenerator, CYAN, RED};
 use specs::prelude::*;
-use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 
 use components::{
-    ApplyMove, ApplyTeleport, AreaOfEffect, Attributes, BlocksTile, BlocksVisibility, Chasing,
-    Confusion, Consumable, DMSerializationHelper, DefenseBonus, Door, EntityMoved, EntryTrigger,
-    EquipmentChanged, Equippable, Equipped, Faction, Hidden, HungerClock, IdentifiedItem,
-    InBackpack, InflictsDamage, Initiative, Item, LightSource, LootTable, MagicItem, MagicMapper,
-    MeleePowerBonus, MoveMode, MyTurn, Name, NaturalAttackDefense, ObfuscatedName,
-    OtherLevelPosition, ParticleLifetime, Player, Pools, Position, ProvidesFood, ProvidesHealing,
-    ProvidesRemoveCurse, Quips, Ranged, Renderable, SerializationHelper, SerializeMe,
-    SingleActivation, Skills, SpawnParticleBurst, SpawnParticleLine, TeleportTo, Vendor, Viewshed,
-    WantsToApproach, WantsToDropItem, WantsToFlee, WantsToMelee, WantsToPickupItem,
-    WantsToRemoveItem, WantsToUseItem, Wearable,
+    CharacterHistory, CursedItem, IdentifiedItem, Item, Name, Player, Pools, Position, Ranged,
+    SpellTemplate, WantsToCastSpell, WantsToDropItem, WantsToRemoveItem, WantsToUseItem,
 };
 
 use map::Map;
 use player::RunState;
 use RunState::PreRun;
 
-use crate::components::{
-    AlwaysTargetsSelf, AttributeBonus, CursedItem, DamageOverTime, Duration, KnownSpells, OnDeath,
-    ProvidesIdentification, ProvidesMana, Slow, SpecialAbilities, SpellTemplate, StatusEffect,
-    Target, TeachesSpell, TileSize, WantsToCastSpell, WantsToShoot, Weapon,
-};
+use crate::effects::EffectQueue;
+use crate::gamelog::{GameEvents, GameLog};
 use crate::map::dungeon::{
     freeze_level_entities, level_transition, thaw_level_entities, MasterDungeonMap,
 };
+use crate::options::{DeathMode, GameOptions};
 use crate::player::RunState::{
     AwaitingInput, GameOver, MagicMapReveal, MainMenu, MapGeneration, NextLevel, PreviousLevel,
     SaveGame, ShowCheatMenu, ShowDropItem, ShowInventory, ShowRemoveItem, ShowTargeting, Ticking,
     TownPortal,
 };
 use crate::player::VendorMode;
-use crate::raws::rawmaster::{spawn_named_item, SpawnType, RAWS};
+use crate::raws::rawmaster::{spawn_named_item, RawMaster, SpawnType};
+use crate::replay::{Decision, Playback, Replay};
+use crate::saveload_system::SaveSlot;
+use crate::spawner::NewCharacter;
 use map::camera::{render_camera, render_debug_map};
 
 use systems::{inventory_system, particle_system};
 
 mod astar;
 mod cave_decorator;
+mod component_registry;
 mod components;
 mod damage_system;
+mod dice;
 pub mod effects;
 mod gamelog;
 mod gamesystem;
 mod gui;
+mod headless;
+mod level_up;
 mod map;
 mod map_builders;
+mod options;
 mod player;
 mod random_table;
 mod raws;
 mod rect;
+mod replay;
 mod rex_assets;
 mod rng;
 mod saveload_system;
 mod spatial;
 mod spawner;
 mod systems;
+#[cfg(test)]
+mod test_support;
 
 const SHOW_MAPGEN_VISUALIZER: bool = false;
 const SHOW_FPS: bool = true;
@@ -71,9 +73,198 @@ pub struct State {
     mapgen_index: usize,
     mapgen_timer: f32,
     dispatcher: Box<dyn systems::UnifiedDispatcher + 'static>,
+    raws: Arc<RawMaster>,
+    recording: Option<Replay>,
+    playback: Option<Playback>,
+    /// Why the last attempt to load a save failed, shown on the main menu.
+    load_error: Option<String>,
+    /// The slots the load screen lists, read when it opens.
+    save_slots: Vec<saveload_system::SlotSummary>,
+    options: GameOptions,
+    /// Whether runs save by themselves and leave a session file to spot crashes
+    /// with. Only the windowed game turns it on.
+    autosave: bool,
+    /// Set when the run should autosave as soon as it's the player's turn again.
+    autosave_due: bool,
+    /// An autosave the player can go back to, after a crash or a death.
+    recovery: Option<saveload_system::SlotSummary>,
+    /// The character being made on the creation screen.
+    creation: Option<gui::CharacterSheet>,
+    /// What's ticked on the level up screen.
+    level_up: gui::LevelUpPicks,
 }
 
 impl State {
+    /// Builds a world with every component registered, the raws loaded and the
+    /// town generated. Needs no rltk context, so it backs both frontends.
+    pub fn new() -> Self {
+        Self::with_seed(rng::new_seed())
+    }
+
+    /// Like [`State::new`], but the first run is played from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut gs = Self {
+            ecs: new_world(),
+            mapgen_next_state: Some(MainMenu {
+                menu_selection: gui::MainMenuSelection::NewGame,
+            }),
+            mapgen_index: 0,
+            mapgen_history: Vec::new(),
+            mapgen_timer: 0.0,
+            dispatcher: systems::build(),
+            raws: Arc::new(raws::load_raws()),
+            recording: None,
+            playback: None,
+            load_error: None,
+            save_slots: Vec::new(),
+            options: GameOptions::default(),
+            autosave: false,
+            autosave_due: false,
+            recovery: None,
+            creation: None,
+            level_up: gui::LevelUpPicks::default(),
+        };
+
+        let character = gs.default_character();
+        gs.new_game(seed, &character);
+
+        gs
+    }
+
+    /// Throws the current world away and starts a fresh run from `seed`. Everything
+    /// random from here on, from the potion names to the town layout, comes out of
+    /// the generator seeded here, so the same seed and inputs replay the same game.
+    fn new_game(&mut self, seed: u64, character: &NewCharacter) {
+        self.recording = Some(Replay::new(seed, character.clone()));
+        self.playback = None;
+        self.ecs = new_world();
+        self.ecs.insert(rng::RunSeed(seed));
+        self.ecs.insert(saveload_system::next_free_slot());
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        let dungeon_master = MasterDungeonMap::new(&self.raws, &mut rng);
+        self.ecs.insert(rng);
+        self.ecs.insert(self.raws.clone());
+
+        self.ecs.insert(dungeon_master);
+        self.ecs.insert(Map::new(1, 64, 64, "New Map"));
+        self.ecs.insert(Point::new(0, 0));
+        let player_entity = spawner::player(&mut self.ecs, 0, 0, character);
+        self.ecs.insert(player_entity);
+        self.ecs.insert(MapGeneration {});
+        self.ecs.insert(particle_system::ParticleBuilder::new());
+        self.ecs.insert(rex_assets::RexAssets::new());
+
+        self.generate_world_map(1, 0);
+    }
+
+    /// Who runs start as when the player hasn't made anyone: the first class the
+    /// raws list, as it comes.
+    fn default_character(&self) -> NewCharacter {
+        NewCharacter::of_class(&self.raws.classes()[0])
+    }
+
+    /// Reads the player's options and turns on autosaving. When the last session
+    /// stopped in the middle of a run, the player is asked whether to recover it,
+    /// unless playing strict permadeath, which gives that run up.
+    pub fn start_session(&mut self) {
+        self.options = options::load_options();
+        self.autosave = true;
+        if let Some(slot) = saveload_system::interrupted_session() {
+            match self.options.death_mode {
+                DeathMode::RecoverAutosave => {
+                    self.recovery = Some(slot);
+                    self.mapgen_next_state = Some(RunState::RecoveryPrompt);
+                }
+                DeathMode::Permadeath => {
+                    saveload_system::delete_slot(&slot.name);
+                    saveload_system::clear_session();
+                }
+            }
+        }
+    }
+
+    /// Loads `slot` and carries on playing it, or goes back to the main menu with
+    /// the reason it couldn't be loaded.
+    fn resume_slot(&mut self, slot: &str) -> RunState {
+        self.recovery = None;
+        match saveload_system::load_game(&mut self.ecs, slot) {
+            Ok(()) => {
+                // A loaded game can't be replayed from its seed alone
+                self.recording = None;
+                // Loading uses the save up, so a run can't be taken back to before
+                // something went wrong
+                if self.options.death_mode == DeathMode::Permadeath {
+                    saveload_system::delete_slot(slot);
+                }
+                if self.autosave {
+                    saveload_system::mark_session(slot);
+                }
+                AwaitingInput
+            }
+            Err(e) => {
+                // The world may be half loaded, so start over behind the menu
+                let character = self.default_character();
+                self.new_game(rng::new_seed(), &character);
+                self.load_error = Some(e.to_string());
+                MainMenu {
+                    menu_selection: gui::MainMenuSelection::LoadGame,
+                }
+            }
+        }
+    }
+
+    /// Parses the raws on disk again and swaps them in. Anything spawned from here
+    /// on uses the new templates; what's already in the world is left as it was.
+    fn reload_raws(&mut self) {
+        let dir = raws::raws_dir();
+        let logger = match raws::load_raws_from(&dir) {
+            Ok(reloaded) => {
+                self.raws = Arc::new(reloaded);
+                self.ecs.insert(self.raws.clone());
+                let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+                self.ecs
+                    .write_resource::<MasterDungeonMap>()
+                    .name_unidentified_items(&self.raws, &mut rng);
+                gamelog::Logger::new().append(format!("Reloaded the raws in {}.", dir.display()))
+            }
+            Err(e) => {
+                raws::log_raws_error(&e);
+                gamelog::Logger::new().color(RED).append(e)
+            }
+        };
+        logger.log(&mut self.ecs.write_resource::<GameLog>());
+    }
+
+    fn autosave(&mut self) {
+        self.autosave_due = false;
+        if self.autosave && self.playback.is_none() {
+            saveload_system::save_game(&mut self.ecs, self.options.save_encoding);
+        }
+    }
+
+    /// Leaves the current run for the menu. It's no longer being played, so a
+    /// later start has nothing to recover.
+    fn end_session(&mut self) {
+        self.recovery = None;
+        if self.autosave {
+            saveload_system::clear_session();
+        }
+    }
+
+    /// Starts the replay's run over from its seed and plays its decisions back.
+    pub fn start_replay(&mut self, replay: Replay) {
+        self.new_game(replay.seed, &replay.character);
+        self.recording = None;
+        self.playback = Some(Playback::new(replay));
+        self.mapgen_next_state = Some(PreRun);
+    }
+
+    /// Whether a replay has played all its decisions and ended in the recorded world.
+    /// `None` when no replay has finished.
+    pub fn replay_verified(&self) -> Option<bool> {
+        self.playback.as_ref().and_then(Playback::verified)
+    }
+
     fn run_systems(&mut self) {
         self.dispatcher.run_now(&mut self.ecs);
         self.ecs.maintain();
@@ -90,13 +281,23 @@ impl State {
             thaw_level_entities(&self.ecs);
         }
 
-        gamelog::clear_log();
+        let player_name = {
+            let player = *self.ecs.fetch::<Entity>();
+            self.ecs
+                .read_storage::<Name>()
+                .get(player)
+                .unwrap()
+                .name
+                .clone()
+        };
+        let mut log = self.ecs.write_resource::<GameLog>();
+        log.clear();
         gamelog::Logger::new()
-            .append("Welcome to")
+            .append(format!("Welcome, {player_name}, to"))
             .color(CYAN)
             .append("Rusty Roguelike")
-            .log();
-        gamelog::clear_events();
+            .log(&mut log);
+        self.ecs.write_resource::<GameEvents>().clear();
     }
 
     fn goto_level(&mut self, offset: i32) {
@@ -107,172 +308,293 @@ impl State {
         self.generate_world_map(current_depth + offset, offset);
 
         // Notify the player
-        gamelog::Logger::new().append("You change level.").log();
+        gamelog::Logger::new()
+            .append("You change level.")
+            .log(&mut self.ecs.write_resource::<GameLog>());
+        self.autosave_due = true;
     }
 
-    fn game_over_cleanup(&mut self) {
-        let mut to_delete = Vec::new();
-        for e in self.ecs.entities().join() {
-            to_delete.push(e);
+    /// Hands out the next replayed decision while a replay is playing and asks
+    /// `read` for one otherwise.
+    fn take_decision(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Option<Decision>,
+    ) -> Option<Decision> {
+        if let Some(playback) = &mut self.playback {
+            if playback.verified().is_none() {
+                return playback.next_decision();
+            }
         }
+        read(self)
+    }
 
-        for del in &to_delete {
-            self.ecs.delete_entity(*del).expect("Deletion failed");
-        }
+    /// Draws the screen for `runstate` and turns this frame's input into a decision.
+    fn read_decision(&mut self, ctx: &mut rltk::Rltk, runstate: RunState) -> Option<Decision> {
+        let item_decision = |result: (gui::ItemMenuResult, Option<Entity>)| match result.0 {
+            gui::ItemMenuResult::Cancel => Some(Decision::Cancel),
+            gui::ItemMenuResult::NoResponse => None,
+            gui::ItemMenuResult::Selected => Some(Decision::SelectItem {
+                item: result.1.unwrap().id(),
+            }),
+        };
 
-        {
-            let player_entity = spawner::player(&mut self.ecs, 0, 0);
-            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
-            *player_entity_writer = player_entity;
+        match runstate {
+            AwaitingInput => Player::read_command(ctx).map(Decision::Command),
+            ShowInventory => item_decision(gui::show_inventory(self, ctx)),
+            ShowDropItem => item_decision(gui::drop_item_menu(self, ctx)),
+            ShowRemoveItem => item_decision(gui::remove_item_menu(self, ctx)),
+            RunState::ShowRemoveCurse => item_decision(gui::remove_curse_menu(self, ctx)),
+            RunState::ShowIdentify => item_decision(gui::identify_menu(self, ctx)),
+            ShowTargeting { range, .. } => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => Some(Decision::Cancel),
+                    gui::ItemMenuResult::NoResponse => None,
+                    gui::ItemMenuResult::Selected => {
+                        let target = result.1.unwrap();
+                        Some(Decision::Target {
+                            x: target.x,
+                            y: target.y,
+                        })
+                    }
+                }
+            }
+            ShowCheatMenu => match gui::show_cheat_mode(self, ctx) {
+                gui::CheatMenuResult::NoResponse => None,
+                gui::CheatMenuResult::Cancel => Some(Decision::Cancel),
+                result => Some(Decision::Cheat(result)),
+            },
+            RunState::ShowVendor { vendor, mode } => {
+                let result = gui::show_vendor_menu(self, ctx, vendor, mode);
+                match result.0 {
+                    gui::VendorResult::NoResponse => None,
+                    gui::VendorResult::Cancel => Some(Decision::Cancel),
+                    gui::VendorResult::Sell => Some(Decision::Sell {
+                        item: result.1.unwrap().id(),
+                    }),
+                    gui::VendorResult::Buy => Some(Decision::Buy {
+                        tag: result.2.unwrap(),
+                        price: result.3.unwrap(),
+                    }),
+                    gui::VendorResult::BuyMode => Some(Decision::VendorMode(VendorMode::Buy)),
+                    gui::VendorResult::SellMode => Some(Decision::VendorMode(VendorMode::Sell)),
+                }
+            }
+            RunState::LevelUp => {
+                let mut picks = std::mem::take(&mut self.level_up);
+                let result = gui::level_up_menu(self, ctx, &mut picks);
+                self.level_up = picks;
+                match result {
+                    gui::LevelUpMenuResult::NoResponse => None,
+                    gui::LevelUpMenuResult::Done(choice) => Some(Decision::LevelUp(choice)),
+                }
+            }
+            _ => None,
         }
-
-        self.ecs.insert(MasterDungeonMap::default());
-
-        self.generate_world_map(1, 0);
     }
-}
-
-impl GameState for State {
-    fn tick(&mut self, ctx: &mut rltk::Rltk) {
-        let mut newrunstate;
-        {
-            let runstate = self.ecs.fetch::<RunState>();
-            newrunstate = *runstate;
-        }
 
-        ctx.set_active_console(1);
-        ctx.cls();
-        ctx.set_active_console(0);
-        ctx.cls();
-        particle_system::update_particles(&mut self.ecs, ctx);
-
-        match newrunstate {
-            RunState::MainMenu { .. } => {}
-            GameOver { .. } => {}
-            _ => {
-                render_camera(&self.ecs, ctx);
-                gui::draw_ui(&self.ecs, ctx);
-            }
+    /// Carries out a decision made on the screen for `runstate`. Both frontends and
+    /// replays go through here, which is what keeps a replay faithful to the run.
+    fn apply_decision(&mut self, runstate: RunState, decision: Decision) -> RunState {
+        if let Some(recording) = &mut self.recording {
+            recording.decisions.push(decision.clone());
         }
+        particle_system::cull_particles(&mut self.ecs);
 
-        match newrunstate {
-            PreRun => {
-                self.run_systems();
-                self.ecs.maintain();
-                newrunstate = AwaitingInput;
-            }
-            AwaitingInput => {
-                newrunstate = Player::player_input(self, ctx);
-                if newrunstate != RunState::AwaitingInput {
-                    gamelog::record_event("Turn", 1);
+        let mut newrunstate = runstate;
+        match (runstate, decision) {
+            (AwaitingInput, Decision::Command(command)) => {
+                newrunstate = Player::perform(self, command);
+                if newrunstate != AwaitingInput {
+                    self.ecs.write_resource::<GameEvents>().record("Turn", 1);
+                    let mut turns = self.ecs.write_resource::<rng::TurnsPlayed>();
+                    turns.0 += 1;
+                    let every = self.options.autosave_turns;
+                    if every > 0 && turns.0 % every == 0 {
+                        self.autosave_due = true;
+                    }
                 }
             }
-            ShowInventory => {
-                let result = gui::show_inventory(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let is_ranged = self.ecs.read_storage::<Ranged>();
-                        let is_item_ranged = is_ranged.get(item_entity);
-                        if let Some(is_item_ranged) = is_item_ranged {
-                            newrunstate = ShowTargeting {
-                                range: is_item_ranged.range,
+            // The level is already gained, so there's no backing out of it
+            (RunState::LevelUp, Decision::Cancel) => {}
+            (_, Decision::Cancel) => newrunstate = AwaitingInput,
+            (ShowInventory, Decision::SelectItem { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                let is_ranged = self.ecs.read_storage::<Ranged>();
+                let is_item_ranged = is_ranged.get(item_entity);
+                if let Some(is_item_ranged) = is_item_ranged {
+                    newrunstate = ShowTargeting {
+                        range: is_item_ranged.range,
+                        item: item_entity,
+                    };
+                } else {
+                    let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                    intent
+                        .insert(
+                            *self.ecs.fetch::<Entity>(),
+                            WantsToUseItem {
                                 item: item_entity,
-                            };
-                        } else {
-                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                            intent
-                                .insert(
-                                    *self.ecs.fetch::<Entity>(),
-                                    WantsToUseItem {
-                                        item: item_entity,
-                                        target: None,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = Ticking;
-                        }
-                    }
+                                target: None,
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    newrunstate = Ticking;
                 }
             }
-            ShowDropItem => {
-                let result = gui::drop_item_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
-                        intent
-                            .insert(
-                                *self.ecs.fetch::<Entity>(),
-                                WantsToDropItem { item: item_entity },
-                            )
-                            .expect("Unable to insert drop intent");
-                        newrunstate = Ticking;
-                    }
+            (ShowDropItem, Decision::SelectItem { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                intent
+                    .insert(
+                        *self.ecs.fetch::<Entity>(),
+                        WantsToDropItem { item: item_entity },
+                    )
+                    .expect("Unable to insert drop intent");
+                newrunstate = Ticking;
+            }
+            (ShowTargeting { item, .. }, Decision::Target { x, y }) => {
+                let target = Some(Point::new(x, y));
+                if self.ecs.read_storage::<SpellTemplate>().get(item).is_some() {
+                    let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                    intent
+                        .insert(
+                            *self.ecs.fetch::<Entity>(),
+                            WantsToCastSpell {
+                                spell: item,
+                                target,
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    newrunstate = RunState::Ticking;
+                } else {
+                    let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                    intent
+                        .insert(*self.ecs.fetch::<Entity>(), WantsToUseItem { item, target })
+                        .expect("Unable to insert intent");
+                    newrunstate = Ticking;
                 }
             }
-            ShowTargeting { range, item } => {
-                let result = gui::ranged_target(self, ctx, range);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        if self.ecs.read_storage::<SpellTemplate>().get(item).is_some() {
-                            let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
-                            intent
-                                .insert(
-                                    *self.ecs.fetch::<Entity>(),
-                                    WantsToCastSpell {
-                                        spell: item,
-                                        target: result.1,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = RunState::Ticking;
-                        } else {
-                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                            intent
-                                .insert(
-                                    *self.ecs.fetch::<Entity>(),
-                                    WantsToUseItem {
-                                        item,
-                                        target: result.1,
-                                    },
-                                )
-                                .expect("Unable to insert intent");
-                            newrunstate = Ticking;
-                        }
+            (ShowRemoveItem, Decision::SelectItem { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                intent
+                    .insert(
+                        *self.ecs.fetch::<Entity>(),
+                        WantsToRemoveItem { item: item_entity },
+                    )
+                    .expect("Unable to insert drop intent");
+                newrunstate = Ticking;
+            }
+            (ShowCheatMenu, Decision::Cheat(result)) => match result {
+                gui::CheatMenuResult::Cancel => newrunstate = AwaitingInput,
+                gui::CheatMenuResult::NoResponse => {}
+                gui::CheatMenuResult::TeleportToExit => {
+                    self.goto_level(1);
+                    self.mapgen_next_state = Some(PreRun);
+                    newrunstate = MapGeneration;
+                }
+                gui::CheatMenuResult::Heal => {
+                    let player = self.ecs.fetch::<Entity>();
+                    let mut pools = self.ecs.write_storage::<Pools>();
+                    let player_pools = pools.get_mut(*player).unwrap();
+                    player_pools.hit_points.current = player_pools.hit_points.max;
+                    newrunstate = RunState::AwaitingInput;
+                }
+                gui::CheatMenuResult::Reveal => {
+                    let mut map = self.ecs.fetch_mut::<Map>();
+                    for v in &mut map.revealed_tiles {
+                        *v = true;
                     }
+                    newrunstate = RunState::AwaitingInput;
+                }
+                gui::CheatMenuResult::GodMode => {
+                    let player = self.ecs.fetch::<Entity>();
+                    let mut pools = self.ecs.write_storage::<Pools>();
+                    let player_pools = pools.get_mut(*player).unwrap();
+                    player_pools.god_mode = true;
+                    newrunstate = RunState::AwaitingInput;
                 }
+                gui::CheatMenuResult::ReloadRaws => {
+                    self.reload_raws();
+                    newrunstate = RunState::AwaitingInput;
+                }
+            },
+            (RunState::ShowVendor { vendor, .. }, Decision::VendorMode(mode)) => {
+                newrunstate = RunState::ShowVendor { vendor, mode };
             }
-            MainMenu { .. } => {
-                let result = gui::main_menu(self, ctx);
-                match result {
-                    gui::MainMenuResult::NoSelection { selected } => {
-                        newrunstate = MainMenu {
-                            menu_selection: selected,
-                        }
-                    }
-                    gui::MainMenuResult::Selected { selected } => match selected {
-                        gui::MainMenuSelection::NewGame => newrunstate = PreRun,
-                        gui::MainMenuSelection::LoadGame => {
-                            saveload_system::load_game(&mut self.ecs);
-                            newrunstate = AwaitingInput;
-                            saveload_system::delete_save();
-                        }
-                        gui::MainMenuSelection::Quit => ctx.quit(),
-                    },
+            (RunState::ShowVendor { .. }, Decision::Sell { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                let price = self
+                    .ecs
+                    .read_storage::<Item>()
+                    .get(item_entity)
+                    .unwrap()
+                    .base_value
+                    * 0.8;
+                self.ecs
+                    .write_storage::<Pools>()
+                    .get_mut(*self.ecs.fetch::<Entity>())
+                    .unwrap()
+                    .gold += price;
+                self.ecs
+                    .delete_entity(item_entity)
+                    .expect("Unable to delete");
+            }
+            (RunState::ShowVendor { .. }, Decision::Buy { tag, price }) => {
+                let mut pools = self.ecs.write_storage::<Pools>();
+                let player_entity = self.ecs.fetch::<Entity>();
+                let mut identified = self.ecs.write_storage::<IdentifiedItem>();
+                identified
+                    .insert(*player_entity, IdentifiedItem { name: tag.clone() })
+                    .expect("Unable to insert");
+                std::mem::drop(identified);
+                let player_pools = pools.get_mut(*player_entity).unwrap();
+                std::mem::drop(player_entity);
+
+                if player_pools.gold >= price {
+                    player_pools.gold -= price;
+                    std::mem::drop(pools);
+                    let player_entity = *self.ecs.fetch::<Entity>();
+                    spawn_named_item(
+                        &self.raws,
+                        &mut self.ecs,
+                        &tag,
+                        SpawnType::Carried { by: player_entity },
+                    );
                 }
             }
-            SaveGame => {
-                saveload_system::save_game(&mut self.ecs);
-                newrunstate = MainMenu {
-                    menu_selection: gui::MainMenuSelection::LoadGame,
-                };
+            (RunState::ShowRemoveCurse, Decision::SelectItem { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                self.ecs.write_storage::<CursedItem>().remove(item_entity);
+                newrunstate = RunState::Ticking;
+            }
+            (RunState::LevelUp, Decision::LevelUp(choice)) => {
+                level_up::apply_level_up(&self.ecs, &choice);
+                newrunstate = RunState::Ticking;
+            }
+            (RunState::ShowIdentify, Decision::SelectItem { item }) => {
+                let item_entity = replay::entity_by_id(&self.ecs, item);
+                if let Some(name) = self.ecs.read_storage::<Name>().get(item_entity) {
+                    let mut dm = self.ecs.fetch_mut::<MasterDungeonMap>();
+                    dm.identified_items.insert(name.name.clone());
+                }
+                newrunstate = RunState::Ticking;
+            }
+            _ => {}
+        }
+
+        newrunstate
+    }
+
+    /// Advances every run state that needs neither player input nor a console to draw on.
+    /// States that wait on the frontend are returned unchanged.
+    fn advance_simulation(&mut self, runstate: RunState) -> RunState {
+        let mut newrunstate = runstate;
+        match runstate {
+            PreRun => {
+                self.run_systems();
+                self.ecs.maintain();
+                newrunstate = AwaitingInput;
             }
             NextLevel => {
                 self.goto_level(1);
@@ -284,36 +606,6 @@ impl GameState for State {
                 self.mapgen_next_state = Some(PreRun);
                 newrunstate = MapGeneration;
             }
-            ShowRemoveItem => {
-                let result = gui::remove_item_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
-                        intent
-                            .insert(
-                                *self.ecs.fetch::<Entity>(),
-                                WantsToRemoveItem { item: item_entity },
-                            )
-                            .expect("Unable to insert drop intent");
-                        newrunstate = Ticking;
-                    }
-                }
-            }
-            GameOver => {
-                let result = gui::game_over(ctx);
-                match result {
-                    gui::GameOverResult::NoSelection => {}
-                    gui::GameOverResult::QuitToMenu => {
-                        self.game_over_cleanup();
-                        newrunstate = MainMenu {
-                            menu_selection: gui::MainMenuSelection::NewGame,
-                        };
-                    }
-                }
-            }
             MagicMapReveal { row } => {
                 let mut map = self.ecs.fetch_mut::<Map>();
                 for x in 0..map.width {
@@ -326,58 +618,7 @@ impl GameState for State {
                     newrunstate = MagicMapReveal { row: row + 1 };
                 }
             }
-            MapGeneration => {
-                if !SHOW_MAPGEN_VISUALIZER {
-                    newrunstate = self.mapgen_next_state.unwrap();
-                } else {
-                    ctx.cls();
-                    if self.mapgen_index < self.mapgen_history.len() {
-                        render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
-                    }
-
-                    self.mapgen_timer += ctx.frame_time_ms;
-                    if self.mapgen_timer > 75.0 {
-                        self.mapgen_timer = 0.0;
-                        self.mapgen_index += 1;
-                        if self.mapgen_index >= self.mapgen_history.len() {
-                            newrunstate = self.mapgen_next_state.unwrap();
-                        }
-                    }
-                }
-            }
-            ShowCheatMenu => {
-                let result = gui::show_cheat_mode(self, ctx);
-                match result {
-                    gui::CheatMenuResult::Cancel => newrunstate = AwaitingInput,
-                    gui::CheatMenuResult::NoResponse => {}
-                    gui::CheatMenuResult::TeleportToExit => {
-                        self.goto_level(1);
-                        self.mapgen_next_state = Some(PreRun);
-                        newrunstate = MapGeneration;
-                    }
-                    gui::CheatMenuResult::Heal => {
-                        let player = self.ecs.fetch::<Entity>();
-                        let mut pools = self.ecs.write_storage::<Pools>();
-                        let player_pools = pools.get_mut(*player).unwrap();
-                        player_pools.hit_points.current = player_pools.hit_points.max;
-                        newrunstate = RunState::AwaitingInput;
-                    }
-                    gui::CheatMenuResult::Reveal => {
-                        let mut map = self.ecs.fetch_mut::<Map>();
-                        for v in &mut map.revealed_tiles {
-                            *v = true;
-                        }
-                        newrunstate = RunState::AwaitingInput;
-                    }
-                    gui::CheatMenuResult::GodMode => {
-                        let player = self.ecs.fetch::<Entity>();
-                        let mut pools = self.ecs.write_storage::<Pools>();
-                        let player_pools = pools.get_mut(*player).unwrap();
-                        player_pools.god_mode = true;
-                        newrunstate = RunState::AwaitingInput;
-                    }
-                }
-            }
+            MapGeneration => newrunstate = self.mapgen_next_state.unwrap(),
             Ticking => {
                 let mut should_change_target = false;
                 while newrunstate == RunState::Ticking {
@@ -402,67 +643,6 @@ impl GameState for State {
                     player::end_turn_targeting(&self.ecs);
                 }
             }
-            RunState::ShowVendor { vendor, mode } => {
-                let result = gui::show_vendor_menu(self, ctx, vendor, mode);
-                match result.0 {
-                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::VendorResult::NoResponse => {}
-                    gui::VendorResult::Sell => {
-                        let price = self
-                            .ecs
-                            .read_storage::<Item>()
-                            .get(result.1.unwrap())
-                            .unwrap()
-                            .base_value
-                            * 0.8;
-                        self.ecs
-                            .write_storage::<Pools>()
-                            .get_mut(*self.ecs.fetch::<Entity>())
-                            .unwrap()
-                            .gold += price;
-                        self.ecs
-                            .delete_entity(result.1.unwrap())
-                            .expect("Unable to delete");
-                    }
-                    gui::VendorResult::Buy => {
-                        let tag = result.2.unwrap();
-                        let price = result.3.unwrap();
-                        let mut pools = self.ecs.write_storage::<Pools>();
-                        let player_entity = self.ecs.fetch::<Entity>();
-                        let mut identified = self.ecs.write_storage::<IdentifiedItem>();
-                        identified
-                            .insert(*player_entity, IdentifiedItem { name: tag.clone() })
-                            .expect("Unable to insert");
-                        std::mem::drop(identified);
-                        let player_pools = pools.get_mut(*player_entity).unwrap();
-                        std::mem::drop(player_entity);
-
-                        if player_pools.gold >= price {
-                            player_pools.gold -= price;
-                            std::mem::drop(pools);
-                            let player_entity = *self.ecs.fetch::<Entity>();
-                            spawn_named_item(
-                                &RAWS.lock().unwrap(),
-                                &mut self.ecs,
-                                &tag,
-                                SpawnType::Carried { by: player_entity },
-                            );
-                        }
-                    }
-                    gui::VendorResult::BuyMode => {
-                        newrunstate = RunState::ShowVendor {
-                            vendor,
-                            mode: VendorMode::Buy,
-                        }
-                    }
-                    gui::VendorResult::SellMode => {
-                        newrunstate = RunState::ShowVendor {
-                            vendor,
-                            mode: VendorMode::Sell,
-                        }
-                    }
-                }
-            }
             RunState::TownPortal => {
                 spawner::spawn_town_portal(&mut self.ecs);
 
@@ -485,40 +665,262 @@ impl GameState for State {
                 self.mapgen_next_state = Some(RunState::PreRun);
                 newrunstate = RunState::MapGeneration;
             }
-            RunState::ShowRemoveCurse => {
-                let result = gui::remove_curse_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        self.ecs.write_storage::<CursedItem>().remove(item_entity);
-                        newrunstate = RunState::Ticking;
+            _ => {}
+        }
+
+        newrunstate
+    }
+
+    fn commit_runstate(&mut self, newrunstate: RunState) {
+        let newrunstate = level_up::after_turn(&self.ecs, newrunstate);
+        let previous = *self.ecs.fetch::<RunState>();
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+
+        damage_system::delete_the_dead(&mut self.ecs);
+
+        let current = *self.ecs.fetch::<RunState>();
+        if current == AwaitingInput && self.autosave_due {
+            self.autosave();
+        }
+        if current == GameOver && previous != GameOver && self.autosave {
+            let slot = self.ecs.fetch::<SaveSlot>().0.clone();
+            match self.options.death_mode {
+                DeathMode::Permadeath => saveload_system::delete_slot(&slot),
+                DeathMode::RecoverAutosave => {
+                    self.recovery = saveload_system::list_slots()
+                        .into_iter()
+                        .find(|summary| summary.name == slot);
+                }
+            }
+        }
+        // Written whenever the player gets control back as well as when the run
+        // ends, so closing the window keeps everything up to the last turn
+        let run_ended = matches!(current, GameOver | SaveGame);
+        if (run_ended || current == AwaitingInput) && current != previous {
+            if let Some(recording) = &mut self.recording {
+                recording.checksum = replay::checksum(&self.ecs);
+                replay::save_replay(recording, &replay::replay_path());
+            }
+        }
+        if run_ended || current == AwaitingInput {
+            if let Some(playback) = &mut self.playback {
+                if playback.is_finished() && playback.verified().is_none() {
+                    let verified = playback.verify(&self.ecs);
+                    gamelog::Logger::new()
+                        .append(if verified {
+                            "Replay finished in the recorded world."
+                        } else {
+                            "Replay diverged from the recording!"
+                        })
+                        .log(&mut self.ecs.write_resource::<GameLog>());
+                }
+            }
+        }
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut rltk::Rltk) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
+        particle_system::update_particles(&mut self.ecs, ctx.frame_time_ms);
+
+        match newrunstate {
+            RunState::MainMenu { .. }
+            | RunState::ChooseClass
+            | RunState::CreateCharacter
+            | RunState::ShowLoadGame
+            | RunState::RecoveryPrompt => {}
+            GameOver { .. } => {}
+            _ => {
+                render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+
+        match newrunstate {
+            AwaitingInput
+            | ShowInventory
+            | ShowDropItem
+            | ShowTargeting { .. }
+            | ShowRemoveItem
+            | ShowCheatMenu
+            | RunState::ShowVendor { .. }
+            | RunState::ShowRemoveCurse
+            | RunState::ShowIdentify
+            | RunState::LevelUp => {
+                let decision = self.take_decision(|gs| gs.read_decision(ctx, newrunstate));
+                if let Some(decision) = decision {
+                    newrunstate = self.apply_decision(newrunstate, decision);
+                }
+            }
+            MainMenu { .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = MainMenu {
+                            menu_selection: selected,
+                        }
                     }
+                    gui::MainMenuResult::Selected { selected } => match selected {
+                        gui::MainMenuSelection::NewGame => {
+                            self.load_error = None;
+                            newrunstate = RunState::ChooseClass;
+                        }
+                        gui::MainMenuSelection::LoadGame => {
+                            self.load_error = None;
+                            self.save_slots = saveload_system::list_slots();
+                            newrunstate = RunState::ShowLoadGame;
+                        }
+                        gui::MainMenuSelection::DeathMode => {
+                            self.options.death_mode = self.options.death_mode.toggled();
+                            options::save_options(&self.options);
+                            newrunstate = MainMenu {
+                                menu_selection: selected,
+                            };
+                        }
+                        gui::MainMenuSelection::Quit => ctx.quit(),
+                    },
                 }
             }
-            RunState::ShowIdentify => {
-                let result = gui::identify_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        if let Some(name) = self.ecs.read_storage::<Name>().get(item_entity) {
-                            let mut dm = self.ecs.fetch_mut::<MasterDungeonMap>();
-                            dm.identified_items.insert(name.name.clone());
+            RunState::ChooseClass => match gui::class_menu(self, ctx) {
+                gui::ClassMenuResult::NoResponse => {}
+                gui::ClassMenuResult::Cancel => {
+                    newrunstate = MainMenu {
+                        menu_selection: gui::MainMenuSelection::NewGame,
+                    };
+                }
+                gui::ClassMenuResult::Selected(index) => {
+                    self.creation = Some(gui::CharacterSheet::new(&self.raws.classes()[index]));
+                    newrunstate = RunState::CreateCharacter;
+                }
+            },
+            RunState::CreateCharacter => {
+                let sheet = self.creation.as_mut().unwrap();
+                match gui::character_menu(sheet, ctx) {
+                    gui::CharacterMenuResult::NoResponse => {}
+                    gui::CharacterMenuResult::Cancel => {
+                        self.creation = None;
+                        newrunstate = RunState::ChooseClass;
+                    }
+                    gui::CharacterMenuResult::Done => {
+                        let character = self.creation.take().unwrap().character();
+                        self.new_game(rng::new_seed(), &character);
+                        if self.autosave {
+                            saveload_system::mark_session(&self.ecs.fetch::<SaveSlot>().0);
                         }
-                        newrunstate = RunState::Ticking;
+                        newrunstate = PreRun;
                     }
                 }
             }
+            RunState::ShowLoadGame => match gui::load_game_menu(self, ctx) {
+                gui::LoadMenuResult::NoResponse => {}
+                gui::LoadMenuResult::Cancel => {
+                    newrunstate = MainMenu {
+                        menu_selection: gui::MainMenuSelection::LoadGame,
+                    };
+                }
+                gui::LoadMenuResult::Delete(index) => {
+                    saveload_system::delete_slot(&self.save_slots[index].name);
+                    self.save_slots = saveload_system::list_slots();
+                }
+                gui::LoadMenuResult::Load(index) => {
+                    let slot = self.save_slots[index].name.clone();
+                    newrunstate = self.resume_slot(&slot);
+                }
+            },
+            RunState::RecoveryPrompt => {
+                let slot = self.recovery.clone().unwrap();
+                match gui::recovery_prompt(&slot, ctx) {
+                    gui::RecoveryPromptResult::NoResponse => {}
+                    gui::RecoveryPromptResult::Recover => {
+                        newrunstate = self.resume_slot(&slot.name);
+                    }
+                    gui::RecoveryPromptResult::Dismiss => {
+                        self.end_session();
+                        newrunstate = MainMenu {
+                            menu_selection: gui::MainMenuSelection::LoadGame,
+                        };
+                    }
+                }
+            }
+            SaveGame => {
+                saveload_system::save_game(&mut self.ecs, self.options.save_encoding);
+                self.end_session();
+                newrunstate = MainMenu {
+                    menu_selection: gui::MainMenuSelection::LoadGame,
+                };
+            }
+            GameOver => {
+                let seed = self.ecs.fetch::<rng::RunSeed>().0;
+                let player = *self.ecs.fetch::<Entity>();
+                let name = self
+                    .ecs
+                    .read_storage::<Name>()
+                    .get(player)
+                    .unwrap()
+                    .name
+                    .clone();
+                let result = {
+                    let histories = self.ecs.read_storage::<CharacterHistory>();
+                    let level_ups = histories
+                        .get(player)
+                        .map_or(&[][..], |history| &history.level_ups);
+                    gui::game_over(
+                        ctx,
+                        &name,
+                        level_ups,
+                        seed,
+                        &self.ecs.fetch::<GameEvents>(),
+                        self.recovery.is_some(),
+                    )
+                };
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::RecoverAutosave => {
+                        let slot = self.recovery.clone().unwrap();
+                        newrunstate = self.resume_slot(&slot.name);
+                    }
+                    gui::GameOverResult::QuitToMenu => {
+                        self.end_session();
+                        let character = self.default_character();
+                        self.new_game(rng::new_seed(), &character);
+                        newrunstate = MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        };
+                    }
+                }
+            }
+            MapGeneration if SHOW_MAPGEN_VISUALIZER => {
+                ctx.cls();
+                if self.mapgen_index < self.mapgen_history.len() {
+                    render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+                }
+
+                self.mapgen_timer += ctx.frame_time_ms;
+                if self.mapgen_timer > 75.0 {
+                    self.mapgen_timer = 0.0;
+                    self.mapgen_index += 1;
+                    if self.mapgen_index >= self.mapgen_history.len() {
+                        newrunstate = self.mapgen_next_state.unwrap();
+                    }
+                }
+            }
+            _ => newrunstate = self.advance_simulation(newrunstate),
         }
-        {
-            let mut runwriter = self.ecs.write_resource::<RunState>();
-            *runwriter = newrunstate;
-        }
+        self.commit_runstate(newrunstate);
 
-        damage_system::delete_the_dead(&mut self.ecs);
         rltk::render_draw_buffer(ctx).expect("Draw failed");
 
         if SHOW_FPS {
@@ -527,9 +929,55 @@ impl GameState for State {
     }
 }
 
+/// Creates an empty world with every component registered.
+fn new_world() -> World {
+    let mut ecs = World::new();
+    component_registry::register_components(&mut ecs);
+    ecs.insert(EffectQueue::default());
+    ecs.insert(GameLog::default());
+    ecs.insert(GameEvents::default());
+    ecs.insert(rng::TurnsPlayed::default());
+    ecs.insert(level_up::PendingLevelUps::default());
+
+    ecs
+}
+
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(valid) = raws::validate_from_args(&args).or_else(|| raws::list_from_args(&args)) {
+        return if valid {
+            Ok(())
+        } else {
+            Err(color_eyre::eyre::eyre!("The raws aren't valid"))
+        };
+    }
+    let replay = replay::replay_from_args(&args);
+    if replay.is_none() && args.iter().any(|arg| arg == "--replay") {
+        return Err(color_eyre::eyre::eyre!("Unable to read the replay file"));
+    }
+
+    if let Some(turns) = headless::turns_from_args(&args) {
+        let seed = headless::seed_from_args(&args).unwrap_or_else(rng::new_seed);
+        let mut gs = State::with_seed(seed);
+        let turns = match replay {
+            Some(replay) => {
+                gs.start_replay(replay);
+                i32::MAX
+            }
+            None => turns,
+        };
+        let report = headless::run(&mut gs, &mut headless::RandomWalker::new(seed), turns);
+        println!("{report}");
+        if gs.replay_verified() == Some(false) {
+            return Err(color_eyre::eyre::eyre!(
+                "Replay diverged from the recording"
+            ));
+        }
+        return Ok(());
+    }
+
     use rltk::RltkBuilder;
 
     let mut context = RltkBuilder::simple(80, 60)
@@ -543,116 +991,147 @@ fn main() -> color_eyre::Result<()> {
         .expect("Failed to construct a builder.");
     context.with_post_scanlines(true);
 
-    let mut gs = State {
-        ecs: World::new(),
-        mapgen_next_state: Some(MainMenu {
-            menu_selection: gui::MainMenuSelection::NewGame,
-        }),
-        mapgen_index: 0,
-        mapgen_history: Vec::new(),
-        mapgen_timer: 0.0,
-        dispatcher: systems::build(),
-    };
-
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<Item>();
-    gs.ecs.register::<ProvidesHealing>();
-    gs.ecs.register::<InBackpack>();
-    gs.ecs.register::<WantsToPickupItem>();
-    gs.ecs.register::<WantsToUseItem>();
-    gs.ecs.register::<WantsToDropItem>();
-    gs.ecs.register::<Consumable>();
-    gs.ecs.register::<Ranged>();
-    gs.ecs.register::<InflictsDamage>();
-    gs.ecs.register::<AreaOfEffect>();
-    gs.ecs.register::<Confusion>();
-    gs.ecs.register::<SimpleMarker<SerializeMe>>();
-    gs.ecs.register::<SerializationHelper>();
-    gs.ecs.register::<Equippable>();
-    gs.ecs.register::<Equipped>();
-    gs.ecs.register::<MeleePowerBonus>();
-    gs.ecs.register::<DefenseBonus>();
-    gs.ecs.register::<WantsToRemoveItem>();
-    gs.ecs.register::<ParticleLifetime>();
-    gs.ecs.register::<HungerClock>();
-    gs.ecs.register::<ProvidesFood>();
-    gs.ecs.register::<MagicMapper>();
-    gs.ecs.register::<Hidden>();
-    gs.ecs.register::<EntryTrigger>();
-    gs.ecs.register::<EntityMoved>();
-    gs.ecs.register::<SingleActivation>();
-    gs.ecs.register::<BlocksVisibility>();
-    gs.ecs.register::<Door>();
-    gs.ecs.register::<Quips>();
-    gs.ecs.register::<Attributes>();
-    gs.ecs.register::<Skills>();
-    gs.ecs.register::<Pools>();
-    gs.ecs.register::<Wearable>();
-    gs.ecs.register::<NaturalAttackDefense>();
-    gs.ecs.register::<LootTable>();
-    gs.ecs.register::<OtherLevelPosition>();
-    gs.ecs.register::<DMSerializationHelper>();
-    gs.ecs.register::<LightSource>();
-    gs.ecs.register::<Initiative>();
-    gs.ecs.register::<MyTurn>();
-    gs.ecs.register::<Faction>();
-    gs.ecs.register::<WantsToApproach>();
-    gs.ecs.register::<WantsToFlee>();
-    gs.ecs.register::<MoveMode>();
-    gs.ecs.register::<Chasing>();
-    gs.ecs.register::<EquipmentChanged>();
-    gs.ecs.register::<Vendor>();
-    gs.ecs.register::<components::TownPortal>();
-    gs.ecs.register::<TeleportTo>();
-    gs.ecs.register::<ApplyMove>();
-    gs.ecs.register::<ApplyTeleport>();
-    gs.ecs.register::<MagicItem>();
-    gs.ecs.register::<ObfuscatedName>();
-    gs.ecs.register::<IdentifiedItem>();
-    gs.ecs.register::<SpawnParticleLine>();
-    gs.ecs.register::<SpawnParticleBurst>();
-    gs.ecs.register::<CursedItem>();
-    gs.ecs.register::<ProvidesRemoveCurse>();
-    gs.ecs.register::<ProvidesIdentification>();
-    gs.ecs.register::<AttributeBonus>();
-    gs.ecs.register::<Duration>();
-    gs.ecs.register::<StatusEffect>();
-    gs.ecs.register::<KnownSpells>();
-    gs.ecs.register::<SpellTemplate>();
-    gs.ecs.register::<WantsToCastSpell>();
-    gs.ecs.register::<ProvidesMana>();
-    gs.ecs.register::<TeachesSpell>();
-    gs.ecs.register::<Slow>();
-    gs.ecs.register::<DamageOverTime>();
-    gs.ecs.register::<SpecialAbilities>();
-    gs.ecs.register::<TileSize>();
-    gs.ecs.register::<OnDeath>();
-    gs.ecs.register::<AlwaysTargetsSelf>();
-    gs.ecs.register::<Weapon>();
-    gs.ecs.register::<Target>();
-    gs.ecs.register::<WantsToShoot>();
-    gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
-
-    raws::load_raws();
-
-    gs.ecs.insert(MasterDungeonMap::default());
-    gs.ecs.insert(Map::new(1, 64, 64, "New Map"));
-    gs.ecs.insert(Point::new(0, 0));
-    let player_entity = spawner::player(&mut gs.ecs, 0, 0);
-    gs.ecs.insert(player_entity);
-    gs.ecs.insert(MapGeneration {});
-    gs.ecs.insert(particle_system::ParticleBuilder::new());
-    gs.ecs.insert(rex_assets::RexAssets::new());
-
-    gs.generate_world_map(1, 0);
-
+    let mut gs = State::new();
+    if let Some(replay) = replay {
+        gs.start_replay(replay);
+    } else {
+        gs.start_session();
+    }
     rltk::main_loop(context, gs).expect("Failed to run main loop");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{headless, replay, State};
+    use crate::map::Map;
+    use crate::options::{self, DeathMode, GameOptions};
+    use crate::player::RunState;
+    use crate::saveload_system::{self, SaveEncoding, SaveSlot};
+    use crate::test_support::saves::temp_data_dir;
+
+    #[test]
+    fn a_seed_builds_the_same_world_every_time() {
+        let _data_dir = temp_data_dir();
+        let first = State::with_seed(7);
+        let second = State::with_seed(7);
+        let other = State::with_seed(8);
+
+        assert!(first.ecs.fetch::<Map>().tiles == second.ecs.fetch::<Map>().tiles);
+        assert_eq!(replay::checksum(&first.ecs), replay::checksum(&second.ecs));
+        assert_ne!(replay::checksum(&first.ecs), replay::checksum(&other.ecs));
+    }
+
+    #[test]
+    fn a_seed_plays_out_the_same_way_every_time() {
+        let _data_dir = temp_data_dir();
+        let play = |seed| {
+            let mut gs = State::with_seed(seed);
+            let report = headless::run(&mut gs, &mut headless::RandomWalker::new(seed), 200);
+            (report.to_string(), replay::checksum(&gs.ecs))
+        };
+
+        assert_eq!(play(7), play(7));
+    }
+
+    #[test]
+    fn recorded_runs_replay_into_the_same_world() {
+        let _data_dir = temp_data_dir();
+        let seed = 11;
+        let mut recorded = State::with_seed(seed);
+        headless::run(&mut recorded, &mut headless::RandomWalker::new(seed), 100);
+        let recording =
+            replay::load_replay(&replay::replay_path()).expect("nothing was recorded");
+        assert!(!recording.decisions.is_empty());
+
+        let mut replayed = State::with_seed(seed + 1);
+        replayed.start_replay(recording);
+        // The walker only gets a say once the recorded decisions run out
+        headless::run(&mut replayed, &mut headless::RandomWalker::new(0), 1000);
+
+        assert_eq!(replayed.replay_verified(), Some(true));
+    }
+
+    /// Saves the run in `gs` and marks it as being played, as if the game had
+    /// stopped in the middle of it.
+    fn interrupt(gs: &mut State) -> String {
+        let slot = gs.ecs.fetch::<SaveSlot>().0.clone();
+        saveload_system::save_game(&mut gs.ecs, SaveEncoding::Json);
+        saveload_system::mark_session(&slot);
+        slot
+    }
+
+    #[test]
+    fn runs_autosave_every_few_turns() {
+        let _data_dir = temp_data_dir();
+        options::save_options(&GameOptions {
+            autosave_turns: 5,
+            ..GameOptions::default()
+        });
+        let mut gs = State::with_seed(3);
+        gs.start_session();
+        let slot = gs.ecs.fetch::<SaveSlot>().0.clone();
+        let mut walker = headless::RandomWalker::new(3);
+
+        headless::run(&mut gs, &mut walker, 4);
+        assert!(!saveload_system::slot_path(&slot).exists());
+        headless::run(&mut gs, &mut walker, 3);
+
+        let saved = saveload_system::list_slots();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, slot);
+        assert_eq!(saved[0].header.as_ref().unwrap().metadata.turns, 5);
+    }
+
+    #[test]
+    fn strict_permadeath_uses_saves_up_when_they_load() {
+        let _data_dir = temp_data_dir();
+        let mut gs = State::with_seed(3);
+        gs.start_session();
+        let slot = interrupt(&mut gs);
+
+        assert!(gs.resume_slot(&slot) == RunState::AwaitingInput);
+        assert!(!saveload_system::slot_path(&slot).exists());
+
+        gs.options.death_mode = DeathMode::RecoverAutosave;
+        saveload_system::save_game(&mut gs.ecs, SaveEncoding::Json);
+        assert!(gs.resume_slot(&slot) == RunState::AwaitingInput);
+        assert!(saveload_system::slot_path(&slot).exists());
+    }
+
+    #[test]
+    fn strict_permadeath_gives_up_interrupted_runs() {
+        let _data_dir = temp_data_dir();
+        let slot = interrupt(&mut State::with_seed(3));
+
+        let mut gs = State::with_seed(4);
+        gs.start_session();
+
+        assert!(gs.recovery.is_none());
+        assert!(matches!(gs.mapgen_next_state, Some(RunState::MainMenu { .. })));
+        assert!(!saveload_system::slot_path(&slot).exists());
+        assert!(saveload_system::interrupted_session().is_none());
+    }
+
+    #[test]
+    fn interrupted_runs_can_be_recovered() {
+        let _data_dir = temp_data_dir();
+        options::save_options(&GameOptions {
+            death_mode: DeathMode::RecoverAutosave,
+            ..GameOptions::default()
+        });
+        let slot = interrupt(&mut State::with_seed(3));
+
+        let mut gs = State::with_seed(4);
+        gs.start_session();
+
+        assert!(matches!(gs.mapgen_next_state, Some(RunState::RecoveryPrompt)));
+        let recovery = gs.recovery.as_ref().map(|summary| summary.name.clone());
+        assert_eq!(recovery, Some(slot.clone()));
+        assert!(gs.resume_slot(&slot) == RunState::AwaitingInput);
+        assert!(gs.recovery.is_none());
+        assert_eq!(gs.ecs.fetch::<SaveSlot>().0, slot);
+        assert!(saveload_system::slot_path(&slot).exists());
+    }
+}
@@ -1,7 +1,7 @@
-use core::default::Default;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-use rltk::Point;
+use rltk::{Point, RandomNumberGenerator};
 use serde::{Deserialize, Serialize};
 use specs::{Entity, Join, World, WorldExt};
 
@@ -9,8 +9,7 @@ use crate::components::{OtherLevelPosition, Position, Viewshed};
 use crate::map::tiletype::TileType;
 use crate::map::Map;
 use crate::map_builders::level_builder;
-use crate::raws::rawmaster::{get_potion_tag, get_scroll_tags};
-use crate::rng::roll_dice;
+use crate::raws::rawmaster::{get_potion_tag, get_scroll_tags, RawMaster};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MasterDungeonMap {
@@ -20,32 +19,40 @@ pub struct MasterDungeonMap {
     pub potion_mappings: HashMap<String, String>,
 }
 
-impl Default for MasterDungeonMap {
-    fn default() -> Self {
+impl MasterDungeonMap {
+    /// Starts a dungeon with fresh unidentified names for every magic scroll and potion.
+    pub fn new(raws: &RawMaster, rng: &mut RandomNumberGenerator) -> Self {
         let mut dm = Self {
-            maps: Default::default(),
-            identified_items: Default::default(),
-            scroll_mappings: Default::default(),
-            potion_mappings: Default::default(),
+            maps: HashMap::new(),
+            identified_items: HashSet::new(),
+            scroll_mappings: HashMap::new(),
+            potion_mappings: HashMap::new(),
         };
+        dm.name_unidentified_items(raws, rng);
+        dm
+    }
 
-        for scroll_tag in &get_scroll_tags() {
-            let masked_name = make_scroll_name();
-            dm.scroll_mappings.insert(scroll_tag.into(), masked_name);
+    /// Picks unidentified names for the magic scrolls and potions in `raws` that
+    /// don't have one yet, such as those added by reloading the raws.
+    pub fn name_unidentified_items(&mut self, raws: &RawMaster, rng: &mut RandomNumberGenerator) {
+        for scroll_tag in &get_scroll_tags(raws) {
+            if !self.scroll_mappings.contains_key(scroll_tag) {
+                let masked_name = make_scroll_name(rng);
+                self.scroll_mappings.insert(scroll_tag.into(), masked_name);
+            }
         }
 
-        let mut used_potion_names = HashSet::new();
-        for potion_tag in &get_potion_tag() {
-            let masked_name = make_potion_name(&mut used_potion_names);
-            dm.potion_mappings
-                .insert(potion_tag.to_string(), masked_name);
+        let mut used_potion_names: HashSet<String> =
+            self.potion_mappings.values().cloned().collect();
+        for potion_tag in &get_potion_tag(raws) {
+            if !self.potion_mappings.contains_key(potion_tag) {
+                let masked_name = make_potion_name(rng, &mut used_potion_names);
+                self.potion_mappings
+                    .insert(potion_tag.to_string(), masked_name);
+            }
         }
-
-        dm
     }
-}
 
-impl MasterDungeonMap {
     pub fn store(&mut self, map: &Map) {
         self.maps.insert(map.depth, map.clone());
     }
@@ -60,13 +67,13 @@ impl MasterDungeonMap {
     }
 }
 
-fn make_scroll_name() -> String {
-    let length = 4 + roll_dice(1, 4);
+fn make_scroll_name(rng: &mut RandomNumberGenerator) -> String {
+    let length = 4 + rng.roll_dice(1, 4);
     let mut name = "Scroll of ".into();
 
     for i in 0..length {
         if i % 2 == 0 {
-            name += match roll_dice(1, 5) {
+            name += match rng.roll_dice(1, 5) {
                 1 => "a",
                 2 => "e",
                 3 => "i",
@@ -75,7 +82,7 @@ fn make_scroll_name() -> String {
                 _ => unreachable!(),
             }
         } else {
-            name += match roll_dice(1, 21) {
+            name += match rng.roll_dice(1, 21) {
                 1 => "b",
                 2 => "c",
                 3 => "d",
@@ -118,12 +125,13 @@ const POTION_ADJECTIVES: &[&str] = &[
     "Glowing",
 ];
 
-fn make_potion_name(used_names: &mut HashSet<String>) -> String {
+fn make_potion_name(rng: &mut RandomNumberGenerator, used_names: &mut HashSet<String>) -> String {
     loop {
-        let mut name = POTION_ADJECTIVES[roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize - 1]
+        let mut name = POTION_ADJECTIVES
+            [rng.roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize - 1]
             .to_string();
         name += " ";
-        name += POTION_COLORS[roll_dice(1, POTION_COLORS.len() as i32) as usize - 1];
+        name += POTION_COLORS[rng.roll_dice(1, POTION_COLORS.len() as i32) as usize - 1];
         name += " Potion";
 
         if !used_names.contains(&name) {
@@ -148,7 +156,9 @@ pub fn level_transition(ecs: &mut World, new_depth: i32, offset: i32) -> Option<
 
 fn transition_to_existing_map(ecs: &World, new_depth: i32, offset: i32) {
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
-    let map = dungeon_master.get_map(new_depth).unwrap();
+    let mut map = dungeon_master.get_map(new_depth).unwrap();
+    // Stored maps may have come from a save, which leaves out the derived data
+    map.restore_unsaved();
     let mut worldmap_resource = ecs.write_resource::<Map>();
     let player_entity = ecs.fetch::<Entity>();
 
@@ -183,8 +193,13 @@ fn transition_to_existing_map(ecs: &World, new_depth: i32, offset: i32) {
 }
 
 fn transition_to_new_map(ecs: &mut World, new_depth: i32) -> Vec<Map> {
-    let mut builder = level_builder(new_depth, 80, 50);
-    builder.build_map();
+    let mut builder = {
+        let raws = ecs.fetch::<Arc<RawMaster>>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let mut builder = level_builder(&raws, new_depth, 80, 50, &mut rng);
+        builder.build_map(&raws, &mut rng);
+        builder
+    };
     if new_depth > 1 {
         if let Some(pos) = &builder.build_data.starting_position {
             let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
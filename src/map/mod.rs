@@ -7,29 +7,38 @@ use specs::prelude::*;
 pub use themes::*;
 
 use crate::map::tiletype::TileType;
+use crate::spatial::SpatialMap;
 
 pub mod camera;
 pub mod dungeon;
+mod rle;
 mod themes;
 pub mod tiletype;
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
+    #[serde(with = "rle")]
     pub tiles: Vec<TileType>,
     pub width: i32,
     pub height: i32,
+    #[serde(with = "rle")]
     pub revealed_tiles: Vec<bool>,
+    /// Worked out again by the visibility system, so it isn't saved.
+    #[serde(skip)]
     pub visible_tiles: Vec<bool>,
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
     pub view_blocked: HashSet<usize>,
     pub name: String,
     pub natural_light: bool,
+    /// Worked out again by the lighting system, so it isn't saved.
+    #[serde(skip)]
     pub light: Vec<RGB>,
 
+    /// Rebuilt by the map indexing system, so it isn't saved.
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
-    pub tile_content: Vec<Vec<Entity>>,
+    pub spatial: SpatialMap,
 }
 
 impl Map {
@@ -37,20 +46,29 @@ impl Map {
         (y as usize * self.width as usize) + x as usize
     }
 
+    /// Sizes the vectors a save leaves out. The visibility, lighting and map
+    /// indexing systems fill them in on their next run.
+    pub fn restore_unsaved(&mut self) {
+        let tile_count = self.tiles.len();
+        self.visible_tiles = vec![false; tile_count];
+        self.light = vec![RGB::named(BLACK); tile_count];
+        self.spatial.set_size(tile_count);
+    }
+
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
         if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
             return false;
         }
         let idx = self.xy_idx(x, y);
-        !crate::spatial::is_blocked(idx)
+        !self.spatial.is_blocked(idx)
     }
 
     pub fn clear_content_index(&mut self) {
-        crate::spatial::clear();
+        self.spatial.clear();
     }
 
     pub fn populate_blocked(&mut self) {
-        crate::spatial::populate_blocked_from_map(self);
+        self.spatial.populate_blocked_from_tiles(&self.tiles);
     }
 
     pub fn populate_blocked_multi(&mut self, width: i32, height: i32) {
@@ -58,7 +76,7 @@ impl Map {
         for y in 1..self.height - 1 {
             for x in 1..self.width - 1 {
                 let idx = self.xy_idx(x, y);
-                if crate::spatial::is_blocked(idx) {
+                if self.spatial.is_blocked(idx) {
                     return;
                 }
 
@@ -68,11 +86,11 @@ impl Map {
                         let ty = y + cy;
                         if tx < self.width - 1 && ty < self.height - 1 {
                             let tidx = self.xy_idx(tx, ty);
-                            if crate::spatial::is_blocked(tidx) {
-                                crate::spatial::set_blocked(idx, true);
+                            if self.spatial.is_blocked(tidx) {
+                                self.spatial.set_blocked(idx, true);
                             }
                         } else {
-                            crate::spatial::set_blocked(idx, true);
+                            self.spatial.set_blocked(idx, true);
                         }
                     }
                 }
@@ -83,14 +101,15 @@ impl Map {
     /// Generates an empty map, consisting entirely of solid walls
     pub fn new(new_depth: i32, width: i32, height: i32, name: impl ToString) -> Self {
         let map_tile_count = (width * height) as usize;
-        crate::spatial::set_size(map_tile_count);
+        let mut spatial = SpatialMap::default();
+        spatial.set_size(map_tile_count);
         Self {
             tiles: vec![TileType::Wall; map_tile_count],
             width,
             height,
             revealed_tiles: vec![false; map_tile_count],
             visible_tiles: vec![false; map_tile_count],
-            tile_content: vec![Vec::new(); map_tile_count],
+            spatial,
             depth: new_depth,
             bloodstains: HashSet::new(),
             view_blocked: HashSet::new(),
@@ -161,3 +180,30 @@ impl Algorithm2D for Map {
         Point::new(self.width, self.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Map;
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn maps_save_tiles_as_runs_without_derived_data() {
+        let scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let map = scenario.ecs.fetch::<Map>().clone();
+
+        let value = serde_json::to_value(&map).unwrap();
+        let loaded: Map = serde_json::from_value(value.clone()).unwrap();
+
+        assert!(value["tiles"].as_array().unwrap().len() < map.tiles.len());
+        assert!(value.get("visible_tiles").is_none());
+        assert!(value.get("light").is_none());
+        assert!(loaded.tiles == map.tiles);
+        assert_eq!(loaded.revealed_tiles, map.revealed_tiles);
+    }
+}
@@ -0,0 +1,32 @@
+//! Saves a tile vector as `(value, count)` runs. Maps are mostly long stretches of
+//! wall, floor and unexplored tiles, so this is a fraction of the size.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + PartialEq,
+    S: Serializer,
+{
+    let mut runs: Vec<(&T, usize)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => runs.push((value, 1)),
+        }
+    }
+    runs.serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de> + Clone,
+    D: Deserializer<'de>,
+{
+    let runs = Vec::<(T, usize)>::deserialize(deserializer)?;
+    let mut values = Vec::new();
+    for (value, count) in runs {
+        values.extend(std::iter::repeat(value).take(count));
+    }
+    Ok(values)
+}
@@ -5,7 +5,7 @@ use TileType::{
     Stalagmite, UpStairs, Wall, WoodFloor,
 };
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
@@ -1,32 +1,37 @@
 use rltk::DistanceAlg::PythagorasSquared;
 use rltk::{DistanceAlg, Point};
+use serde::Deserialize;
 
 use crate::components::Position;
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 
-#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum XStart {
     Left,
     Center,
     Right,
 }
 
-#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum XEnd {
     Left,
     Center,
     Right,
 }
 
-#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum YStart {
     Top,
     Center,
     Bottom,
 }
 
-#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum YEnd {
     Top,
     Center,
@@ -1,4 +1,4 @@
-use crate::rng::roll_dice;
+use rltk::RandomNumberGenerator;
 
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, InitialMapBuilder};
@@ -33,8 +33,8 @@ impl BspDungeonBuilder {
 
         let mut n_rooms = 0;
         while n_rooms < 240 {
-            let rect = self.get_random_rect();
-            let candidate = self.get_random_sub_rect(rect);
+            let rect = self.get_random_rect(&mut build_data.rng);
+            let candidate = self.get_random_sub_rect(rect, &mut build_data.rng);
 
             if self.is_possible(candidate, build_data, &rooms) {
                 rooms.push(candidate);
@@ -75,24 +75,24 @@ impl BspDungeonBuilder {
         ));
     }
 
-    fn get_random_rect(&mut self) -> Rect {
+    fn get_random_rect(&mut self, rng: &mut RandomNumberGenerator) -> Rect {
         if self.rects.len() == 1 {
             return self.rects[0];
         }
-        let idx = (roll_dice(1, self.rects.len() as i32) - 1) as usize;
+        let idx = (rng.roll_dice(1, self.rects.len() as i32) - 1) as usize;
         self.rects[idx]
     }
 
-    fn get_random_sub_rect(&self, rect: Rect) -> Rect {
+    fn get_random_sub_rect(&self, rect: Rect, rng: &mut RandomNumberGenerator) -> Rect {
         let mut result = rect;
         let rect_width = i32::abs(rect.x1 - rect.x2);
         let rect_height = i32::abs(rect.y1 - rect.y2);
 
-        let w = i32::max(3, roll_dice(1, i32::min(rect_width, 10)) - 1) + 1;
-        let h = i32::max(3, roll_dice(1, i32::min(rect_height, 10)) - 1) + 1;
+        let w = i32::max(3, rng.roll_dice(1, i32::min(rect_width, 10)) - 1) + 1;
+        let h = i32::max(3, rng.roll_dice(1, i32::min(rect_height, 10)) - 1) + 1;
 
-        result.x1 += roll_dice(1, 6) - 1;
-        result.y1 += roll_dice(1, 6) - 1;
+        result.x1 += rng.roll_dice(1, 6) - 1;
+        result.y1 += rng.roll_dice(1, 6) - 1;
         result.x2 = result.x1 + w;
         result.y2 = result.y1 + h;
 
@@ -1,8 +1,9 @@
+use rltk::RandomNumberGenerator;
+
 use crate::map::tiletype::TileType;
 use crate::map_builders::common::draw_corridor;
 use crate::map_builders::{BuilderMap, InitialMapBuilder};
 use crate::rect::Rect;
-use crate::rng::roll_dice;
 
 const MIN_ROOM_SIZE: i32 = 8;
 
@@ -31,7 +32,7 @@ impl BspInteriorBuilder {
             build_data.map.height - 2,
         ));
         let first_room = self.rects[0];
-        self.add_subrect(first_room);
+        self.add_subrect(first_room, &mut build_data.rng);
 
         let rooms_copy = self.rects.clone();
         for r in &rooms_copy {
@@ -53,17 +54,25 @@ impl BspInteriorBuilder {
         for i in 0..rooms.len() - 1 {
             let room = rooms[i];
             let next_room = rooms[i + 1];
-            let start_x = room.x1 + (roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
-            let start_y = room.y1 + (roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
-            let end_x = next_room.x1 + (roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
-            let end_y = next_room.y1 + (roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+            let start_x = room.x1 + (build_data.rng.roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
+            let start_y = room.y1 + (build_data.rng.roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
+            let end_x = next_room.x1
+                + (build_data
+                    .rng
+                    .roll_dice(1, i32::abs(next_room.x1 - next_room.x2))
+                    - 1);
+            let end_y = next_room.y1
+                + (build_data
+                    .rng
+                    .roll_dice(1, i32::abs(next_room.y1 - next_room.y2))
+                    - 1);
             draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             build_data.take_snapshot();
         }
 
         build_data.rooms = Some(rooms);
     }
-    fn add_subrect(&mut self, rect: Rect) {
+    fn add_subrect(&mut self, rect: Rect, rng: &mut RandomNumberGenerator) {
         if !self.rects.is_empty() {
             self.rects.remove(self.rects.len() - 1);
         }
@@ -73,31 +82,31 @@ impl BspInteriorBuilder {
         let half_width = width / 2;
         let half_height = height / 2;
 
-        let split = roll_dice(1, 4);
+        let split = rng.roll_dice(1, 4);
 
         if split <= 2 {
             let h1 = Rect::new(rect.x1, rect.y1, half_width - 1, height);
             self.rects.push(h1);
             if half_width > MIN_ROOM_SIZE {
-                self.add_subrect(h1);
+                self.add_subrect(h1, rng);
             }
 
             let h2 = Rect::new(rect.x1 + half_width, rect.y1, half_width, height);
             self.rects.push(h2);
             if half_width > MIN_ROOM_SIZE {
-                self.add_subrect(h2);
+                self.add_subrect(h2, rng);
             }
         } else {
             let v1 = Rect::new(rect.x1, rect.y1, width, half_height - 1);
             self.rects.push(v1);
             if half_height > MIN_ROOM_SIZE {
-                self.add_subrect(v1);
+                self.add_subrect(v1, rng);
             }
 
             let v2 = Rect::new(rect.x1, rect.y1 + half_height, width, half_height);
             self.rects.push(v2);
             if half_height > MIN_ROOM_SIZE {
-                self.add_subrect(v2);
+                self.add_subrect(v2, rng);
             }
         }
     }
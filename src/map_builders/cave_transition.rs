@@ -30,7 +30,7 @@ impl CaveTransition {
         builder.with(NearestCorridors::new());
         builder.with(RoomExploder::new());
         builder.with(RoomBasedSpawner::new());
-        builder.build_map();
+        builder.build_map(&build_data.raws, &mut build_data.rng);
 
         for h in &builder.build_data.history {
             build_data.history.push(h.clone());
@@ -1,6 +1,5 @@
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, InitialMapBuilder, MetaMapBuilder};
-use crate::rng::roll_dice;
 
 pub struct CellularAutomataBuilder {}
 
@@ -71,7 +70,7 @@ impl CellularAutomataBuilder {
     fn build(&mut self, build_data: &mut BuilderMap) {
         for y in 1..build_data.map.height - 1 {
             for x in 1..build_data.map.width - 1 {
-                let roll = roll_dice(1, 100);
+                let roll = build_data.rng.roll_dice(1, 100);
                 let idx = build_data.map.xy_idx(x, y);
                 if roll > 55 {
                     build_data.map.tiles[idx] = TileType::Floor;
@@ -1,36 +1,8 @@
 use crate::map::tiletype::TileType;
-use crate::map_builders::area_starting_points::{
-    AreaEndingPosition, AreaStartingPosition, XEnd, XStart, YEnd, YStart,
-};
-use crate::map_builders::bsp_interior::BspInteriorBuilder;
-use crate::map_builders::cull_unreachable::CullUnreachable;
-use crate::map_builders::voronoi_spawning::VoronoiSpawning;
-use crate::map_builders::{BuilderChain, BuilderMap, InitialMapBuilder};
-use crate::rng::{range, roll_dice};
+use crate::map_builders::{BuilderMap, InitialMapBuilder};
 use itertools::Itertools;
 use rltk::{DistanceAlg, Point};
 
-pub fn dark_elf_city(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven City");
-    chain.start_with(BspInteriorBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::Right, YStart::Center));
-    chain.with(AreaEndingPosition::new(XEnd::Left, YEnd::Center));
-    chain.with(VoronoiSpawning::new());
-
-    chain
-}
-
-pub fn dark_elf_plaza(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven Plaza");
-    chain.start_with(PlazaMapBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::Left, YStart::Center));
-    chain.with(CullUnreachable::new());
-
-    chain
-}
-
 pub struct PlazaMapBuilder {}
 
 impl InitialMapBuilder for PlazaMapBuilder {
@@ -57,8 +29,8 @@ impl PlazaMapBuilder {
         let mut voronoi_seeds = vec![];
 
         while voronoi_seeds.len() < 32 {
-            let vx = roll_dice(1, build_data.map.width - 1);
-            let vy = roll_dice(1, build_data.map.height - 1);
+            let vx = build_data.rng.roll_dice(1, build_data.map.width - 1);
+            let vy = build_data.rng.roll_dice(1, build_data.map.height - 1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -101,7 +73,7 @@ impl PlazaMapBuilder {
                     self.fill_zone(build_data, &voronoi_membership, *zone, TileType::Wall);
                 }
                 _ => {
-                    let roll = roll_dice(1, 6);
+                    let roll = build_data.rng.roll_dice(1, 6);
                     match roll {
                         1 => self.fill_zone(
                             build_data,
@@ -183,7 +155,7 @@ impl PlazaMapBuilder {
             .enumerate()
             .filter(|(_, tile_zone)| **tile_zone == zone)
             .for_each(|(idx, _)| {
-                build_data.map.tiles[idx] = match roll_dice(1, 10) {
+                build_data.map.tiles[idx] = match build_data.rng.roll_dice(1, 10) {
                     1 => TileType::Stalactite,
                     2 => TileType::Stalagmite,
                     _ => TileType::Grass,
@@ -213,7 +185,7 @@ impl PlazaMapBuilder {
             for x in center.x - 2..=center.x + 2 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Road;
-                if roll_dice(1, 6) > 2 {
+                if build_data.rng.roll_dice(1, 6) > 2 {
                     build_data.map.bloodstains.insert(idx);
                 }
             }
@@ -223,7 +195,7 @@ impl PlazaMapBuilder {
             .spawn_list
             .push((build_data.map.xy_idx(center.x, center.y), "Altar".into()));
 
-        let available_enemies = match roll_dice(1, 3) {
+        let available_enemies = match build_data.rng.roll_dice(1, 3) {
             1 => vec!["Arbat Dark Elf", "Arbat Dark Elf Leader", "Arbat Orc Slave"],
             2 => vec!["Barbo Dark Elf", "Barbo Goblin Archer"],
             3 => vec!["Cirro Dark Elf", "Cirro Dark Priestess", "Cirro Spider"],
@@ -232,10 +204,10 @@ impl PlazaMapBuilder {
 
         for idx in &zone_tiles {
             if build_data.map.tiles[*idx] == TileType::Grass {
-                match roll_dice(1, 10) {
+                match build_data.rng.roll_dice(1, 10) {
                     1 => build_data.spawn_list.push((*idx, "Chair".into())),
                     2 => {
-                        let to_spawn = range(0, available_enemies.len() as i32);
+                        let to_spawn = build_data.rng.range(0, available_enemies.len() as i32);
                         build_data
                             .spawn_list
                             .push((*idx, available_enemies[to_spawn as usize].into()));
@@ -1,5 +1,4 @@
 use crate::map::tiletype::TileType;
-use crate::rng::roll_dice;
 
 use super::{BuilderMap, MetaMapBuilder};
 
@@ -29,7 +28,7 @@ impl DoorPlacement {
             for (i, tile) in tiles.iter().enumerate() {
                 if *tile == TileType::Floor
                     && self.door_possible(build_data, i)
-                    && roll_dice(1, 3) == 1
+                    && build_data.rng.roll_dice(1, 3) == 1
                 {
                     build_data.spawn_list.push((i, "Door".to_string()));
                 }
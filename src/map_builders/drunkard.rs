@@ -2,7 +2,6 @@ use crate::components::Position;
 use crate::map::tiletype::TileType;
 use crate::map_builders::common::{paint, Symmetry};
 use crate::map_builders::{BuilderMap, InitialMapBuilder, MetaMapBuilder};
-use crate::rng::roll_dice;
 
 pub struct DrunkardsWalkBuilder {
     settings: DrunkardSettings,
@@ -135,8 +134,8 @@ impl DrunkardsWalkBuilder {
                         drunk_x = starting_position.x;
                         drunk_y = starting_position.y;
                     } else {
-                        drunk_x = roll_dice(1, build_data.map.width - 3) + 1;
-                        drunk_y = roll_dice(1, build_data.map.height - 3) + 1;
+                        drunk_x = build_data.rng.roll_dice(1, build_data.map.width - 3) + 1;
+                        drunk_y = build_data.rng.roll_dice(1, build_data.map.height - 3) + 1;
                     }
                 }
             }
@@ -157,7 +156,7 @@ impl DrunkardsWalkBuilder {
                 );
                 build_data.map.tiles[drunk_idx] = TileType::DownStairs;
 
-                let stagger_direction = roll_dice(1, 4);
+                let stagger_direction = build_data.rng.roll_dice(1, 4);
                 match stagger_direction {
                     1 => {
                         if drunk_x > 2 {
@@ -1,39 +1,9 @@
 use rltk::{DistanceAlg, Point};
 
 use crate::map::tiletype::TileType;
-use crate::map_builders::area_starting_points::{
-    AreaEndingPosition, AreaStartingPosition, XEnd, XStart, YEnd, YStart,
-};
-use crate::map_builders::bsp_dungeon::BspDungeonBuilder;
-use crate::map_builders::cull_unreachable::CullUnreachable;
-use crate::map_builders::distant_exit::DistantExit;
 use crate::map_builders::dla::DlaBuilder;
-use crate::map_builders::room_corridor_spawner::CorridorSpawner;
-use crate::map_builders::room_draw::RoomDrawer;
-use crate::map_builders::room_sorter::{RoomSort, RoomSorter};
-use crate::map_builders::rooms_corridors_bsp::BspCorridors;
-use crate::map_builders::voronoi_spawning::VoronoiSpawning;
 use crate::map_builders::{BuilderChain, BuilderMap, MetaMapBuilder};
 
-pub fn dwarf_fort_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarven Fortress");
-    chain.start_with(BspDungeonBuilder::new());
-    chain.with(RoomSorter::new(RoomSort::Central));
-    chain.with(RoomDrawer::new());
-    chain.with(BspCorridors::new());
-    chain.with(CorridorSpawner::new());
-    chain.with(DragonsLair::new());
-    chain.with(DragonSpawner::new());
-
-    chain.with(AreaStartingPosition::new(XStart::Left, YStart::Top));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::Right, YEnd::Bottom));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-
-    chain
-}
-
 pub struct DragonsLair {}
 
 impl MetaMapBuilder for DragonsLair {
@@ -53,7 +23,7 @@ impl DragonsLair {
 
         let mut builder = BuilderChain::new(6, build_data.width, build_data.height, "New Map");
         builder.start_with(DlaBuilder::insectoid());
-        builder.build_map();
+        builder.build_map(&build_data.raws, &mut build_data.rng);
 
         for h in &builder.build_data.history {
             build_data.history.push(h.clone());
@@ -1,18 +0,0 @@
-use crate::map_builders::area_starting_points::{AreaStartingPosition, XStart, YStart};
-use crate::map_builders::cellular_automata::CellularAutomataBuilder;
-use crate::map_builders::cull_unreachable::CullUnreachable;
-use crate::map_builders::voronoi_spawning::VoronoiSpawning;
-use crate::map_builders::yellow_brick_road::YellowBrickRoad;
-use crate::map_builders::BuilderChain;
-
-pub fn forest_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Into the Woods");
-    chain.start_with(CellularAutomataBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::Left, YStart::Center));
-
-    chain.with(VoronoiSpawning::new());
-    chain.with(YellowBrickRoad::new());
-    chain
-}
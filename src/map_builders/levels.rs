@@ -0,0 +1,136 @@
+//! Builds the chain for a depth from the `levels` raws.
+
+use rltk::{console, RandomNumberGenerator};
+
+use crate::cave_decorator::CaveDecorator;
+use crate::map_builders::area_starting_points::{AreaEndingPosition, AreaStartingPosition};
+use crate::map_builders::bsp_dungeon::BspDungeonBuilder;
+use crate::map_builders::bsp_interior::BspInteriorBuilder;
+use crate::map_builders::cave_transition::CaveTransition;
+use crate::map_builders::cellular_automata::CellularAutomataBuilder;
+use crate::map_builders::cull_unreachable::CullUnreachable;
+use crate::map_builders::dark_elves::PlazaMapBuilder;
+use crate::map_builders::distant_exit::DistantExit;
+use crate::map_builders::dla::DlaBuilder;
+use crate::map_builders::door_placement::DoorPlacement;
+use crate::map_builders::drunkard::DrunkardsWalkBuilder;
+use crate::map_builders::maze::MazeBuilder;
+use crate::map_builders::prefab_builder::PrefabBuilder;
+use crate::map_builders::room_based_spawner::RoomBasedSpawner;
+use crate::map_builders::room_based_stairs::RoomBasedStairs;
+use crate::map_builders::room_based_starting_position::RoomBasedStartingPosition;
+use crate::map_builders::room_corner_rounding::RoomCornerRounder;
+use crate::map_builders::room_corridor_spawner::CorridorSpawner;
+use crate::map_builders::room_draw::RoomDrawer;
+use crate::map_builders::room_exploder::RoomExploder;
+use crate::map_builders::room_sorter::RoomSorter;
+use crate::map_builders::rooms_corridors_bsp::BspCorridors;
+use crate::map_builders::rooms_corridors_dogleg::DoglegCorridors;
+use crate::map_builders::rooms_corridors_lines::StraightLineCorridors;
+use crate::map_builders::rooms_corridors_nearest::NearestCorridors;
+use crate::map_builders::simple_map::SimpleMapBuilder;
+use crate::map_builders::town::TownBuilder;
+use crate::map_builders::voronoi::VoronoiCellBuilder;
+use crate::map_builders::voronoi_spawning::VoronoiSpawning;
+use crate::map_builders::waveform_collapse::WaveformCollapseBuilder;
+use crate::map_builders::yellow_brick_road::YellowBrickRoad;
+use crate::map_builders::{
+    random_builder, BuilderChain, DragonSpawner, DragonsLair, InitialMapBuilder, MetaMapBuilder,
+};
+use crate::raws::rawmaster::RawMaster;
+use crate::raws::{Dla, DrunkardsWalk, InitialBuilder, MetaBuilder, Voronoi};
+
+/// The chain for the first level in the raws covering `new_depth`, or a random
+/// one when none do.
+pub fn level_builder(
+    raws: &RawMaster,
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    rng: &mut RandomNumberGenerator,
+) -> BuilderChain {
+    console::log(format!("Depth: {new_depth}"));
+    let Some(level) = raws.level_for_depth(new_depth) else {
+        return random_builder(new_depth, width, height, rng);
+    };
+    let mut chain = match &level.start {
+        InitialBuilder::Random => {
+            let mut chain = random_builder(new_depth, width, height, rng);
+            chain.build_data.map.name = level.title().to_string();
+            chain
+        }
+        start => {
+            let mut chain = BuilderChain::new(new_depth, width, height, level.title());
+            chain.start_with(initial_builder(start));
+            chain
+        }
+    };
+    for builder in &level.builders {
+        chain.with(meta_builder(builder));
+    }
+    chain
+}
+
+fn initial_builder(start: &InitialBuilder) -> Box<dyn InitialMapBuilder> {
+    match start {
+        InitialBuilder::Town => TownBuilder::new(),
+        InitialBuilder::SimpleMap => SimpleMapBuilder::new(),
+        InitialBuilder::BspDungeon => BspDungeonBuilder::new(),
+        InitialBuilder::BspInterior => BspInteriorBuilder::new(),
+        InitialBuilder::CellularAutomata => CellularAutomataBuilder::new(),
+        InitialBuilder::DrunkardsWalk(preset) => match preset {
+            DrunkardsWalk::OpenArea => DrunkardsWalkBuilder::open_area(),
+            DrunkardsWalk::OpenHalls => DrunkardsWalkBuilder::open_halls(),
+            DrunkardsWalk::WindingPassages => DrunkardsWalkBuilder::winding_passages(),
+            DrunkardsWalk::FatPassages => DrunkardsWalkBuilder::fat_passages(),
+            DrunkardsWalk::FearfulSymmetry => DrunkardsWalkBuilder::fearful_symmetry(),
+        },
+        InitialBuilder::Maze => MazeBuilder::new(),
+        InitialBuilder::Dla(preset) => match preset {
+            Dla::WalkInwards => DlaBuilder::walk_inwards(),
+            Dla::WalkOutwards => DlaBuilder::walk_outwards(),
+            Dla::CentralAttractor => DlaBuilder::central_attractor(),
+            Dla::Insectoid => DlaBuilder::insectoid(),
+            Dla::HeavyErosion => DlaBuilder::heavy_erosion(),
+        },
+        InitialBuilder::Voronoi(distance) => match distance {
+            Voronoi::Pythagoras => VoronoiCellBuilder::pythagoras(),
+            Voronoi::Manhattan => VoronoiCellBuilder::manhattan(),
+            Voronoi::Chebyshev => VoronoiCellBuilder::chebyshev(),
+        },
+        InitialBuilder::Prefab(name) => PrefabBuilder::constant(name),
+        InitialBuilder::Plaza => PlazaMapBuilder::new(),
+        InitialBuilder::Random => unreachable!("Random levels start from random_builder"),
+    }
+}
+
+fn meta_builder(builder: &MetaBuilder) -> Box<dyn MetaMapBuilder> {
+    match builder {
+        MetaBuilder::AreaStartingPosition { x, y } => AreaStartingPosition::new(*x, *y),
+        MetaBuilder::AreaEndingPosition { x, y } => AreaEndingPosition::new(*x, *y),
+        MetaBuilder::CullUnreachable => CullUnreachable::new(),
+        MetaBuilder::VoronoiSpawning => VoronoiSpawning::new(),
+        MetaBuilder::DistantExit => DistantExit::new(),
+        MetaBuilder::YellowBrickRoad => YellowBrickRoad::new(),
+        MetaBuilder::CaveDecorator => CaveDecorator::new(),
+        MetaBuilder::CaveTransition => CaveTransition::new(),
+        MetaBuilder::RoomSorter(sort_by) => RoomSorter::new(*sort_by),
+        MetaBuilder::RoomDrawer => RoomDrawer::new(),
+        MetaBuilder::BspCorridors => BspCorridors::new(),
+        MetaBuilder::DoglegCorridors => DoglegCorridors::new(),
+        MetaBuilder::StraightLineCorridors => StraightLineCorridors::new(),
+        MetaBuilder::NearestCorridors => NearestCorridors::new(),
+        MetaBuilder::CorridorSpawner => CorridorSpawner::new(),
+        MetaBuilder::RoomExploder => RoomExploder::new(),
+        MetaBuilder::RoomCornerRounder => RoomCornerRounder::new(),
+        MetaBuilder::RoomBasedStartingPosition => RoomBasedStartingPosition::new(),
+        MetaBuilder::RoomBasedStairs => RoomBasedStairs::new(),
+        MetaBuilder::RoomBasedSpawner => RoomBasedSpawner::new(),
+        MetaBuilder::WaveformCollapse => WaveformCollapseBuilder::new(),
+        MetaBuilder::DoorPlacement => DoorPlacement::new(),
+        MetaBuilder::Vaults => PrefabBuilder::vaults(),
+        MetaBuilder::Sectional(name) => PrefabBuilder::sectional(name),
+        MetaBuilder::DragonsLair => DragonsLair::new(),
+        MetaBuilder::DragonSpawner => DragonSpawner::new(),
+    }
+}
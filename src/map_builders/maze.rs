@@ -1,6 +1,6 @@
 use std::cmp;
 
-use crate::rng::roll_dice;
+use rltk::RandomNumberGenerator;
 
 use crate::map::{tiletype::TileType, Map};
 use crate::map_builders::{BuilderMap, InitialMapBuilder};
@@ -127,13 +127,13 @@ impl Grid {
         neighbors
     }
 
-    fn find_next_cell(&mut self) -> Option<usize> {
+    fn find_next_cell(&mut self, rng: &mut RandomNumberGenerator) -> Option<usize> {
         let neighbors = self.get_available_neighbors();
         if !neighbors.is_empty() {
             return if neighbors.len() == 1 {
                 Some(neighbors[0])
             } else {
-                Some(neighbors[(roll_dice(1, neighbors.len() as i32) - 1) as usize])
+                Some(neighbors[(rng.roll_dice(1, neighbors.len() as i32) - 1) as usize])
             };
         }
 
@@ -144,7 +144,7 @@ impl Grid {
         let mut i = 0;
         loop {
             self.cells[self.current].visited = true;
-            let next = self.find_next_cell();
+            let next = self.find_next_cell(&mut build_data.rng);
 
             match next {
                 Some(next) => {
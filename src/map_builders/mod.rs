@@ -1,28 +1,26 @@
-use rltk::console;
+use std::sync::Arc;
+
+use rltk::RandomNumberGenerator;
 use specs::World;
 
+pub use area_starting_points::{XEnd, XStart, YEnd, YStart};
 pub use dwarf_fort::*;
+pub use levels::level_builder;
+pub use prefab_builder::{HorizontalPlacement, VerticalPlacement};
+pub use room_sorter::RoomSort;
 
 use crate::components::Position;
 use crate::map::Map;
-use crate::map_builders::area_starting_points::{AreaStartingPosition, XStart, YStart};
+use crate::map_builders::area_starting_points::AreaStartingPosition;
 use crate::map_builders::bsp_dungeon::BspDungeonBuilder;
 use crate::map_builders::bsp_interior::BspInteriorBuilder;
 use crate::map_builders::cellular_automata::CellularAutomataBuilder;
 use crate::map_builders::cull_unreachable::CullUnreachable;
-use crate::map_builders::dark_elves::{dark_elf_city, dark_elf_plaza};
 use crate::map_builders::distant_exit::DistantExit;
 use crate::map_builders::dla::DlaBuilder;
 use crate::map_builders::door_placement::DoorPlacement;
 use crate::map_builders::drunkard::DrunkardsWalkBuilder;
-use crate::map_builders::forest::forest_builder;
-use crate::map_builders::limestone_cavern::{
-    limestone_cavern_builder, limestone_deep_cavern_builder, limestone_transition_builder,
-};
 use crate::map_builders::maze::MazeBuilder;
-use crate::map_builders::mushroom_forest::{mushroom_builder, mushroom_entrance, mushroom_exit};
-use crate::map_builders::prefab_builder::prefab_level::WFC_POPULATED;
-use crate::map_builders::prefab_builder::prefab_section::UNDERGROUND_FORT;
 use crate::map_builders::prefab_builder::PrefabBuilder;
 use crate::map_builders::room_based_spawner::RoomBasedSpawner;
 use crate::map_builders::room_based_stairs::RoomBasedStairs;
@@ -31,16 +29,15 @@ use crate::map_builders::room_corner_rounding::RoomCornerRounder;
 use crate::map_builders::room_corridor_spawner::CorridorSpawner;
 use crate::map_builders::room_draw::RoomDrawer;
 use crate::map_builders::room_exploder::RoomExploder;
-use crate::map_builders::room_sorter::{RoomSort, RoomSorter};
+use crate::map_builders::room_sorter::RoomSorter;
 use crate::map_builders::rooms_corridors_bsp::BspCorridors;
 use crate::map_builders::rooms_corridors_dogleg::DoglegCorridors;
 use crate::map_builders::simple_map::SimpleMapBuilder;
-use crate::map_builders::town::town_builder;
 use crate::map_builders::voronoi::VoronoiCellBuilder;
 use crate::map_builders::voronoi_spawning::VoronoiSpawning;
 use crate::map_builders::waveform_collapse::WaveformCollapseBuilder;
+use crate::raws::rawmaster::RawMaster;
 use crate::rect::Rect;
-use crate::rng::roll_dice;
 use crate::{spawner, SHOW_MAPGEN_VISUALIZER};
 
 mod area_starting_points;
@@ -56,10 +53,8 @@ mod dla;
 mod door_placement;
 mod drunkard;
 mod dwarf_fort;
-mod forest;
-mod limestone_cavern;
+mod levels;
 mod maze;
-mod mushroom_forest;
 mod prefab_builder;
 mod room_based_spawner;
 mod room_based_stairs;
@@ -80,7 +75,6 @@ mod voronoi_spawning;
 mod waveform_collapse;
 mod yellow_brick_road;
 
-#[derive(Default)]
 pub struct BuilderMap {
     pub spawn_list: Vec<(usize, String)>,
     pub map: Map,
@@ -90,24 +84,9 @@ pub struct BuilderMap {
     pub corridors: Option<Vec<Vec<usize>>>,
     pub width: i32,
     pub height: i32,
-}
-
-pub fn level_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    console::log(format!("Depth: {new_depth}"));
-    match new_depth {
-        1 => town_builder(new_depth, width, height),
-        2 => forest_builder(new_depth, width, height),
-        3 => limestone_cavern_builder(new_depth, width, height),
-        4 => limestone_deep_cavern_builder(new_depth, width, height),
-        5 => limestone_transition_builder(new_depth, width, height),
-        6 => dwarf_fort_builder(new_depth, width, height),
-        7 => mushroom_entrance(new_depth, width, height),
-        8 => mushroom_builder(new_depth, width, height),
-        9 => mushroom_exit(new_depth, width, height),
-        10 => dark_elf_city(new_depth, width, height),
-        11 => dark_elf_plaza(new_depth, width, height),
-        _ => random_builder(new_depth, width, height),
-    }
+    /// The world's raws and generator, lent to the chain by `BuilderChain::build_map`.
+    pub raws: Arc<RawMaster>,
+    pub rng: RandomNumberGenerator,
 }
 
 impl BuilderMap {
@@ -130,7 +109,6 @@ pub trait MetaMapBuilder {
     fn build_map(&mut self, build_data: &mut BuilderMap);
 }
 
-#[derive(Default)]
 pub struct BuilderChain {
     starter: Option<Box<dyn InitialMapBuilder>>,
     builders: Vec<Box<dyn MetaMapBuilder>>,
@@ -151,6 +129,8 @@ impl BuilderChain {
                 corridors: None,
                 width,
                 height,
+                raws: Arc::default(),
+                rng: RandomNumberGenerator::seeded(0),
             },
         }
     }
@@ -166,7 +146,9 @@ impl BuilderChain {
         self.builders.push(metabuilder);
     }
 
-    pub fn build_map(&mut self) {
+    pub fn build_map(&mut self, raws: &Arc<RawMaster>, rng: &mut RandomNumberGenerator) {
+        self.build_data.raws = raws.clone();
+        std::mem::swap(&mut self.build_data.rng, rng);
         match &mut self.starter {
             None => panic!("Cannot run a map builder chain without a starting build system"),
             Some(starter) => starter.build_map(&mut self.build_data),
@@ -175,6 +157,7 @@ impl BuilderChain {
         for metabuilder in &mut self.builders {
             metabuilder.build_map(&mut self.build_data);
         }
+        std::mem::swap(&mut self.build_data.rng, rng);
     }
 
     pub fn spawn_entities(&mut self, ecs: &mut World) {
@@ -184,27 +167,32 @@ impl BuilderChain {
     }
 }
 
-pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
+pub fn random_builder(
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    rng: &mut RandomNumberGenerator,
+) -> BuilderChain {
     let mut builder = BuilderChain::new(new_depth, width, height, "New Map");
-    let type_roll = roll_dice(1, 2);
+    let type_roll = rng.roll_dice(1, 2);
     match type_roll {
-        1 => random_room_builder(&mut builder),
-        2 => random_shape_builder(&mut builder),
+        1 => random_room_builder(&mut builder, rng),
+        2 => random_shape_builder(&mut builder, rng),
         _ => unreachable!(),
     }
 
-    if roll_dice(1, 3) == 1 {
+    if rng.roll_dice(1, 3) == 1 {
         builder.with(WaveformCollapseBuilder::new());
 
-        let (start_x, start_y) = random_start_position();
+        let (start_x, start_y) = random_start_position(rng);
         builder.with(AreaStartingPosition::new(start_x, start_y));
 
         builder.with(VoronoiSpawning::new());
         builder.with(DistantExit::new());
     }
 
-    if roll_dice(1, 20) == 1 {
-        builder.with(PrefabBuilder::sectional(UNDERGROUND_FORT));
+    if rng.roll_dice(1, 20) == 1 {
+        builder.with(PrefabBuilder::random_sectional());
     }
 
     builder.with(DoorPlacement::new());
@@ -213,8 +201,8 @@ pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
     builder
 }
 
-fn random_room_builder(builder: &mut BuilderChain) {
-    let build_roll = roll_dice(1, 3);
+fn random_room_builder(builder: &mut BuilderChain, rng: &mut RandomNumberGenerator) {
+    let build_roll = rng.roll_dice(1, 3);
     match build_roll {
         1 => builder.start_with(SimpleMapBuilder::new()),
         2 => builder.start_with(BspDungeonBuilder::new()),
@@ -223,7 +211,7 @@ fn random_room_builder(builder: &mut BuilderChain) {
     }
 
     if build_roll != 3 {
-        let sort_roll = roll_dice(1, 5);
+        let sort_roll = rng.roll_dice(1, 5);
         match sort_roll {
             1 => builder.with(RoomSorter::new(RoomSort::Leftmost)),
             2 => builder.with(RoomSorter::new(RoomSort::Rightmost)),
@@ -235,19 +223,19 @@ fn random_room_builder(builder: &mut BuilderChain) {
 
         builder.with(RoomDrawer::new());
 
-        let corridor_roll = roll_dice(1, 2);
+        let corridor_roll = rng.roll_dice(1, 2);
         match corridor_roll {
             1 => builder.with(DoglegCorridors::new()),
             2 => builder.with(BspCorridors::new()),
             _ => unreachable!(),
         }
 
-        let cspawn_roll = roll_dice(1, 2);
+        let cspawn_roll = rng.roll_dice(1, 2);
         if cspawn_roll == 1 {
             builder.with(CorridorSpawner::new());
         }
 
-        let modifier_roll = roll_dice(1, 6);
+        let modifier_roll = rng.roll_dice(1, 6);
         match modifier_roll {
             1 => builder.with(RoomExploder::new()),
             2 => builder.with(RoomCornerRounder::new()),
@@ -255,24 +243,24 @@ fn random_room_builder(builder: &mut BuilderChain) {
         }
     }
 
-    let start_roll = roll_dice(1, 2);
+    let start_roll = rng.roll_dice(1, 2);
     match start_roll {
         1 => builder.with(RoomBasedStartingPosition::new()),
         2 => {
-            let (start_x, start_y) = random_start_position();
+            let (start_x, start_y) = random_start_position(rng);
             builder.with(AreaStartingPosition::new(start_x, start_y));
         }
         _ => unreachable!(),
     }
 
-    let exit_roll = roll_dice(1, 2);
+    let exit_roll = rng.roll_dice(1, 2);
     match exit_roll {
         1 => builder.with(RoomBasedStairs::new()),
         2 => builder.with(DistantExit::new()),
         _ => unreachable!(),
     }
 
-    let spawn_roll = roll_dice(1, 1);
+    let spawn_roll = rng.roll_dice(1, 1);
     match spawn_roll {
         1 => builder.with(RoomBasedSpawner::new()),
         2 => builder.with(VoronoiSpawning::new()),
@@ -280,8 +268,8 @@ fn random_room_builder(builder: &mut BuilderChain) {
     }
 }
 
-fn random_shape_builder(builder: &mut BuilderChain) {
-    let builder_roll = roll_dice(1, 18);
+fn random_shape_builder(builder: &mut BuilderChain, rng: &mut RandomNumberGenerator) {
+    let builder_roll = rng.roll_dice(1, 18);
     match builder_roll {
         1 => builder.start_with(CellularAutomataBuilder::new()),
         2 => builder.start_with(DrunkardsWalkBuilder::open_area()),
@@ -298,28 +286,28 @@ fn random_shape_builder(builder: &mut BuilderChain) {
         13 => builder.start_with(VoronoiCellBuilder::pythagoras()),
         14 => builder.start_with(VoronoiCellBuilder::manhattan()),
         15 => builder.start_with(VoronoiCellBuilder::chebyshev()),
-        _ => builder.start_with(PrefabBuilder::constant(WFC_POPULATED)),
+        _ => builder.start_with(PrefabBuilder::constant("wfc_populated")),
     }
 
     builder.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
     builder.with(CullUnreachable::new());
 
-    let (start_x, start_y) = random_start_position();
+    let (start_x, start_y) = random_start_position(rng);
     builder.with(AreaStartingPosition::new(start_x, start_y));
 
     builder.with(VoronoiSpawning::new());
     builder.with(DistantExit::new());
 }
 
-fn random_start_position() -> (XStart, YStart) {
-    let start_x = match roll_dice(1, 3) {
+fn random_start_position(rng: &mut RandomNumberGenerator) -> (XStart, YStart) {
+    let start_x = match rng.roll_dice(1, 3) {
         1 => XStart::Left,
         2 => XStart::Center,
         3 => XStart::Right,
         _ => unreachable!(),
     };
 
-    let start_y = match roll_dice(1, 3) {
+    let start_y = match rng.roll_dice(1, 3) {
         1 => YStart::Bottom,
         2 => YStart::Center,
         3 => YStart::Top,
@@ -1,30 +1,41 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
-use rltk::{console, XpFile};
+use rltk::console;
+use serde::Deserialize;
 
 use crate::components::Position;
 use crate::map::tiletype::TileType;
-use crate::map_builders::prefab_builder::prefab_level::PrefabLevel;
-use crate::map_builders::prefab_builder::prefab_rooms::{
-    CHECKERBOARD, SILLY_SIMPLE, TOTALLY_NOT_A_TRAP,
-};
-use crate::map_builders::prefab_builder::prefab_section::{
-    HorizontalPlacement, PrefabSection, VerticalPlacement,
-};
 use crate::map_builders::{BuilderMap, InitialMapBuilder, MetaMapBuilder};
-use crate::rng::roll_dice;
+use crate::random_table::RandomTable;
+use crate::raws::rawmaster::RawMaster;
+use crate::raws::{Glyph, Prefab, PrefabKind};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HorizontalPlacement {
+    Left,
+    Center,
+    Right,
+}
 
-pub mod prefab_level;
-pub mod prefab_rooms;
-pub mod prefab_section;
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalPlacement {
+    Top,
+    Center,
+    Bottom,
+}
 
+/// Which prefabs to draw, by name from the `prefabs` raws.
 #[derive(Eq, PartialEq, Clone)]
-#[allow(dead_code)]
 pub enum PrefabMode {
-    RexLevel { template: &'static str },
-    Constant { level: PrefabLevel },
-    Sectional { section: PrefabSection },
+    Constant {
+        level: String,
+    },
+    /// The named section, or one picked for the depth when there's no name.
+    Sectional {
+        section: Option<String>,
+    },
     RoomVaults,
 }
 
@@ -44,23 +55,46 @@ impl InitialMapBuilder for PrefabBuilder {
     }
 }
 
+/// The prefab of `kind` called `name`. The raws are checked for these before
+/// the game starts, so a missing one is a bug.
+fn prefab_named<'a>(raws: &'a RawMaster, name: &str, kind: PrefabKind) -> &'a Prefab {
+    match raws.prefab(name) {
+        Some(prefab) if prefab.kind == kind => prefab,
+        _ => panic!("No {kind:?} prefab named {name}"),
+    }
+}
+
+/// One of `prefabs`, weighted by how common they are.
+fn pick_prefab<'a>(prefabs: &[&'a Prefab], build_data: &mut BuilderMap) -> Option<&'a Prefab> {
+    let mut table = RandomTable::default();
+    for prefab in prefabs {
+        table.add(&prefab.name, prefab.weight);
+    }
+    let name = table.roll(&mut build_data.rng);
+    prefabs.iter().find(|prefab| prefab.name == name).copied()
+}
+
 impl PrefabBuilder {
-    #[allow(dead_code)]
-    pub fn rex_level(template: &'static str) -> Box<Self> {
+    pub fn constant(level: &str) -> Box<Self> {
         Box::new(Self {
-            mode: PrefabMode::RexLevel { template },
+            mode: PrefabMode::Constant {
+                level: level.to_string(),
+            },
         })
     }
 
-    pub fn constant(level: PrefabLevel) -> Box<Self> {
+    pub fn sectional(section: &str) -> Box<Self> {
         Box::new(Self {
-            mode: PrefabMode::Constant { level },
+            mode: PrefabMode::Sectional {
+                section: Some(section.to_string()),
+            },
         })
     }
 
-    pub fn sectional(section: PrefabSection) -> Box<Self> {
+    /// A section from those that suit the depth, if any do.
+    pub fn random_sectional() -> Box<Self> {
         Box::new(Self {
-            mode: PrefabMode::Sectional { section },
+            mode: PrefabMode::Sectional { section: None },
         })
     }
 
@@ -70,117 +104,45 @@ impl PrefabBuilder {
         })
     }
 
-    fn load_rex_map(&mut self, path: &str, build_data: &mut BuilderMap) {
-        let xp_file = XpFile::from_resource(path).unwrap();
-
-        for layer in &xp_file.layers {
-            for y in 0..layer.height {
-                for x in 0..layer.width {
-                    let cell = layer.get(x, y).unwrap();
-                    if x < build_data.map.width as usize && y < build_data.map.height as usize {
-                        let idx = build_data.map.xy_idx(x as i32, y as i32);
-                        self.char_to_map(cell.ch as u8 as char, idx, build_data);
-                    }
-                }
-            }
-        }
-    }
-
-    fn load_ascii_map(&mut self, level: &PrefabLevel, build_data: &mut BuilderMap) {
-        let string_vec = Self::read_ascii_to_vec(level.template);
-
-        let mut i = 0;
+    fn load_level(&mut self, level: &Prefab, build_data: &mut BuilderMap) {
         for ty in 0..level.height {
             for tx in 0..level.width {
                 if tx < build_data.map.width as usize && ty < build_data.map.height as usize {
                     let idx = build_data.map.xy_idx(tx as i32, ty as i32);
-                    if let Some(&c) = string_vec.get(i) {
-                        self.char_to_map(c, idx, build_data);
-                    } else {
-                        self.char_to_map(' ', idx, build_data);
-                    }
+                    self.char_to_map(level, level.char_at(tx, ty), idx, build_data);
                 }
-                i += 1;
             }
         }
     }
 
-    fn char_to_map(&mut self, ch: char, idx: usize, build_data: &mut BuilderMap) {
-        match ch {
-            ' ' => build_data.map.tiles[idx] = TileType::Floor,
-            '#' => build_data.map.tiles[idx] = TileType::Wall,
-            '@' => {
+    fn char_to_map(&mut self, prefab: &Prefab, ch: char, idx: usize, build_data: &mut BuilderMap) {
+        match prefab.glyph(ch) {
+            Some(Glyph::Tile(tile)) => build_data.map.tiles[idx] = tile,
+            Some(Glyph::Spawn(name)) => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, name));
+            }
+            Some(Glyph::Start) => {
                 let x = idx as i32 % build_data.map.width;
                 let y = idx as i32 / build_data.map.width;
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.starting_position = Some(Position { x, y });
             }
-            '>' => build_data.map.tiles[idx] = TileType::DownStairs,
-            'g' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Goblin".to_string()));
-            }
-            'o' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Orc".to_string()));
-            }
-            '^' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Bear Trap".to_string()));
-            }
-            '%' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Rations".to_string()));
-            }
-            '!' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data
-                    .spawn_list
-                    .push((idx, "Health Potion".to_string()));
-            }
-            '≈' => build_data.map.tiles[idx] = TileType::DeepWater,
-            'O' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Orc Leader".to_string()));
-            }
-            '☼' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Watch Fire".to_string()));
-            }
-            'e' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.spawn_list.push((idx, "Dark Elf".into()));
-            }
-            unknown => {
-                console::log(format!("Unknown glyph loading map: {unknown}"));
-            }
-        }
-    }
-
-    fn read_ascii_to_vec(template: &str) -> Vec<char> {
-        let mut string_vec: Vec<char> = template
-            .chars()
-            .filter(|a| *a != '\r' && *a != '\n')
-            .collect();
-        for c in &mut string_vec {
-            if *c as u8 == 160u8 {
-                *c = ' ';
+            None => {
+                console::log(format!("Unknown glyph in prefab {}: {ch}", prefab.name));
             }
         }
-        string_vec
     }
 
-    pub fn apply_sectional(&mut self, section: &PrefabSection, build_data: &mut BuilderMap) {
-        let string_vec = Self::read_ascii_to_vec(section.template);
-
+    pub fn apply_sectional(&mut self, section: &Prefab, build_data: &mut BuilderMap) {
         // Place the new section
-        let chunk_x = match section.placement.0 {
+        let chunk_x = match section.placement.x {
             HorizontalPlacement::Left => 0,
             HorizontalPlacement::Center => (build_data.map.width / 2) - (section.width as i32 / 2),
             HorizontalPlacement::Right => (build_data.map.width - 1) - section.width as i32,
         };
 
-        let chunk_y = match section.placement.1 {
+        let chunk_y = match section.placement.y {
             VerticalPlacement::Top => 0,
             VerticalPlacement::Center => (build_data.map.height / 2) - (section.height as i32 / 2),
             VerticalPlacement::Bottom => (build_data.map.height - 1) - section.height as i32,
@@ -197,7 +159,6 @@ impl PrefabBuilder {
             build_data,
         );
 
-        let mut i = 0;
         for ty in 0..section.height {
             for tx in 0..section.width {
                 if tx > 0
@@ -208,16 +169,8 @@ impl PrefabBuilder {
                     let idx = build_data
                         .map
                         .xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                    if let Some(&c) = string_vec.get(i) {
-                        self.char_to_map(c, idx, build_data);
-                    } else {
-                        eprintln!(
-                            "Broken template detected for y: {ty}, x: {tx}, using empty tile."
-                        );
-                        self.char_to_map(' ', idx, build_data);
-                    }
+                    self.char_to_map(section, section.char_at(tx, ty), idx, build_data);
                 }
-                i += 1;
             }
         }
         build_data.take_snapshot();
@@ -236,46 +189,29 @@ impl PrefabBuilder {
         build_data.take_snapshot();
     }
 
-    fn apply_room_vaults(&mut self, build_data: &mut BuilderMap) {
+    fn apply_room_vaults(&mut self, raws: &RawMaster, build_data: &mut BuilderMap) {
         self.apply_previous_iteration(|_, _| true, build_data);
 
-        let vault_roll = roll_dice(1, 6) + build_data.map.depth;
+        let vault_roll = build_data.rng.roll_dice(1, 6) + build_data.map.depth;
         if vault_roll < 4 {
             return;
         }
 
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, SILLY_SIMPLE, CHECKERBOARD];
-
-        for vault in &master_vault_list {
-            assert_eq!(
-                vault.template.len(),
-                vault.width * vault.height,
-                "This template is broken: {}",
-                vault.template
-            );
-        }
-
-        let mut possible_vaults = master_vault_list
-            .iter()
-            .filter(|v| {
-                build_data.map.depth >= v.first_depth && build_data.map.depth <= v.last_depth
-            })
-            .collect_vec();
+        let mut possible_vaults: Vec<&Prefab> = raws
+            .prefabs_for_depth(PrefabKind::Room, build_data.map.depth)
+            .collect();
 
         if possible_vaults.is_empty() {
             return;
         }
 
-        let n_vaults = i32::min(roll_dice(1, 3), possible_vaults.len() as i32);
+        let n_vaults = i32::min(build_data.rng.roll_dice(1, 3), possible_vaults.len() as i32);
         let mut used_tiles = HashSet::new();
 
         for _ in 0..n_vaults {
-            let vault_index = if possible_vaults.len() == 1 {
-                0
-            } else {
-                (roll_dice(1, possible_vaults.len() as i32) - 1) as usize
+            let Some(vault) = pick_prefab(&possible_vaults, build_data) else {
+                break;
             };
-            let vault = possible_vaults[vault_index];
 
             let mut vault_positions = Vec::new();
             let mut idx = 0usize;
@@ -318,7 +254,7 @@ impl PrefabBuilder {
                 let pos_idx = if vault_positions.len() == 1 {
                     0
                 } else {
-                    (roll_dice(1, vault_positions.len() as i32) - 1) as usize
+                    (build_data.rng.roll_dice(1, vault_positions.len() as i32) - 1) as usize
                 };
                 let pos = &vault_positions[pos_idx];
 
@@ -337,35 +273,43 @@ impl PrefabBuilder {
                         || y > chunk_y + vault.height as i32
                 });
 
-                let string_vec = Self::read_ascii_to_vec(vault.template);
-                let mut i = 0;
                 for ty in 0..vault.height {
                     for tx in 0..vault.width {
                         let idx = build_data
                             .map
                             .xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                        if let Some(&c) = string_vec.get(i) {
-                            self.char_to_map(c, idx, build_data);
-                            used_tiles.insert(idx);
-                        } else {
-                            eprintln!("Recovered out-of-bounds: i: {i}, tx: {tx}, ty: {ty}!!");
-                        }
-                        i += 1;
+                        self.char_to_map(vault, vault.char_at(tx, ty), idx, build_data);
+                        used_tiles.insert(idx);
                     }
                 }
 
                 build_data.take_snapshot();
-                possible_vaults.remove(vault_index);
+                possible_vaults.retain(|possible| possible.name != vault.name);
             }
         }
     }
 
     fn build(&mut self, build_data: &mut BuilderMap) {
-        match self.mode {
-            PrefabMode::RexLevel { template } => self.load_rex_map(template, build_data),
-            PrefabMode::Constant { level } => self.load_ascii_map(&level, build_data),
-            PrefabMode::Sectional { section } => self.apply_sectional(&section, build_data),
-            PrefabMode::RoomVaults => self.apply_room_vaults(build_data),
+        let raws = build_data.raws.clone();
+        match self.mode.clone() {
+            PrefabMode::Constant { level } => {
+                self.load_level(prefab_named(&raws, &level, PrefabKind::Level), build_data);
+            }
+            PrefabMode::Sectional {
+                section: Some(section),
+            } => {
+                let section = prefab_named(&raws, &section, PrefabKind::Section);
+                self.apply_sectional(section, build_data);
+            }
+            PrefabMode::Sectional { section: None } => {
+                let sections: Vec<&Prefab> = raws
+                    .prefabs_for_depth(PrefabKind::Section, build_data.map.depth)
+                    .collect();
+                if let Some(section) = pick_prefab(&sections, build_data) {
+                    self.apply_sectional(section, build_data);
+                }
+            }
+            PrefabMode::RoomVaults => self.apply_room_vaults(&raws, build_data),
         }
         build_data.take_snapshot();
     }
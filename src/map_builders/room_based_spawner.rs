@@ -18,6 +18,8 @@ impl RoomBasedSpawner {
         if let Some(rooms) = &build_data.rooms {
             for room in rooms.iter().skip(1) {
                 spawner::spawn_room(
+                    &build_data.raws,
+                    &mut build_data.rng,
                     &build_data.map,
                     room,
                     build_data.map.depth,
@@ -18,7 +18,14 @@ impl CorridorSpawner {
         if let Some(corridors) = &build_data.corridors {
             for c in corridors {
                 let depth = build_data.map.depth;
-                spawner::spawn_region(&build_data.map, c, depth, &mut build_data.spawn_list);
+                spawner::spawn_region(
+                    &build_data.raws,
+                    &mut build_data.rng,
+                    &build_data.map,
+                    c,
+                    depth,
+                    &mut build_data.spawn_list,
+                );
             }
         } else {
             panic!("Corridor Based Spawning only workds after corridors have been created.");
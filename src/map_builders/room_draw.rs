@@ -3,7 +3,6 @@ use rltk::{DistanceAlg, Point};
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 use crate::rect::Rect;
-use crate::rng::roll_dice;
 
 pub struct RoomDrawer {}
 
@@ -54,7 +53,7 @@ impl RoomDrawer {
         );
 
         for room in &rooms {
-            let room_type = roll_dice(1, 4);
+            let room_type = build_data.rng.roll_dice(1, 4);
             match room_type {
                 1 => self.circle(build_data, room),
                 _ => self.rectangle(build_data, room),
@@ -1,7 +1,6 @@
 use crate::map::tiletype::TileType;
 use crate::map_builders::common::{paint, Symmetry};
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
-use crate::rng::roll_dice;
 
 pub struct RoomExploder {}
 
@@ -20,7 +19,7 @@ impl RoomExploder {
 
         for room in &rooms {
             let start = room.center();
-            let n_diggers = roll_dice(1, 20) - 5;
+            let n_diggers = build_data.rng.roll_dice(1, 20) - 5;
             if n_diggers > 0 {
                 for _ in 0..n_diggers {
                     let mut drunk_x = start.0;
@@ -37,7 +36,7 @@ impl RoomExploder {
                         paint(&mut build_data.map, Symmetry::None, 1, drunk_x, drunk_y);
                         build_data.map.tiles[drunk_idx] = TileType::DownStairs;
 
-                        let stagger_direction = roll_dice(1, 4);
+                        let stagger_direction = build_data.rng.roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if drunk_x > 2 {
@@ -1,8 +1,11 @@
 use rltk::{DistanceAlg, Point};
+use serde::Deserialize;
 
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 use crate::rect::Rect;
 
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RoomSort {
     Leftmost,
     Rightmost,
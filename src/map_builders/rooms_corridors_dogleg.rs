@@ -1,5 +1,3 @@
-use crate::rng::range;
-
 use crate::map_builders::common::{apply_horizontal_tunnel, apply_vertical_tunnel};
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 
@@ -27,7 +25,7 @@ impl DoglegCorridors {
             if i > 0 {
                 let (new_x, new_y) = room.center();
                 let (prev_x, prev_y) = rooms[i - 1].center();
-                if range(0, 2) == 1 {
+                if build_data.rng.range(0, 2) == 1 {
                     let mut c1 =
                         apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, prev_y);
                     let mut c2 = apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, new_x);
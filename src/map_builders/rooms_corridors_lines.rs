@@ -14,7 +14,6 @@ impl MetaMapBuilder for StraightLineCorridors {
 }
 
 impl StraightLineCorridors {
-    #[allow(dead_code)]
     pub fn new() -> Box<Self> {
         Box::new(Self {})
     }
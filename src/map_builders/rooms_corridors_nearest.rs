@@ -14,7 +14,6 @@ impl MetaMapBuilder for NearestCorridors {
 }
 
 impl NearestCorridors {
-    #[allow(dead_code)]
     pub fn new() -> Box<Self> {
         Box::new(Self {})
     }
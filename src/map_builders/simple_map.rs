@@ -1,5 +1,3 @@
-use crate::rng::{range, roll_dice};
-
 use crate::map_builders::{BuilderMap, InitialMapBuilder};
 use crate::rect::Rect;
 
@@ -24,10 +22,10 @@ impl SimpleMapBuilder {
         let mut rooms = Vec::new();
 
         for _i in 0..MAX_ROOMS {
-            let w = range(MIN_SIZE, MAX_SIZE);
-            let h = range(MIN_SIZE, MAX_SIZE);
-            let x = roll_dice(1, build_data.map.width - w - 1) - 1;
-            let y = roll_dice(1, build_data.map.height - h - 1) - 1;
+            let w = build_data.rng.range(MIN_SIZE, MAX_SIZE);
+            let h = build_data.rng.range(MIN_SIZE, MAX_SIZE);
+            let x = build_data.rng.roll_dice(1, build_data.map.width - w - 1) - 1;
+            let y = build_data.rng.roll_dice(1, build_data.map.height - h - 1) - 1;
             let new_room = Rect::new(x, y, w, h);
             let mut ok = true;
             for other_room in &rooms {
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use crate::astar::a_star_search;
 use itertools::Itertools;
@@ -6,14 +6,7 @@ use rltk::{DistanceAlg, Point};
 
 use crate::components::Position;
 use crate::map::tiletype::TileType;
-use crate::map_builders::{BuilderChain, BuilderMap, InitialMapBuilder};
-use crate::rng::roll_dice;
-
-pub fn town_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "The Town of Bracketon");
-    chain.start_with(TownBuilder::new());
-    chain
-}
+use crate::map_builders::{BuilderMap, InitialMapBuilder};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum BuildingTag {
@@ -73,11 +66,11 @@ impl TownBuilder {
         build_data.take_snapshot();
     }
     fn water_and_piers(&self, build_data: &mut BuilderMap) {
-        let mut n = (roll_dice(1, 65535) as f32) / 65535f32;
+        let mut n = (build_data.rng.roll_dice(1, 65535) as f32) / 65535f32;
         let mut water_width = Vec::new();
 
         for y in 0..build_data.height {
-            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + roll_dice(1, 6);
+            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + build_data.rng.roll_dice(1, 6);
             water_width.push(n_water);
             n += 0.1;
             for x in 0..n_water {
@@ -91,18 +84,18 @@ impl TownBuilder {
         }
         build_data.take_snapshot();
 
-        for _i in 0..roll_dice(1, 4) + 6 {
-            let y = roll_dice(1, build_data.height) - 1;
-            for x in 2 + roll_dice(1, 6)..water_width[y as usize] + 4 {
+        for _i in 0..build_data.rng.roll_dice(1, 4) + 6 {
+            let y = build_data.rng.roll_dice(1, build_data.height) - 1;
+            for x in 2 + build_data.rng.roll_dice(1, 6)..water_width[y as usize] + 4 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::WoodFloor;
             }
         }
         build_data.take_snapshot();
     }
-    fn town_walls(&self, build_data: &mut BuilderMap) -> (HashSet<usize>, i32) {
-        let mut available_building_tiles = HashSet::new();
-        let wall_gap_y = roll_dice(1, build_data.height - 9) + 5;
+    fn town_walls(&self, build_data: &mut BuilderMap) -> (BTreeSet<usize>, i32) {
+        let mut available_building_tiles = BTreeSet::new();
+        let wall_gap_y = build_data.rng.roll_dice(1, build_data.height - 9) + 5;
         for y in 1..build_data.height - 2 {
             if !(y > wall_gap_y - 4 && y < wall_gap_y + 4) {
                 let idx = build_data.map.xy_idx(30, y);
@@ -140,15 +133,15 @@ impl TownBuilder {
         &self,
 
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) -> Vec<(i32, i32, i32, i32)> {
         let mut buildings = Vec::new();
         let mut n_buildings = 0;
         while n_buildings < 12 {
-            let bx = roll_dice(1, build_data.map.width - 32) + 30;
-            let by = roll_dice(1, build_data.map.height) - 2;
-            let bw = roll_dice(1, 8) + 4;
-            let bh = roll_dice(1, 8) + 4;
+            let bx = build_data.rng.roll_dice(1, build_data.map.width - 32) + 30;
+            let by = build_data.rng.roll_dice(1, build_data.map.height) - 2;
+            let bw = build_data.rng.roll_dice(1, 8) + 4;
+            let bh = build_data.rng.roll_dice(1, 8) + 4;
             let mut possible = true;
             for y in by..by + bh {
                 for x in bx..bx + bw {
@@ -220,7 +213,7 @@ impl TownBuilder {
     ) -> Vec<usize> {
         let mut doors = Vec::new();
         for building in buildings {
-            let door_x = building.0 + 1 + roll_dice(1, building.2 - 3);
+            let door_x = building.0 + 1 + build_data.rng.roll_dice(1, building.2 - 3);
             let cy = building.1 + (building.3 / 2);
             let idx = if cy > wall_gap_y {
                 build_data.map.xy_idx(door_x, building.1)
@@ -363,7 +356,7 @@ impl TownBuilder {
                 let idx = build_data.map.xy_idx(x, y);
                 if build_data.map.tiles[idx] == TileType::WoodFloor
                     && idx != player_idx
-                    && roll_dice(1, 3) == 1
+                    && build_data.rng.roll_dice(1, 3) == 1
                     && !to_place.is_empty()
                 {
                     let entity_tag = to_place[0];
@@ -432,7 +425,7 @@ impl TownBuilder {
                 let idx = build_data.map.xy_idx(x, y);
                 if build_data.map.tiles[idx] == TileType::WoodFloor
                     && idx != 0
-                    && roll_dice(1, 2) == 1
+                    && build_data.rng.roll_dice(1, 2) == 1
                 {
                     build_data.spawn_list.push((idx, "Rat".to_string()));
                 }
@@ -441,8 +434,8 @@ impl TownBuilder {
     }
     fn spawn_dockers(&self, build_data: &mut BuilderMap) {
         for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
-            if *tt == TileType::Bridge && roll_dice(1, 6) == 1 {
-                let roll = roll_dice(1, 3);
+            if *tt == TileType::Bridge && build_data.rng.roll_dice(1, 6) == 1 {
+                let roll = build_data.rng.roll_dice(1, 3);
                 match roll {
                     1 => build_data.spawn_list.push((idx, "Dock Worker".to_string())),
                     2 => build_data
@@ -458,11 +451,11 @@ impl TownBuilder {
         &self,
         build_data: &mut BuilderMap,
 
-        available_building_tiles: &HashSet<usize>,
+        available_building_tiles: &BTreeSet<usize>,
     ) {
         for idx in available_building_tiles {
-            if roll_dice(1, 10) == 1 {
-                let roll = roll_dice(1, 4);
+            if build_data.rng.roll_dice(1, 10) == 1 {
+                let roll = build_data.rng.roll_dice(1, 4);
                 match roll {
                     1 => build_data.spawn_list.push((*idx, "Peasant".to_string())),
                     2 => build_data.spawn_list.push((*idx, "Drunk".to_string())),
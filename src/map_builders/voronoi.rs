@@ -2,7 +2,6 @@ use rltk::{DistanceAlg, Point};
 
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, InitialMapBuilder};
-use crate::rng::roll_dice;
 
 #[derive(Eq, PartialEq, Copy, Clone)]
 pub enum DistanceAlgorithm {
@@ -55,8 +54,8 @@ impl VoronoiCellBuilder {
         let mut voronoi_seeds = Vec::new();
 
         while voronoi_seeds.len() < self.n_seeds {
-            let vx = roll_dice(1, build_data.map.width - 1);
-            let vy = roll_dice(1, build_data.map.height - 1);
+            let vx = build_data.rng.roll_dice(1, build_data.map.width - 1);
+            let vy = build_data.rng.roll_dice(1, build_data.map.height - 1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -1,10 +1,9 @@
-use std::collections::{hash_map, HashMap};
+use std::collections::{btree_map, BTreeMap};
 
 use rltk::{CellularDistanceFunction, FastNoise, NoiseType};
 
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
-use crate::rng::roll_dice;
 use crate::spawner;
 
 pub struct VoronoiSpawning {}
@@ -21,8 +20,8 @@ impl VoronoiSpawning {
     }
 
     fn build(&mut self, build_data: &mut BuilderMap) {
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = FastNoise::seeded(roll_dice(1, 65536) as u64);
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = FastNoise::seeded(build_data.rng.roll_dice(1, 65536) as u64);
         noise.set_noise_type(NoiseType::Cellular);
         noise.set_frequency(0.08);
         noise.set_cellular_distance_function(CellularDistanceFunction::Manhattan);
@@ -34,7 +33,7 @@ impl VoronoiSpawning {
                     let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
                     let cell_value = cell_value_f as i32;
 
-                    if let hash_map::Entry::Vacant(e) = noise_areas.entry(cell_value) {
+                    if let btree_map::Entry::Vacant(e) = noise_areas.entry(cell_value) {
                         e.insert(vec![idx]);
                     } else {
                         noise_areas.get_mut(&cell_value).unwrap().push(idx);
@@ -45,6 +44,8 @@ impl VoronoiSpawning {
 
         for area in &noise_areas {
             spawner::spawn_region(
+                &build_data.raws,
+                &mut build_data.rng,
                 &build_data.map,
                 area.1,
                 build_data.map.depth,
@@ -64,15 +64,14 @@ pub fn build_patterns(
         }
     }
 
-    #[allow(clippy::iter_with_drain)]
-    // Dedupe
+    // Dedupe, keeping the first occurrence so the pattern order doesn't depend on hashing
     if dedupe {
         rltk::console::log(format!(
             "Pre de-duplication, there are {} patterns",
             patterns.len()
         ));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set);
+        let mut seen: HashSet<Vec<TileType>> = HashSet::new();
+        patterns.retain(|pattern| seen.insert(pattern.clone()));
         rltk::console::log(format!("There are {} patterns", patterns.len()));
     }
 
@@ -40,7 +40,7 @@ impl WaveformCollapseBuilder {
         );
         loop {
             let mut solver = Solver::new(constraints.clone(), CHUNK_SIZE, &build_data.map);
-            while !solver.iteration(&mut build_data.map) {
+            while !solver.iteration(&mut build_data.map, &mut build_data.rng) {
                 build_data.take_snapshot();
             }
             build_data.take_snapshot();
@@ -1,5 +1,6 @@
-use crate::rng::roll_dice;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
+
+use rltk::RandomNumberGenerator;
 
 use super::{Map, MapChunk};
 
@@ -83,7 +84,7 @@ impl Solver {
         neighbors
     }
 
-    pub fn iteration(&mut self, map: &mut Map) -> bool {
+    pub fn iteration(&mut self, map: &mut Map, rng: &mut RandomNumberGenerator) -> bool {
         if self.remaining.is_empty() {
             return true;
         }
@@ -106,7 +107,7 @@ impl Solver {
 
         // Pick a random chunk we haven't dealt with yet and get its index, remove from remaining list
         let remaining_index = if !neighbors_exist {
-            (roll_dice(1, self.remaining.len() as i32) - 1) as usize
+            (rng.roll_dice(1, self.remaining.len() as i32) - 1) as usize
         } else {
             0usize
         };
@@ -165,7 +166,7 @@ impl Solver {
 
         if neighbors == 0 {
             // There is nothing nearby, so we can have anything!
-            let new_chunk_idx = (roll_dice(1, self.constraints.len() as i32) - 1) as usize;
+            let new_chunk_idx = (rng.roll_dice(1, self.constraints.len() as i32) - 1) as usize;
             self.chunks[chunk_index] = Some(new_chunk_idx);
             let left_x = chunk_x as i32 * self.chunk_size;
             let right_x = (chunk_x as i32 + 1) * self.chunk_size;
@@ -183,7 +184,7 @@ impl Solver {
             }
         } else {
             // There are neighbors, so we try to be compatible with them
-            let mut options_to_check: HashSet<usize> = HashSet::new();
+            let mut options_to_check: BTreeSet<usize> = BTreeSet::new();
             for o in &options {
                 for i in o {
                     options_to_check.insert(*i);
@@ -211,7 +212,7 @@ impl Solver {
                 let new_chunk_idx = if possible_options.len() == 1 {
                     0
                 } else {
-                    roll_dice(1, possible_options.len() as i32) - 1
+                    rng.roll_dice(1, possible_options.len() as i32) - 1
                 };
 
                 self.chunks[chunk_index] = Some(possible_options[new_chunk_idx as usize]);
@@ -3,7 +3,6 @@ use rltk::{DistanceAlg, Point};
 
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
-use crate::rng::roll_dice;
 
 pub struct YellowBrickRoad {}
 
@@ -80,7 +79,7 @@ impl YellowBrickRoad {
         }
         build_data.take_snapshot();
 
-        let exit_dir = roll_dice(1, 2);
+        let exit_dir = build_data.rng.roll_dice(1, 2);
         let (seed_x, seed_y, stream_startx, stream_starty) = if exit_dir == 1 {
             (build_data.map.width - 1, 1, 0, build_data.height - 1)
         } else {
@@ -0,0 +1,89 @@
+//! Settings that outlive a run, kept next to the saves.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::saveload_system::{data_dir, SaveEncoding};
+
+/// What happens to a run's save when its character dies.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathMode {
+    /// The save is deleted, so the run is over for good. Loading a save uses it
+    /// up, and a run the game stopped in the middle of can't be recovered.
+    Permadeath,
+    /// The last autosave survives and the game over screen can go back to it.
+    RecoverAutosave,
+}
+
+impl DeathMode {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Permadeath => "Strict permadeath",
+            Self::RecoverAutosave => "Recover last autosave",
+        }
+    }
+
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Permadeath => Self::RecoverAutosave,
+            Self::RecoverAutosave => Self::Permadeath,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct GameOptions {
+    pub death_mode: DeathMode,
+    /// Player turns between autosaves. Changing level always autosaves.
+    pub autosave_turns: i32,
+    /// How saves are written. Either can be loaded whatever this is set to.
+    pub save_encoding: SaveEncoding,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self {
+            death_mode: DeathMode::Permadeath,
+            autosave_turns: 100,
+            save_encoding: SaveEncoding::Binary,
+        }
+    }
+}
+
+fn options_path() -> PathBuf {
+    data_dir().join("options.json")
+}
+
+/// The saved options, or the defaults when there are none or they can't be read.
+pub fn load_options() -> GameOptions {
+    fs::read_to_string(options_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_options(options: &GameOptions) {
+    fs::create_dir_all(data_dir()).expect("Unable to create the data directory");
+    let data = serde_json::to_string_pretty(options).expect("Unable to serialize options");
+    fs::write(options_path(), data).expect("Unable to write options");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeathMode, GameOptions};
+
+    #[test]
+    fn options_files_missing_settings_use_the_defaults() {
+        let options: GameOptions =
+            serde_json::from_str(r#"{ "death_mode": "RecoverAutosave" }"#).unwrap();
+
+        assert_eq!(options.death_mode, DeathMode::RecoverAutosave);
+        assert_eq!(
+            options.autosave_turns,
+            GameOptions::default().autosave_turns
+        );
+    }
+}
@@ -1,19 +1,22 @@
-use rltk::{to_cp437, DistanceAlg, Point, VirtualKeyCode};
+use std::sync::Arc;
+
+use rltk::{to_cp437, DistanceAlg, Point, RandomNumberGenerator, VirtualKeyCode};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
 use crate::components::{
-    Equipped, KnownSpells, Name, Target, WantsToCastSpell, WantsToShoot, Weapon,
+    ActionKind, Equipped, Initiative, KnownSpells, Name, Target, WantsToCastSpell, WantsToShoot,
+    Weapon,
 };
+use crate::gamelog::{self, GameLog};
 use crate::gui::MainMenuSelection;
 use crate::map::tiletype::TileType;
 use crate::player::RunState::{
     NextLevel, PreviousLevel, SaveGame, ShowCheatMenu, ShowDropItem, ShowInventory, ShowRemoveItem,
     Ticking,
 };
-use crate::raws::rawmaster::{faction_reaction, find_spell_entity, RAWS};
+use crate::raws::rawmaster::{faction_reaction, find_spell_entity, RawMaster};
 use crate::raws::Reaction;
-use crate::rng::roll_dice;
-use crate::{gamelog, spatial};
 
 use super::components::{
     Attributes, BlocksTile, BlocksVisibility, Consumable, Door, EntityMoved, Faction, HungerClock,
@@ -32,11 +35,16 @@ pub enum RunState {
     ShowDropItem,
     ShowTargeting { range: i32, item: Entity },
     MainMenu { menu_selection: MainMenuSelection },
+    ChooseClass,
+    CreateCharacter,
+    ShowLoadGame,
+    RecoveryPrompt,
     SaveGame,
     NextLevel,
     PreviousLevel,
     ShowRemoveItem,
     GameOver,
+    LevelUp,
     MagicMapReveal { row: i32 },
     MapGeneration,
     ShowCheatMenu,
@@ -47,12 +55,31 @@ pub enum RunState {
     ShowIdentify,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum VendorMode {
     Buy,
     Sell,
 }
 
+/// Everything the player can ask for from the map screen.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum PlayerCommand {
+    Move { dx: i32, dy: i32 },
+    UseConsumable { slot: i32 },
+    CastSpell { slot: i32 },
+    PickUp,
+    Inventory,
+    Drop,
+    SaveGame,
+    CheatMenu,
+    NextLevel,
+    PreviousLevel,
+    Skip,
+    RemoveItem,
+    Fire,
+    CycleTarget,
+}
+
 impl Player {
     pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &World) -> RunState {
         let mut positions = ecs.write_storage::<Position>();
@@ -60,7 +87,8 @@ impl Player {
         let mut viewsheds = ecs.write_storage::<Viewshed>();
         let entities = ecs.entities();
         let combat_stats = ecs.read_storage::<Attributes>();
-        let map = ecs.fetch::<Map>();
+        let mut map = ecs.fetch_mut::<Map>();
+        let raws = ecs.fetch::<Arc<RawMaster>>();
         let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
         let mut entity_moved = ecs.write_storage::<EntityMoved>();
         let mut doors = ecs.write_storage::<Door>();
@@ -70,6 +98,7 @@ impl Player {
         let factions = ecs.read_storage::<Faction>();
         let mut ppos = ecs.write_resource::<Point>();
         let vendors = ecs.read_storage::<Vendor>();
+        let mut initiatives = ecs.write_storage::<Initiative>();
 
         let mut result = RunState::AwaitingInput;
         let mut swap_entities = Vec::new();
@@ -86,8 +115,9 @@ impl Player {
             }
             let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
-            result =
-                spatial::for_each_tile_content_with_gamemode(destination_idx, |potential_target| {
+            result = map.spatial.for_each_tile_content_with_gamemode(
+                destination_idx,
+                |potential_target| {
                     if let Some(_vendor) = vendors.get(potential_target) {
                         return Some(RunState::ShowVendor {
                             vendor: potential_target,
@@ -98,8 +128,7 @@ impl Player {
                     let mut hostile = true;
                     if combat_stats.get(potential_target).is_some() {
                         if let Some(faction) = factions.get(potential_target) {
-                            let reaction =
-                                faction_reaction(&faction.name, "Player", &RAWS.lock().unwrap());
+                            let reaction = faction_reaction(&faction.name, "Player", &raws);
                             if reaction != Reaction::Attack {
                                 hostile = false;
                             }
@@ -113,6 +142,9 @@ impl Player {
                         entity_moved
                             .insert(entity, EntityMoved {})
                             .expect("Unable to insert marker");
+                        if let Some(initiative) = initiatives.get_mut(entity) {
+                            initiative.spend(ActionKind::Move);
+                        }
 
                         viewshed.dirty = true;
                         ppos.x = pos.x;
@@ -143,9 +175,10 @@ impl Player {
                         return Some(RunState::Ticking);
                     }
                     None
-                });
+                },
+            );
 
-            if !spatial::is_blocked(destination_idx) {
+            if !map.spatial.is_blocked(destination_idx) {
                 let old_idx = map.xy_idx(pos.x, pos.y);
                 pos.x = i32::min(map.width - 1, i32::max(0, pos.x + delta_x));
                 pos.y = i32::min(map.height - 1, i32::max(0, pos.y + delta_y));
@@ -153,7 +186,10 @@ impl Player {
                 entity_moved
                     .insert(entity, EntityMoved {})
                     .expect("Unable to insert marker");
-                spatial::move_entity(entity, old_idx, new_idx);
+                map.spatial.move_entity(entity, old_idx, new_idx);
+                if let Some(initiative) = initiatives.get_mut(entity) {
+                    initiative.spend(ActionKind::Move);
+                }
 
                 viewshed.dirty = true;
                 ppos.x = pos.x;
@@ -174,7 +210,7 @@ impl Player {
                 their_pos.x = m.1;
                 their_pos.y = m.2;
                 let new_idx = map.xy_idx(their_pos.x, their_pos.y);
-                spatial::move_entity(m.0, old_idx, new_idx);
+                map.spatial.move_entity(m.0, old_idx, new_idx);
                 result = RunState::Ticking;
             }
         }
@@ -182,103 +218,111 @@ impl Player {
         result
     }
 
-    pub fn player_input(gs: &State, ctx: &mut rltk::Rltk) -> RunState {
-        // TODO: fix this
-        if ctx.shift && ctx.key.is_some() {
-            let key: Option<i32> = match ctx.key.unwrap() {
-                VirtualKeyCode::Key1 => Some(1),
-                VirtualKeyCode::Key2 => Some(2),
-                VirtualKeyCode::Key3 => Some(3),
-                VirtualKeyCode::Key4 => Some(4),
-                VirtualKeyCode::Key5 => Some(5),
-                VirtualKeyCode::Key6 => Some(6),
-                VirtualKeyCode::Key7 => Some(7),
-                VirtualKeyCode::Key8 => Some(8),
-                VirtualKeyCode::Key9 => Some(9),
-                _ => None,
-            };
-            if let Some(key) = key {
-                return use_consumable_hotkey(gs, key - 1);
+    /// Translates the key pressed this frame into a command. Quitting is handled
+    /// right here since it never reaches the simulation.
+    pub fn read_command(ctx: &mut rltk::Rltk) -> Option<PlayerCommand> {
+        let key = ctx.key?;
+
+        let slot = match key {
+            VirtualKeyCode::Key1 => Some(0),
+            VirtualKeyCode::Key2 => Some(1),
+            VirtualKeyCode::Key3 => Some(2),
+            VirtualKeyCode::Key4 => Some(3),
+            VirtualKeyCode::Key5 => Some(4),
+            VirtualKeyCode::Key6 => Some(5),
+            VirtualKeyCode::Key7 => Some(6),
+            VirtualKeyCode::Key8 => Some(7),
+            VirtualKeyCode::Key9 => Some(8),
+            _ => None,
+        };
+        if let Some(slot) = slot {
+            if ctx.shift {
+                return Some(PlayerCommand::UseConsumable { slot });
+            }
+            if ctx.control {
+                return Some(PlayerCommand::CastSpell { slot });
             }
         }
 
-        if ctx.control && ctx.key.is_some() {
-            let key: Option<i32> = match ctx.key.unwrap() {
-                VirtualKeyCode::Key1 => Some(1),
-                VirtualKeyCode::Key2 => Some(2),
-                VirtualKeyCode::Key3 => Some(3),
-                VirtualKeyCode::Key4 => Some(4),
-                VirtualKeyCode::Key5 => Some(5),
-                VirtualKeyCode::Key6 => Some(6),
-                VirtualKeyCode::Key7 => Some(7),
-                VirtualKeyCode::Key8 => Some(8),
-                VirtualKeyCode::Key9 => Some(9),
-                _ => None,
-            };
-            if let Some(key) = key {
-                return use_spell_hotkey(gs, key - 1);
+        match key {
+            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
+                Some(PlayerCommand::Move { dx: -1, dy: 0 })
+            }
+            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
+                Some(PlayerCommand::Move { dx: 1, dy: 0 })
+            }
+            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
+                Some(PlayerCommand::Move { dx: 0, dy: -1 })
+            }
+            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
+                Some(PlayerCommand::Move { dx: 0, dy: 1 })
+            }
+            // Diagonals
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
+                Some(PlayerCommand::Move { dx: 1, dy: -1 })
+            }
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
+                Some(PlayerCommand::Move { dx: -1, dy: -1 })
+            }
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
+                Some(PlayerCommand::Move { dx: 1, dy: 1 })
+            }
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
+                Some(PlayerCommand::Move { dx: -1, dy: 1 })
+            }
+            VirtualKeyCode::G => Some(PlayerCommand::PickUp),
+            VirtualKeyCode::I => Some(PlayerCommand::Inventory),
+            VirtualKeyCode::D => Some(PlayerCommand::Drop),
+            VirtualKeyCode::Escape => Some(PlayerCommand::SaveGame),
+            VirtualKeyCode::Backslash => Some(PlayerCommand::CheatMenu),
+            VirtualKeyCode::Period => Some(PlayerCommand::NextLevel),
+            VirtualKeyCode::Comma => Some(PlayerCommand::PreviousLevel),
+            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => Some(PlayerCommand::Skip),
+            VirtualKeyCode::R => Some(PlayerCommand::RemoveItem),
+            VirtualKeyCode::F => Some(PlayerCommand::Fire),
+            VirtualKeyCode::V => Some(PlayerCommand::CycleTarget),
+            VirtualKeyCode::Q => {
+                ctx.quit();
+                None
             }
+            _ => None,
         }
+    }
 
-        match ctx.key {
-            None => return RunState::AwaitingInput,
-            Some(key) => match key {
-                VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                    return Self::try_move_player(-1, 0, &gs.ecs)
-                }
-                VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                    return Self::try_move_player(1, 0, &gs.ecs)
-                }
-                VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                    return Self::try_move_player(0, -1, &gs.ecs)
-                }
-                VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                    return Self::try_move_player(0, 1, &gs.ecs)
-                }
-                // Diagonals
-                VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
-                    return Self::try_move_player(1, -1, &gs.ecs)
-                }
-                VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
-                    return Self::try_move_player(-1, -1, &gs.ecs)
-                }
-                VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                    return Self::try_move_player(1, 1, &gs.ecs)
-                }
-                VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                    return Self::try_move_player(-1, 1, &gs.ecs)
-                }
-                VirtualKeyCode::G => Self::get_item(&gs.ecs),
-                VirtualKeyCode::I => return ShowInventory,
-                VirtualKeyCode::D => return ShowDropItem,
-                VirtualKeyCode::Escape => return SaveGame,
-                VirtualKeyCode::Backslash => return ShowCheatMenu,
-                VirtualKeyCode::Period => {
-                    if Self::try_next_level(&gs.ecs) {
-                        return NextLevel;
-                    }
-                }
-                VirtualKeyCode::Comma => {
-                    if Self::try_previous_level(&gs.ecs) {
-                        return PreviousLevel;
-                    }
+    /// Carries out a command and returns the run state it leads to.
+    pub fn perform(gs: &State, command: PlayerCommand) -> RunState {
+        match command {
+            PlayerCommand::Move { dx, dy } => return Self::try_move_player(dx, dy, &gs.ecs),
+            PlayerCommand::UseConsumable { slot } => return use_consumable_hotkey(gs, slot),
+            PlayerCommand::CastSpell { slot } => return use_spell_hotkey(gs, slot),
+            PlayerCommand::PickUp => Self::get_item(&gs.ecs),
+            PlayerCommand::Inventory => return ShowInventory,
+            PlayerCommand::Drop => return ShowDropItem,
+            PlayerCommand::SaveGame => return SaveGame,
+            PlayerCommand::CheatMenu => return ShowCheatMenu,
+            PlayerCommand::NextLevel => {
+                if Self::try_next_level(&gs.ecs) {
+                    return NextLevel;
                 }
-                VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return Self::skip_turn(&gs.ecs),
-                VirtualKeyCode::R => return ShowRemoveItem,
-                VirtualKeyCode::F => return fire_on_target(&gs.ecs),
-                VirtualKeyCode::V => {
-                    cycle_target(&gs.ecs);
-                    return RunState::AwaitingInput;
+            }
+            PlayerCommand::PreviousLevel => {
+                if Self::try_previous_level(&gs.ecs) {
+                    return PreviousLevel;
                 }
-                VirtualKeyCode::Q => ctx.quit(),
-                _ => return RunState::AwaitingInput,
-            },
+            }
+            PlayerCommand::Skip => return Self::skip_turn(&gs.ecs),
+            PlayerCommand::RemoveItem => return ShowRemoveItem,
+            PlayerCommand::Fire => return fire_on_target(&gs.ecs),
+            PlayerCommand::CycleTarget => {
+                cycle_target(&gs.ecs);
+                return RunState::AwaitingInput;
+            }
         }
 
         RunState::Ticking
     }
 
-    fn get_item(ecs: &World) {
+    pub fn get_item(ecs: &World) {
         let player_pos = ecs.fetch::<Point>();
         let player_entity = ecs.fetch::<Entity>();
         let entities = ecs.entities();
@@ -295,7 +339,7 @@ impl Player {
         match target_item {
             None => gamelog::Logger::new()
                 .append("There is nothing here to pick up.")
-                .log(),
+                .log(&mut ecs.write_resource::<GameLog>()),
             Some(item) => {
                 let mut pickup = ecs.write_storage::<WantsToPickupItem>();
                 pickup
@@ -320,7 +364,7 @@ impl Player {
         } else {
             gamelog::Logger::new()
                 .append("There is no way down from here.")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
             false
         }
     }
@@ -334,31 +378,34 @@ impl Player {
         } else {
             gamelog::Logger::new()
                 .append("There is no way up from here.")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
             false
         }
     }
 
-    fn skip_turn(ecs: &World) -> RunState {
+    pub fn skip_turn(ecs: &World) -> RunState {
         let player_entity = ecs.fetch::<Entity>();
         let viewshed_components = ecs.read_storage::<Viewshed>();
         let factions = ecs.read_storage::<Faction>();
 
         let worldmap_resource = ecs.fetch::<Map>();
+        let raws = ecs.fetch::<Arc<RawMaster>>();
 
         let mut can_heal = true;
         let viewshed = viewshed_components.get(*player_entity).unwrap();
         for tile in &viewshed.visible_tiles {
             let idx = worldmap_resource.xy_idx(tile.x, tile.y);
-            spatial::for_each_tile_content(idx, |entity_id| {
-                let faction = factions.get(entity_id);
-                if let Some(faction) = faction {
-                    let reaction = faction_reaction(&faction.name, "Player", &RAWS.lock().unwrap());
-                    if reaction == Reaction::Attack {
-                        can_heal = false;
+            worldmap_resource
+                .spatial
+                .for_each_tile_content(idx, |entity_id| {
+                    let faction = factions.get(entity_id);
+                    if let Some(faction) = faction {
+                        let reaction = faction_reaction(&faction.name, "Player", &raws);
+                        if reaction == Reaction::Attack {
+                            can_heal = false;
+                        }
                     }
-                }
-            });
+                });
         }
 
         let hunger_clock = ecs.read_storage::<HungerClock>();
@@ -373,7 +420,11 @@ impl Player {
             let mut health_components = ecs.write_storage::<Pools>();
             let pools = health_components.get_mut(*player_entity).unwrap();
             pools.hit_points.current = i32::min(pools.hit_points.current + 1, pools.hit_points.max);
-            if roll_dice(1, 6) == 1 {
+            if ecs
+                .write_resource::<RandomNumberGenerator>()
+                .roll_dice(1, 6)
+                == 1
+            {
                 pools.mana.current = i32::min(pools.mana.current + 1, pools.mana.max);
             }
         }
@@ -415,7 +466,7 @@ fn use_spell_hotkey(gs: &State, key: i32) -> RunState {
         } else {
             gamelog::Logger::new()
                 .append("You don't have enough mana to cast that!")
-                .log();
+                .log(&mut gs.ecs.write_resource::<GameLog>());
         }
     }
 
@@ -484,13 +535,14 @@ fn get_player_target_list(ecs: &World) -> Vec<(f32, Entity)> {
                 let distance_to_target = DistanceAlg::Pythagoras
                     .distance2d(*tile_point, Point::new(player_pos.x, player_pos.y));
                 if distance_to_target < range as f32 {
-                    spatial::for_each_tile_content(tile_idx, |possible_target| {
-                        if possible_target != *player_entity
-                            && factions.get(possible_target).is_some()
-                        {
-                            possible_targets.push((distance_to_target, possible_target));
-                        }
-                    });
+                    map.spatial
+                        .for_each_tile_content(tile_idx, |possible_target| {
+                            if possible_target != *player_entity
+                                && factions.get(possible_target).is_some()
+                            {
+                                possible_targets.push((distance_to_target, possible_target));
+                            }
+                        });
                 }
             }
         }
@@ -564,7 +616,7 @@ fn fire_on_target(ecs: &World) -> RunState {
             gamelog::Logger::new()
                 .append("You fire at")
                 .npc_name(&name.name)
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
         }
         shoot_store
             .insert(*player_entity, WantsToShoot { target })
@@ -574,7 +626,7 @@ fn fire_on_target(ecs: &World) -> RunState {
     } else {
         gamelog::Logger::new()
             .append("You don't have a target selected!")
-            .log();
+            .log(&mut ecs.write_resource::<GameLog>());
         RunState::AwaitingInput
     }
 }
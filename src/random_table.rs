@@ -1,5 +1,6 @@
+use rltk::RandomNumberGenerator;
+
 use crate::raws::rawmaster::{spawn_type_by_name, RawMaster, SpawnTableType};
-use crate::rng::roll_dice;
 
 pub struct RandomEntry {
     name: String,
@@ -31,12 +32,12 @@ impl MasterTable {
         };
     }
 
-    pub fn roll(&self) -> String {
-        let roll = roll_dice(1, 4);
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
+        let roll = rng.roll_dice(1, 4);
         match roll {
-            1 => self.items.roll(),
-            2 => self.props.roll(),
-            3 => self.mobs.roll(),
+            1 => self.items.roll(rng),
+            2 => self.props.roll(rng),
+            3 => self.mobs.roll(rng),
             _ => "None".into(),
         }
     }
@@ -56,11 +57,11 @@ impl RandomTable {
         }
     }
 
-    pub fn roll(&self) -> String {
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
         if self.total_weight == 0 {
             return "None".to_string();
         }
-        let mut roll = roll_dice(1, self.total_weight) - 1;
+        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
         let mut index: usize = 0;
 
         while roll >= 0 {
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice::DiceRoll;
+
+/// What the player can start a new game as.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerClass {
+    pub name: String,
+    pub description: String,
+    pub attributes: ClassAttributes,
+    /// Ranks in the skills the class is better or worse at than the usual 1.
+    #[serde(default)]
+    pub skills: HashMap<String, i32>,
+    /// Spells the player knows from the start.
+    #[serde(default)]
+    pub spells: Vec<String>,
+    #[serde(default)]
+    pub equipped: Vec<String>,
+    #[serde(default)]
+    pub carried: Vec<String>,
+    #[serde(default)]
+    pub gold: DiceRoll,
+    pub faction: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ClassAttributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
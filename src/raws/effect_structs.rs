@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{DamageResponse, DamageType};
+use crate::dice::DiceRoll;
+
+/// One effect of a consumable, weapon proc, spell, weapon trait or trap. Each
+/// becomes the component of the same name on whatever is spawned, so a list is
+/// written as `[ { "damage" : 6 }, "single_activation" ]`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    ProvidesHealing(i32),
+    ProvidesMana(i32),
+    Ranged(i32),
+    /// A number, or a dice expression such as `"2d6+1"`, with an optional type.
+    Damage(DamageEffect<DiceRoll>),
+    AreaOfEffect(i32),
+    /// Confuses for this many turns.
+    Confusion(i32),
+    MagicMapping,
+    TownPortal,
+    Food,
+    SingleActivation,
+    ParticleLine(ParticleEffect),
+    Particle(ParticleEffect),
+    RemoveCurse,
+    Identify,
+    TeachSpell(String),
+    /// An initiative penalty for the target. Negative values hasten it.
+    Slow(f32),
+    /// Damage each turn for a while, with an optional type.
+    DamageOverTime(DamageEffect<i32>),
+    /// Resists, is immune or vulnerable to damage types for a while.
+    Resistances(HashMap<DamageType, DamageResponse>),
+    /// Puts the status of this name from the raws on the target.
+    Status(String),
+    /// Removes the target's statuses that carry this tag.
+    Dispel(String),
+    TargetSelf,
+}
+
+/// The damage an effect deals: just the amount when it's untyped, as in
+/// `{ "damage" : "2d6" }`, or the amount and its type, as in
+/// `{ "damage" : { "amount" : "2d6", "damage_type" : "fire" } }`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "DamageEntry<T>")]
+pub struct DamageEffect<T> {
+    pub amount: T,
+    pub damage_type: Option<DamageType>,
+}
+
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "an amount, or an object with the amount and its damage_type"
+)]
+enum DamageEntry<T> {
+    Amount(T),
+    Typed(TypedDamage<T>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypedDamage<T> {
+    amount: T,
+    #[serde(default)]
+    damage_type: Option<DamageType>,
+}
+
+impl<T> From<DamageEntry<T>> for DamageEffect<T> {
+    fn from(entry: DamageEntry<T>) -> Self {
+        match entry {
+            DamageEntry::Amount(amount) => Self {
+                amount,
+                damage_type: None,
+            },
+            DamageEntry::Typed(TypedDamage {
+                amount,
+                damage_type,
+            }) => Self {
+                amount,
+                damage_type,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParticleEffect {
+    pub glyph: char,
+    /// A hex colour such as `#FF0000`.
+    pub color: String,
+    pub lifetime_ms: f32,
+}
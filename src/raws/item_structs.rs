@@ -2,6 +2,9 @@ use std::collections::HashMap;
 
 use serde::Deserialize;
 
+use crate::components::{DamageResponse, DamageType};
+use crate::raws::effect_structs::Effect;
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct Item {
     pub name: String,
@@ -16,6 +19,8 @@ pub struct Item {
     pub magic: Option<MagicItem>,
     pub attributes: Option<ItemAttributeBonus>,
     pub template_magic: Option<ItemMagicTemplate>,
+    /// Damage types the wearer takes differently while it's equipped.
+    pub resistances: Option<HashMap<DamageType, DamageResponse>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -52,12 +57,14 @@ pub struct Renderable {
 }
 
 #[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Consumable {
-    pub effects: HashMap<String, String>,
+    pub effects: Vec<Effect>,
     pub charges: Option<i32>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Weapon {
     pub range: String,
     pub attribute: String,
@@ -65,11 +72,21 @@ pub struct Weapon {
     pub hit_bonus: i32,
     pub proc_chance: Option<f32>,
     pub proc_target: Option<String>,
-    pub proc_effects: Option<HashMap<String, String>>,
+    pub proc_effects: Option<Vec<Effect>>,
+    pub speed: Option<i32>,
+    pub damage_type: Option<DamageType>,
+    /// The lowest natural d20 roll that crits; 20 if it's not given.
+    pub crit_range: Option<i32>,
+    /// What a crit multiplies the damage dice by; 2 if it's not given.
+    pub crit_multiplier: Option<i32>,
+    /// Points of armour damage reduction it ignores.
+    pub armor_penetration: Option<i32>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct Wearable {
     pub armor_class: f32,
     pub slot: String,
+    /// Damage taken off every melee hit on the wearer.
+    pub damage_reduction: Option<i32>,
 }
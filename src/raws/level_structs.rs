@@ -0,0 +1,110 @@
+use serde::Deserialize;
+
+use crate::map_builders::{RoomSort, XEnd, XStart, YEnd, YStart};
+
+/// One floor of the dungeon, or a run of floors built the same way.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub name: String,
+    /// What the map is called in game, when that isn't `name`.
+    pub title: Option<String>,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    pub start: InitialBuilder,
+    /// Run in order once the map has been started.
+    #[serde(default)]
+    pub builders: Vec<MetaBuilder>,
+}
+
+impl Level {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InitialBuilder {
+    Town,
+    SimpleMap,
+    BspDungeon,
+    BspInterior,
+    CellularAutomata,
+    DrunkardsWalk(DrunkardsWalk),
+    Maze,
+    Dla(Dla),
+    Voronoi(Voronoi),
+    /// A whole level prefab, by name.
+    Prefab(String),
+    Plaza,
+    /// Rolls for a builder and the meta builders that suit it.
+    Random,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DrunkardsWalk {
+    OpenArea,
+    OpenHalls,
+    WindingPassages,
+    FatPassages,
+    FearfulSymmetry,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Dla {
+    WalkInwards,
+    WalkOutwards,
+    CentralAttractor,
+    Insectoid,
+    HeavyErosion,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Voronoi {
+    Pythagoras,
+    Manhattan,
+    Chebyshev,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum MetaBuilder {
+    AreaStartingPosition {
+        x: XStart,
+        y: YStart,
+    },
+    AreaEndingPosition {
+        x: XEnd,
+        y: YEnd,
+    },
+    CullUnreachable,
+    VoronoiSpawning,
+    DistantExit,
+    YellowBrickRoad,
+    CaveDecorator,
+    CaveTransition,
+    RoomSorter(RoomSort),
+    RoomDrawer,
+    BspCorridors,
+    DoglegCorridors,
+    StraightLineCorridors,
+    NearestCorridors,
+    CorridorSpawner,
+    RoomExploder,
+    RoomCornerRounder,
+    RoomBasedStartingPosition,
+    RoomBasedStairs,
+    RoomBasedSpawner,
+    WaveformCollapse,
+    DoorPlacement,
+    /// Scatters the vault prefabs that suit the depth.
+    Vaults,
+    /// A prefab section, by name.
+    Sectional(String),
+    DragonsLair,
+    DragonSpawner,
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use serde::Deserialize;
 
+use crate::components::{DamageResponse, DamageType};
 use crate::raws::item_structs::Renderable;
 
 #[derive(Deserialize, Debug)]
@@ -11,6 +12,7 @@ pub struct Mob {
     pub blocks_tile: bool,
     pub vision_range: i32,
     pub movement: String,
+    pub speed: Option<i32>,
     pub quips: Option<Vec<String>>,
     pub attributes: MobAttributes,
     pub skills: Option<HashMap<String, i32>>,
@@ -26,6 +28,9 @@ pub struct Mob {
     pub vendor: Option<Vec<String>>,
     pub abilities: Option<Vec<MobAbility>>,
     pub on_death: Option<Vec<MobAbility>>,
+    pub resistances: Option<HashMap<DamageType, DamageResponse>>,
+    /// Status names and tags that can't be put on it.
+    pub status_immunities: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -55,6 +60,7 @@ pub struct NaturalAttack {
     pub name: String,
     pub hit_bonus: i32,
     pub damage: String,
+    pub damage_type: Option<DamageType>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -1,5 +1,9 @@
-use std::str;
+use std::env;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 
+use rltk::console;
 use serde::Deserialize;
 
 pub use faction_structs::Reaction;
@@ -9,38 +13,242 @@ use crate::raws::item_structs::Item;
 use crate::raws::loot_structs::LootTable;
 use crate::raws::mob_structs::Mob;
 use crate::raws::prop_structs::Prop;
-use crate::raws::rawmaster::RAWS;
+use crate::raws::rawmaster::RawMaster;
+use crate::raws::sources::{RawIndex, RawMerger};
 use crate::raws::spawn_table_structs::SpawnTableEntry;
 use crate::raws::spell_structs::Spell;
+use crate::raws::validation::RawProblem;
 
+pub use class_structs::*;
+pub use level_structs::*;
+pub use perk_structs::*;
+pub use prefab_structs::*;
+pub use status_structs::*;
 pub use weapon_traits::*;
 
+mod class_structs;
+mod effect_structs;
 mod faction_structs;
 mod item_structs;
+mod level_structs;
 mod loot_structs;
 mod mob_structs;
+mod perk_structs;
+mod prefab_structs;
 mod prop_structs;
 pub mod rawmaster;
+pub mod sources;
 mod spawn_table_structs;
 mod spell_structs;
+mod status_structs;
+pub mod validation;
 mod weapon_traits;
 
-rltk::embedded_resource!(RAW_FILE, "../../raws/spawns.json");
+/// The raws built into the game, for when the raws directory can't be read.
+const EMBEDDED_RAWS: &[(&str, &str)] = &[
+    ("classes.json", include_str!("../../raws/classes.json")),
+    ("factions.json", include_str!("../../raws/factions.json")),
+    (
+        "items/armor.json",
+        include_str!("../../raws/items/armor.json"),
+    ),
+    (
+        "items/consumables.json",
+        include_str!("../../raws/items/consumables.json"),
+    ),
+    (
+        "items/materials.json",
+        include_str!("../../raws/items/materials.json"),
+    ),
+    (
+        "items/weapons.json",
+        include_str!("../../raws/items/weapons.json"),
+    ),
+    ("levels.json", include_str!("../../raws/levels.json")),
+    (
+        "loot_tables.json",
+        include_str!("../../raws/loot_tables.json"),
+    ),
+    ("mobs.json", include_str!("../../raws/mobs.json")),
+    ("perks.json", include_str!("../../raws/perks.json")),
+    (
+        "prefabs/levels.json",
+        include_str!("../../raws/prefabs/levels.json"),
+    ),
+    (
+        "prefabs/rooms.json",
+        include_str!("../../raws/prefabs/rooms.json"),
+    ),
+    (
+        "prefabs/sections.json",
+        include_str!("../../raws/prefabs/sections.json"),
+    ),
+    ("props.json", include_str!("../../raws/props.json")),
+    (
+        "spawn_table.json",
+        include_str!("../../raws/spawn_table.json"),
+    ),
+    ("spells.json", include_str!("../../raws/spells.json")),
+    ("statuses.json", include_str!("../../raws/statuses.json")),
+    (
+        "weapon_traits.json",
+        include_str!("../../raws/weapon_traits.json"),
+    ),
+];
 
-pub fn load_raws() {
-    rltk::link_resource!(RAW_FILE, "../../raws/spawns.json");
+/// The directory raws are read from at runtime: `raws` under the working
+/// directory, unless `ROGUELIKE_RAWS` names another.
+pub fn raws_dir() -> PathBuf {
+    env::var_os("ROGUELIKE_RAWS").map_or_else(|| PathBuf::from("raws"), PathBuf::from)
+}
 
-    let raw_data = rltk::embedding::EMBED
-        .lock()
-        .get_resource("../../raws/spawns.json".to_string())
-        .unwrap();
-    let raw_string = str::from_utf8(raw_data).expect("Unable to convert to a valid UTF-8 string.");
-    let decoder: Raws = serde_json::from_str(raw_string).expect("Unable to parse JSON");
+/// Why raws couldn't be loaded.
+#[derive(Debug)]
+pub enum RawsError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    /// A mod asked for something the raws before it can't give it.
+    Overlay(PathBuf, String),
+    Invalid(Vec<RawProblem>),
+}
+
+impl fmt::Display for RawsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Unable to read {}: {e}", path.display()),
+            Self::Parse(path, e) => write!(f, "Unable to parse {}: {e}", path.display()),
+            Self::Overlay(path, message) => write!(f, "{}: {message}", path.display()),
+            Self::Invalid(problems) => write!(
+                f,
+                "{} problems in the raws, starting with {}",
+                problems.len(),
+                problems[0]
+            ),
+        }
+    }
+}
+
+/// Parses the raws in [`raws_dir`], falling back on the copy built into the game
+/// when they're missing or broken. Each world gets the result as a shared
+/// `Arc<RawMaster>` resource.
+pub fn load_raws() -> RawMaster {
+    load_raws_from(&raws_dir()).unwrap_or_else(|e| {
+        log_raws_error(&e);
+        console::log("Using the built in raws");
+        let (raws, index) = embedded_raws();
+        for problem in validation::validate(&raws) {
+            console::log(format!("Built in raws: {problem}"));
+        }
+        build_raw_master(raws, index)
+    })
+}
+
+/// Merges and validates the raws and mods in `dir`. The result includes the
+/// generated magic and traited items.
+pub fn load_raws_from(dir: &Path) -> Result<RawMaster, RawsError> {
+    let (raws, index) = sources::read_raws_dir(dir)?;
+    let mut problems = validation::validate(&raws);
+    if !problems.is_empty() {
+        for problem in &mut problems {
+            problem.source = index
+                .source_of(problem.section, &problem.entry)
+                .map(Path::to_path_buf);
+        }
+        return Err(RawsError::Invalid(problems));
+    }
+    Ok(build_raw_master(raws, index))
+}
+
+/// Writes `e` to the console, listing every problem when the raws didn't validate.
+pub fn log_raws_error(e: &RawsError) {
+    match e {
+        RawsError::Invalid(problems) => problems
+            .iter()
+            .for_each(|problem| console::log(problem.to_string())),
+        e => console::log(e.to_string()),
+    }
+}
+
+/// Handles `--validate-raws [DIR]`, which checks the raws in `DIR` (or
+/// [`raws_dir`]) and prints what's wrong with them instead of starting the game.
+/// Returns whether they're valid, or `None` when the flag wasn't given.
+pub fn validate_from_args(args: &[String]) -> Option<bool> {
+    let flag = args.iter().position(|arg| arg == "--validate-raws")?;
+    let dir = args
+        .get(flag + 1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or_else(raws_dir, PathBuf::from);
+    Some(match load_raws_from(&dir) {
+        Ok(_) => {
+            println!("The raws in {} are valid.", dir.display());
+            true
+        }
+        Err(RawsError::Invalid(problems)) => {
+            for problem in &problems {
+                println!("{problem}");
+            }
+            println!("{} problems in {}.", problems.len(), dir.display());
+            false
+        }
+        Err(e) => {
+            println!("{e}");
+            false
+        }
+    })
+}
 
-    RAWS.lock().unwrap().load(decoder);
+/// Handles `--list-raws [DIR]`, which prints every entry of the merged raws with
+/// the file it came from. Returns whether they loaded, or `None` when the flag
+/// wasn't given.
+pub fn list_from_args(args: &[String]) -> Option<bool> {
+    let flag = args.iter().position(|arg| arg == "--list-raws")?;
+    let dir = args
+        .get(flag + 1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or_else(raws_dir, PathBuf::from);
+    Some(match sources::read_raws_dir(&dir) {
+        Ok((_, index)) => {
+            for entry in &index.entries {
+                print!(
+                    "{:<14} {:<32} {}",
+                    entry.section,
+                    entry.name,
+                    entry.source.display()
+                );
+                for replaced in &entry.overrides {
+                    print!(" (replaces {})", replaced.display());
+                }
+                println!();
+            }
+            true
+        }
+        Err(e) => {
+            println!("{e}");
+            false
+        }
+    })
 }
 
+fn embedded_raws() -> (Raws, RawIndex) {
+    let mut merger = RawMerger::default();
+    for (name, text) in EMBEDDED_RAWS {
+        let path = Path::new("built in").join(name);
+        merger
+            .add_base(&path, text)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+    merger.finish()
+}
+
+fn build_raw_master(raws: Raws, index: RawIndex) -> RawMaster {
+    let mut master = RawMaster::default();
+    master.load(raws, index);
+    master
+}
+
+/// The contents of one raw file, or all of them merged.
 #[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
 pub struct Raws {
     pub items: Vec<Item>,
     pub mobs: Vec<Mob>,
@@ -50,4 +258,78 @@ pub struct Raws {
     pub faction_table: Vec<FactionInfo>,
     pub spells: Vec<Spell>,
     pub weapon_traits: Vec<WeaponTrait>,
+    pub levels: Vec<Level>,
+    pub prefabs: Vec<Prefab>,
+    pub classes: Vec<PlayerClass>,
+    pub perks: Vec<Perk>,
+    pub statuses: Vec<Status>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use specs::WorldExt;
+
+    use super::load_raws_from;
+    use crate::map::dungeon::MasterDungeonMap;
+    use crate::test_support::Scenario;
+
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &target);
+            } else {
+                fs::copy(&path, &target).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn reloaded_raws_spawn_new_templates() {
+        let dir = std::env::temp_dir().join(format!("reloaded-raws-{}", std::process::id()));
+        copy_dir(Path::new("raws"), &dir);
+        let consumables: Value =
+            serde_json::from_str(include_str!("../../raws/items/consumables.json")).unwrap();
+        let mut tonic = consumables["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["name"] == "Health Potion")
+            .unwrap()
+            .clone();
+        tonic["name"] = "Tonic".into();
+        fs::write(
+            dir.join("tonic.json"),
+            json!({ "items": [tonic] }).to_string(),
+        )
+        .unwrap();
+
+        let reloaded = load_raws_from(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let reloaded = Arc::new(reloaded.unwrap());
+        let mut rng = rltk::RandomNumberGenerator::seeded(1);
+        scenario
+            .ecs
+            .write_resource::<MasterDungeonMap>()
+            .name_unidentified_items(&reloaded, &mut rng);
+        scenario.ecs.insert(reloaded);
+        let player = scenario.player();
+        scenario.give("Tonic", player);
+
+        assert!(scenario.has_item(player, "Tonic"));
+    }
 }
@@ -0,0 +1,24 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::components::{AttributeKind, Skill};
+
+/// An extra the player can take when they go up a level, once per run.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Perk {
+    pub name: String,
+    pub description: String,
+    /// The first level it's offered at.
+    pub min_level: i32,
+    /// Points added to the attributes' base.
+    #[serde(default)]
+    pub attributes: HashMap<AttributeKind, i32>,
+    /// Ranks added to the skills.
+    #[serde(default)]
+    pub skills: HashMap<Skill, i32>,
+    /// Spells learned on taking it.
+    #[serde(default)]
+    pub spells: Vec<String>,
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::map::tiletype::TileType;
+use crate::map_builders::{HorizontalPlacement, VerticalPlacement};
+
+/// A hand drawn room, section or whole level, one string per row in `map`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Prefab {
+    pub name: String,
+    pub kind: PrefabKind,
+    pub width: usize,
+    pub height: usize,
+    /// Where a section goes on the map. Rooms go wherever there's floor for
+    /// them and levels start in the top left.
+    #[serde(default)]
+    pub placement: Placement,
+    /// The depths it's picked for at random. A level that asks for it by name
+    /// gets it whatever the depth.
+    pub min_depth: i32,
+    pub max_depth: i32,
+    /// How often it's picked, against the others that suit the depth.
+    pub weight: i32,
+    /// Glyphs that mean something else here than in [`Glyph::default_for`].
+    #[serde(default)]
+    pub legend: BTreeMap<char, Glyph>,
+    pub map: Vec<String>,
+}
+
+impl Prefab {
+    /// What `ch` stands for in this prefab.
+    pub fn glyph(&self, ch: char) -> Option<Glyph> {
+        self.legend
+            .get(&ch)
+            .cloned()
+            .or_else(|| Glyph::default_for(ch))
+    }
+
+    /// The character at `x`, `y`, or a space where `map` falls short.
+    pub fn char_at(&self, x: usize, y: usize) -> char {
+        self.map
+            .get(y)
+            .and_then(|row| row.chars().nth(x))
+            .unwrap_or(' ')
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefabKind {
+    /// Scattered over open floor by the vaults builder.
+    Room,
+    /// Stamped over part of a map that's already been built.
+    Section,
+    /// Starts a map.
+    Level,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Placement {
+    pub x: HorizontalPlacement,
+    pub y: VerticalPlacement,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            x: HorizontalPlacement::Center,
+            y: VerticalPlacement::Center,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Glyph {
+    Tile(TileType),
+    /// Floor, with the item, mob or prop of this name on it.
+    Spawn(String),
+    /// Floor, where the player starts.
+    Start,
+}
+
+impl Glyph {
+    /// What `ch` stands for in a prefab whose legend doesn't mention it.
+    pub fn default_for(ch: char) -> Option<Self> {
+        let spawn = |name: &str| Self::Spawn(name.to_string());
+        Some(match ch {
+            ' ' | '\u{a0}' => Self::Tile(TileType::Floor),
+            '#' => Self::Tile(TileType::Wall),
+            '>' => Self::Tile(TileType::DownStairs),
+            '≈' => Self::Tile(TileType::DeepWater),
+            '@' => Self::Start,
+            'g' => spawn("Goblin"),
+            'o' => spawn("Orc"),
+            '^' => spawn("Bear Trap"),
+            '%' => spawn("Rations"),
+            '!' => spawn("Health Potion"),
+            _ => return None,
+        })
+    }
+}
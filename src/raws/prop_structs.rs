@@ -1,7 +1,6 @@
-use std::collections::HashMap;
-
 use serde::Deserialize;
 
+use crate::raws::effect_structs::Effect;
 use crate::raws::item_structs::Renderable;
 use crate::raws::mob_structs;
 
@@ -18,6 +17,7 @@ pub struct Prop {
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EntryTrigger {
-    pub effects: HashMap<String, String>,
+    pub effects: Vec<Effect>,
 }
@@ -1,38 +1,35 @@
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
-use std::sync::Mutex;
-
-use lazy_static::lazy_static;
-use rltk::{console, to_cp437, RGB};
+use rltk::{console, to_cp437, RandomNumberGenerator, RGB};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use specs::{Builder, Entities, Entity, EntityBuilder, Join, ReadStorage, World, WorldExt};
+use std::collections::{HashMap, HashSet};
 
 use crate::components;
 use crate::components::{
-    AlwaysTargetsSelf, AreaOfEffect, Attribute, AttributeBonus, Attributes, BlocksTile,
-    BlocksVisibility, Confusion, Consumable, CursedItem, DamageOverTime, Door, Duration,
-    EntryTrigger, EquipmentChanged, EquipmentSlot, Equippable, Faction, Hidden, InBackpack,
-    InflictsDamage, Initiative, LightSource, MagicItemClass, MagicMapper, MoveMode, Movement, Name,
-    NaturalAttack, NaturalAttackDefense, ObfuscatedName, OnDeath, Pool, Pools, Position,
-    ProvidesFood, ProvidesHealing, ProvidesIdentification, ProvidesMana, ProvidesRemoveCurse,
-    Ranged, SerializeMe, SingleActivation, Skill, Skills, Slow, SpawnParticleBurst,
-    SpawnParticleLine, SpecialAbilities, SpecialAbility, SpellTemplate, TeachesSpell, TileSize,
-    TownPortal, Vendor, Viewshed, WeaponAttribute, Wearable,
+    AlwaysTargetsSelf, AppliesStatus, AreaOfEffect, Attribute, AttributeBonus, Attributes,
+    BlocksTile, BlocksVisibility, Confusion, Consumable, CursedItem, DamageOverTime, Dispels, Door,
+    Duration, EntryTrigger, EquipmentChanged, EquipmentSlot, Equippable, Faction, Hidden,
+    InBackpack, InflictsDamage, Initiative, KnownSpell, LightSource, MagicItemClass, MagicMapper,
+    MoveMode, Movement, Name, NaturalAttack, NaturalAttackDefense, ObfuscatedName, OnDeath, Pool,
+    Pools, Position, ProvidesFood, ProvidesHealing, ProvidesIdentification, ProvidesMana,
+    ProvidesRemoveCurse, Ranged, Resistances, SerializeMe, SingleActivation, Skill, Skills, Slow,
+    SpawnParticleBurst, SpawnParticleLine, SpecialAbilities, SpecialAbility, SpellTemplate,
+    StatusImmunities, TeachesSpell, TileSize, TownPortal, Vendor, Viewshed, WeaponAttribute,
+    Wearable,
 };
 use crate::components::{Equipped, LootTable};
 use crate::components::{Quips, Renderable};
-use crate::gamesystem::{attr_bonus, mana_at_level, npc_hp, DiceRoll};
+use crate::dice::DiceRoll;
+use crate::gamesystem::{
+    attr_bonus, mana_at_level, npc_hp, DEFAULT_CRIT_MULTIPLIER, DEFAULT_CRIT_RANGE, NORMAL_SPEED,
+};
 use crate::map::dungeon::MasterDungeonMap;
 use crate::random_table::{MasterTable, RandomTable};
+use crate::raws::effect_structs::Effect;
 use crate::raws::faction_structs::Reaction;
 use crate::raws::item_structs::MagicItem;
+use crate::raws::sources::RawIndex;
 use crate::raws::spawn_table_structs::SpawnTableEntry;
-use crate::raws::Raws;
-use crate::rng::roll_dice;
-
-lazy_static! {
-    pub static ref RAWS: Mutex<RawMaster> = Mutex::new(RawMaster::default());
-}
+use crate::raws::{Level, Perk, PlayerClass, Prefab, PrefabKind, Raws, Status};
 
 pub const LBS_TO_KG_RATIO: f32 = 2.205;
 
@@ -68,6 +65,7 @@ pub struct RawMaster {
     loot_index: HashMap<String, usize>,
     faction_index: HashMap<String, HashMap<String, Reaction>>,
     spell_index: HashMap<String, usize>,
+    index: RawIndex,
 }
 
 struct NewMagicItem {
@@ -75,9 +73,81 @@ struct NewMagicItem {
     bonus: i32,
 }
 
+impl NewMagicItem {
+    fn real_name(&self) -> String {
+        if self.bonus == -1 {
+            format!("{} -1", self.name)
+        } else {
+            format!("{} +{}", self.name, self.bonus)
+        }
+    }
+}
+
 impl RawMaster {
-    pub fn load(&mut self, raws: Raws) {
+    /// Where each entry in the raws came from. Generated magic and traited items
+    /// aren't listed.
+    pub const fn index(&self) -> &RawIndex {
+        &self.index
+    }
+
+    /// The first level in the raws that covers `depth`.
+    pub fn level_for_depth(&self, depth: i32) -> Option<&Level> {
+        self.raws
+            .levels
+            .iter()
+            .find(|level| (level.min_depth..=level.max_depth).contains(&depth))
+    }
+
+    /// The prefab called `name`.
+    pub fn prefab(&self, name: &str) -> Option<&Prefab> {
+        self.raws.prefabs.iter().find(|prefab| prefab.name == name)
+    }
+
+    /// The prefabs of `kind` that can be picked at random on `depth`.
+    pub fn prefabs_for_depth(
+        &self,
+        kind: PrefabKind,
+        depth: i32,
+    ) -> impl Iterator<Item = &Prefab> + '_ {
+        self.raws.prefabs.iter().filter(move |prefab| {
+            prefab.kind == kind && (prefab.min_depth..=prefab.max_depth).contains(&depth)
+        })
+    }
+
+    /// The classes a new game can start as, in the order the raws list them.
+    pub fn classes(&self) -> &[PlayerClass] {
+        &self.raws.classes
+    }
+
+    pub fn class(&self, name: &str) -> Option<&PlayerClass> {
+        self.raws.classes.iter().find(|class| class.name == name)
+    }
+
+    /// The perks a level up can offer, in the order the raws list them.
+    pub fn perks(&self) -> &[Perk] {
+        &self.raws.perks
+    }
+
+    pub fn perk(&self, name: &str) -> Option<&Perk> {
+        self.raws.perks.iter().find(|perk| perk.name == name)
+    }
+
+    pub fn status(&self, name: &str) -> Option<&Status> {
+        self.raws.statuses.iter().find(|status| status.name == name)
+    }
+
+    /// The spell called `name`, as the player knows it once learned.
+    pub fn known_spell(&self, name: &str) -> Option<KnownSpell> {
+        let spell = &self.raws.spells[*self.spell_index.get(name)?];
+        Some(KnownSpell {
+            display_name: spell.name.clone(),
+            mana_cost: spell.mana_cost,
+        })
+    }
+
+    pub fn load(&mut self, raws: Raws, index: RawIndex) {
         self.raws = raws;
+        self.index = index;
         self.item_index = HashMap::new();
         let mut used_names: HashSet<String> = HashSet::new();
         let mut items_to_build = vec![];
@@ -158,12 +228,7 @@ impl RawMaster {
         let base_item_index = self.item_index[&nmw.name];
         let mut base_item_copy = self.raws.items[base_item_index].clone();
         base_item_copy.vendor_category = None;
-
-        if nmw.bonus == -1 {
-            base_item_copy.name = format!("{} -1", nmw.name);
-        } else {
-            base_item_copy.name = format!("{} +{}", nmw.name, nmw.bonus);
-        }
+        base_item_copy.name = nmw.real_name();
 
         base_item_copy.magic = Some(MagicItem {
             class: match nmw.bonus {
@@ -192,20 +257,9 @@ impl RawMaster {
 
         if let Some(weapon) = base_item_copy.weapon.as_mut() {
             weapon.hit_bonus += nmw.bonus;
-            let die_roll: DiceRoll = weapon.base_damage.parse().unwrap();
-            let final_bonus = die_roll.die_bonus + nmw.bonus;
-            weapon.base_damage = match final_bonus.cmp(&0) {
-                Ordering::Less => format!(
-                    "{}d{}-{}",
-                    die_roll.n_dice,
-                    die_roll.die_type,
-                    i32::abs(final_bonus)
-                ),
-                Ordering::Equal => String::new(),
-                Ordering::Greater => {
-                    format!("{}d{}+{final_bonus}", die_roll.n_dice, die_roll.die_type)
-                }
-            };
+            if let Ok(damage) = weapon.base_damage.parse::<DiceRoll>() {
+                weapon.base_damage = damage.plus(nmw.bonus).to_string();
+            }
         }
         if let Some(armor) = base_item_copy.wearable.as_mut() {
             armor.armor_class += nmw.bonus as f32;
@@ -296,81 +350,73 @@ fn get_renderable_component(renderable: &super::item_structs::Renderable) -> Ren
 macro_rules! apply_effects {
     ( $effects:expr, $eb:expr ) => {
         for effect in $effects.iter() {
-            let effect_name = effect.0.as_str();
-            match effect_name {
-                "provides_healing" => {
+            match effect {
+                Effect::ProvidesHealing(heal_amount) => {
                     $eb = $eb.with(ProvidesHealing {
-                        heal_amount: effect.1.parse::<i32>().unwrap(),
+                        heal_amount: *heal_amount,
                     })
                 }
-                "provides_mana" => {
+                Effect::ProvidesMana(mana_amount) => {
                     $eb = $eb.with(ProvidesMana {
-                        mana_amount: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "ranged" => {
-                    $eb = $eb.with(Ranged {
-                        range: effect.1.parse::<i32>().unwrap(),
+                        mana_amount: *mana_amount,
                     })
                 }
-                "damage" => {
+                Effect::Ranged(range) => $eb = $eb.with(Ranged { range: *range }),
+                Effect::Damage(damage) => {
                     $eb = $eb.with(InflictsDamage {
-                        damage: effect.1.parse::<i32>().unwrap(),
+                        damage: damage.amount.clone(),
+                        damage_type: damage.damage_type.unwrap_or_default(),
                     })
                 }
-                "area_of_effect" => {
-                    $eb = $eb.with(AreaOfEffect {
-                        radius: effect.1.parse::<i32>().unwrap(),
-                    })
-                }
-                "confusion" => {
+                Effect::AreaOfEffect(radius) => $eb = $eb.with(AreaOfEffect { radius: *radius }),
+                Effect::Confusion(turns) => {
                     $eb = $eb.with(Confusion {});
-                    $eb = $eb.with(Duration {
-                        turns: effect.1.parse::<i32>().unwrap(),
-                    });
+                    $eb = $eb.with(Duration { turns: *turns });
                 }
-                "magic_mapping" => $eb = $eb.with(MagicMapper {}),
-                "town_portal" => $eb = $eb.with(TownPortal {}),
-                "food" => $eb = $eb.with(ProvidesFood {}),
-                "single_activation" => $eb = $eb.with(SingleActivation {}),
-                "particle_line" => {
-                    $eb = $eb.with(
-                        effect
-                            .1
-                            .parse::<SpawnParticleLine>()
-                            .expect("Failed to parse SpawnParticleLine"),
-                    )
+                Effect::MagicMapping => $eb = $eb.with(MagicMapper {}),
+                Effect::TownPortal => $eb = $eb.with(TownPortal {}),
+                Effect::Food => $eb = $eb.with(ProvidesFood {}),
+                Effect::SingleActivation => $eb = $eb.with(SingleActivation {}),
+                Effect::ParticleLine(particle) => {
+                    $eb = $eb.with(SpawnParticleLine {
+                        glyph: to_cp437(particle.glyph),
+                        color: RGB::from_hex(&particle.color).expect("Bad RGB"),
+                        lifetime_ms: particle.lifetime_ms,
+                    })
                 }
-                "particle" => {
-                    $eb = $eb.with(
-                        effect
-                            .1
-                            .parse::<SpawnParticleBurst>()
-                            .expect("Failed to parse SpawnParticleBurst"),
-                    )
+                Effect::Particle(particle) => {
+                    $eb = $eb.with(SpawnParticleBurst {
+                        glyph: to_cp437(particle.glyph),
+                        color: RGB::from_hex(&particle.color).expect("Bad RGB"),
+                        lifetime_ms: particle.lifetime_ms,
+                    })
                 }
-                "remove_curse" => $eb = $eb.with(ProvidesRemoveCurse {}),
-                "identify" => $eb = $eb.with(ProvidesIdentification {}),
-                "teach_spell" => {
+                Effect::RemoveCurse => $eb = $eb.with(ProvidesRemoveCurse {}),
+                Effect::Identify => $eb = $eb.with(ProvidesIdentification {}),
+                Effect::TeachSpell(spell) => {
                     $eb = $eb.with(TeachesSpell {
-                        spell: effect.1.into(),
+                        spell: spell.clone(),
                     })
                 }
-                "slow" => {
+                Effect::Slow(initiative_penalty) => {
                     $eb = $eb.with(Slow {
-                        initiative_penalty: effect.1.parse::<f32>().unwrap(),
+                        initiative_penalty: *initiative_penalty,
                     })
                 }
-                "damage_over_time" => {
+                Effect::DamageOverTime(damage) => {
                     $eb = $eb.with(DamageOverTime {
-                        damage: effect.1.parse::<i32>().unwrap(),
+                        damage: damage.amount,
+                        damage_type: damage.damage_type.unwrap_or_default(),
                     })
                 }
-                "target_self" => $eb = $eb.with(AlwaysTargetsSelf {}),
-                _ => console::log(format!(
-                    "Warning: consumable effect {} not implemented.",
-                    effect_name
-                )),
+                Effect::Resistances(resistances) => {
+                    $eb = $eb.with(Resistances {
+                        damage: resistances.clone(),
+                    })
+                }
+                Effect::Status(name) => $eb = $eb.with(AppliesStatus { name: name.clone() }),
+                Effect::Dispel(tag) => $eb = $eb.with(Dispels { tag: tag.clone() }),
+                Effect::TargetSelf => $eb = $eb.with(AlwaysTargetsSelf {}),
             }
         }
     };
@@ -423,13 +469,10 @@ pub fn spawn_named_item(
             eb = eb.with(Equippable {
                 slot: EquipmentSlot::Melee,
             });
-            let roll: DiceRoll = weapon.base_damage.parse().unwrap();
             let mut wpn = components::Weapon {
                 attribute: WeaponAttribute::Might,
                 hit_bonus: weapon.hit_bonus,
-                damage_n_dice: roll.n_dice,
-                damage_die_type: roll.die_type,
-                damage_bonus: roll.die_bonus,
+                damage: weapon.base_damage.parse().expect("Bad dice"),
                 proc_chance: weapon.proc_chance,
                 proc_target: weapon.proc_target.clone(),
                 range: if weapon.range == "melee" {
@@ -437,6 +480,11 @@ pub fn spawn_named_item(
                 } else {
                     Some(weapon.range.parse().expect("Not a number"))
                 },
+                speed: weapon.speed.unwrap_or(NORMAL_SPEED),
+                damage_type: weapon.damage_type.unwrap_or_default(),
+                crit_range: weapon.crit_range.unwrap_or(DEFAULT_CRIT_RANGE),
+                crit_multiplier: weapon.crit_multiplier.unwrap_or(DEFAULT_CRIT_MULTIPLIER),
+                armor_penetration: weapon.armor_penetration.unwrap_or(0),
             };
             match weapon.attribute.as_str() {
                 "Quickness" | "quickness" => wpn.attribute = WeaponAttribute::Quickness,
@@ -456,6 +504,13 @@ pub fn spawn_named_item(
             eb = eb.with(Wearable {
                 slot,
                 armor_class: wearable.armor_class,
+                damage_reduction: wearable.damage_reduction.unwrap_or(0),
+            });
+        }
+
+        if let Some(resistances) = &item_template.resistances {
+            eb = eb.with(Resistances {
+                damage: resistances.clone(),
             });
         }
 
@@ -518,6 +573,11 @@ pub fn spawn_named_mob(
 ) -> Option<Entity> {
     if raws.mob_index.contains_key(key) {
         let mob_template = &raws.raws.mobs[raws.mob_index[key]];
+        // Rolled up front, the entity builder holds on to the world
+        let mob_gold = mob_template.gold.as_ref().map_or(0.0, |gold| {
+            let roll: DiceRoll = gold.parse().expect("Bad dice");
+            roll.roll(&mut ecs.write_resource::<RandomNumberGenerator>()) as f32
+        });
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
         eb = spawn_position(pos, eb, key, raws);
 
@@ -628,40 +688,13 @@ pub fn spawn_named_mob(
             },
             total_weight: 0.0,
             total_initiative_penalty: 0.0,
-            gold: mob_template.gold.as_ref().map_or(0.0, |gold| {
-                let roll: DiceRoll = gold.parse().unwrap();
-                (roll_dice(roll.n_dice, roll.die_type) + roll.die_bonus) as f32
-            }),
+            gold: mob_gold,
             god_mode: false,
         };
         eb = eb.with(pools);
         eb = eb.with(EquipmentChanged {});
 
-        let mut skills = Skills {
-            skills: HashMap::new(),
-        };
-        skills.skills.insert(Skill::Melee, 1);
-        skills.skills.insert(Skill::Defense, 1);
-        skills.skills.insert(Skill::Magic, 1);
-        if let Some(mobskills) = &mob_template.skills {
-            for sk in mobskills {
-                match sk.0.as_str() {
-                    "Melee" => {
-                        skills.skills.insert(Skill::Melee, *sk.1);
-                    }
-                    "Defense" => {
-                        skills.skills.insert(Skill::Defense, *sk.1);
-                    }
-                    "Magic" => {
-                        skills.skills.insert(Skill::Magic, *sk.1);
-                    }
-                    _ => {
-                        unreachable!("Unknown skill referenced: [{}]", sk.0);
-                    }
-                }
-            }
-        }
-        eb = eb.with(skills);
+        eb = eb.with(skills_from_raws(mob_template.skills.iter().flatten()));
 
         if let Some(vendor) = &mob_template.vendor {
             eb = eb.with(Vendor {
@@ -676,13 +709,11 @@ pub fn spawn_named_mob(
             };
             if let Some(attacks) = &na.attacks {
                 for nattack in attacks {
-                    let roll: DiceRoll = nattack.damage.parse().unwrap();
                     let attack = NaturalAttack {
                         name: nattack.name.clone(),
                         hit_bonus: nattack.hit_bonus,
-                        damage_n_dice: roll.n_dice,
-                        damage_die_type: roll.die_type,
-                        damage_bonus: roll.die_bonus,
+                        damage: nattack.damage.parse().expect("Bad dice"),
+                        damage_type: nattack.damage_type.unwrap_or_default(),
                     };
                     nature.attacks.push(attack);
                 }
@@ -731,7 +762,11 @@ pub fn spawn_named_mob(
             }
         }
 
-        eb = eb.with(Initiative { current: 2 });
+        eb = eb.with(Initiative {
+            current: 2,
+            speed: mob_template.speed.unwrap_or(NORMAL_SPEED),
+            spent: None,
+        });
 
         if let Some(ability_list) = &mob_template.abilities {
             let mut a = SpecialAbilities { abilities: vec![] };
@@ -758,6 +793,18 @@ pub fn spawn_named_mob(
             eb = eb.with(a);
         }
 
+        if let Some(resistances) = &mob_template.resistances {
+            eb = eb.with(Resistances {
+                damage: resistances.clone(),
+            });
+        }
+
+        if let Some(immunities) = &mob_template.status_immunities {
+            eb = eb.with(StatusImmunities {
+                statuses: immunities.clone(),
+            });
+        }
+
         let new_mob = eb.build();
 
         if let Some(wielding) = &mob_template.equipped {
@@ -889,14 +936,18 @@ fn find_slot_for_equippable_item(tag: &str, raws: &RawMaster) -> EquipmentSlot {
     panic!("Trying to equip {tag}, but it has no slot tag.");
 }
 
-pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
+pub fn get_item_drop(
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+    table: &str,
+) -> Option<String> {
     if raws.loot_index.contains_key(table) {
         let mut rt = RandomTable::default();
         let available_options = &raws.raws.loot_tables[raws.loot_index[table]];
         for item in &available_options.drops {
             rt.add(item.name.clone(), item.weight);
         }
-        return Some(rt.roll());
+        return Some(rt.roll(rng));
     }
 
     None
@@ -931,8 +982,7 @@ pub fn get_vendor_items(categories: &[String], raws: &RawMaster) -> Vec<(String,
     result
 }
 
-pub fn get_scroll_tags() -> Vec<String> {
-    let raws = &RAWS.lock().unwrap();
+pub fn get_scroll_tags(raws: &RawMaster) -> Vec<String> {
     let mut result = vec![];
 
     for item in &raws.raws.items {
@@ -946,8 +996,7 @@ pub fn get_scroll_tags() -> Vec<String> {
     result
 }
 
-pub fn is_tag_magic(tag: &str) -> bool {
-    let raws = &RAWS.lock().unwrap();
+pub fn is_tag_magic(raws: &RawMaster, tag: &str) -> bool {
     if raws.item_index.contains_key(tag) {
         let item_template = &raws.raws.items[raws.item_index[tag]];
         item_template.magic.is_some()
@@ -956,8 +1005,7 @@ pub fn is_tag_magic(tag: &str) -> bool {
     }
 }
 
-pub fn get_potion_tag() -> Vec<String> {
-    let raws = &RAWS.lock().unwrap();
+pub fn get_potion_tag(raws: &RawMaster) -> Vec<String> {
     let mut result = Vec::new();
 
     for item in &raws.raws.items {
@@ -989,8 +1037,35 @@ pub fn spawn_named_spell(raws: &RawMaster, ecs: &mut World, key: &str) -> Option
     Some(eb.build())
 }
 
-pub fn spawn_all_spells(ecs: &mut World) {
-    let raws = &super::RAWS.lock().unwrap();
+/// Rank 1 in every skill, except where `ranks` says otherwise.
+pub fn skills_from_raws<'a>(ranks: impl IntoIterator<Item = (&'a String, &'a i32)>) -> Skills {
+    let mut skills = Skills {
+        skills: HashMap::new(),
+        practice: HashMap::new(),
+    };
+    skills.skills.insert(Skill::Melee, 1);
+    skills.skills.insert(Skill::Defense, 1);
+    skills.skills.insert(Skill::Magic, 1);
+    for (name, rank) in ranks {
+        match name.as_str() {
+            "Melee" => {
+                skills.skills.insert(Skill::Melee, *rank);
+            }
+            "Defense" => {
+                skills.skills.insert(Skill::Defense, *rank);
+            }
+            "Magic" => {
+                skills.skills.insert(Skill::Magic, *rank);
+            }
+            _ => {
+                unreachable!("Unknown skill referenced: [{name}]");
+            }
+        }
+    }
+    skills
+}
+
+pub fn spawn_all_spells(raws: &RawMaster, ecs: &mut World) {
     for spell in &raws.raws.spells {
         spawn_named_spell(raws, ecs, &spell.name);
     }
@@ -1025,6 +1100,8 @@ pub fn find_spell_entity_by_name(
     None
 }
 
+/// Templates on anything but weapons and armour are ignored; the validator
+/// reports them.
 fn append_magic_template(items_to_build: &mut Vec<NewMagicItem>, item: &super::Item) {
     if let Some(template) = &item.template_magic {
         if item.weapon.is_some() || item.wearable.is_some() {
@@ -1040,11 +1117,78 @@ fn append_magic_template(items_to_build: &mut Vec<NewMagicItem>, item: &super::I
                     bonus,
                 });
             }
-        } else {
-            console::log(format!(
-                "{} is marked as templated, but isn't a weapon or armor.",
-                item.name
-            ));
         }
     }
 }
+
+/// The names of the magic and traited items `RawMaster::load` will generate from
+/// the templates in `raws`.
+pub fn generated_item_names(raws: &Raws) -> Vec<String> {
+    let mut names = Vec::new();
+    for item in &raws.items {
+        let mut items_to_build = Vec::new();
+        append_magic_template(&mut items_to_build, item);
+        for nmw in &items_to_build {
+            let name = nmw.real_name();
+            if nmw.bonus > 0 && item.weapon.is_some() {
+                names.extend(
+                    raws.weapon_traits
+                        .iter()
+                        .map(|wt| format!("{} {name}", wt.name)),
+                );
+            }
+            names.push(name);
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::prelude::*;
+
+    use crate::components::{DamageOverTime, DamageType, InflictsDamage};
+    use crate::dice::DiceRoll;
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn equipped_and_carried_items_belong_to_their_owner() {
+        let mut scenario = Scenario::new(
+            "
+            ####
+            #@.#
+            ####
+            ",
+        );
+        let player = scenario.player();
+        scenario.give("Health Potion", player);
+
+        assert!(scenario.has_item(player, "Health Potion"));
+        assert!(!scenario.has_item(player, "Longsword"));
+    }
+
+    #[test]
+    fn damage_effects_carry_their_own_damage_type() {
+        let mut scenario = Scenario::new(
+            "
+            ####
+            #@.#
+            ####
+            ",
+        );
+        let player = scenario.player();
+        let fireball = scenario.give("Fireball Scroll", player);
+        let poison = scenario.give("Poison Potion", player);
+        let dart = scenario.give("Magic Missile Scroll", player);
+
+        let inflicts = scenario.ecs.read_storage::<InflictsDamage>();
+        let over_time = scenario.ecs.read_storage::<DamageOverTime>();
+        let fire = inflicts.get(fireball).unwrap();
+        let venom = over_time.get(poison).unwrap();
+        assert_eq!(fire.damage, DiceRoll::constant(20));
+        assert_eq!(fire.damage_type, DamageType::Fire);
+        assert_eq!(inflicts.get(dart).unwrap().damage_type, DamageType::Untyped);
+        assert_eq!(venom.damage, 2);
+        assert_eq!(venom.damage_type, DamageType::Poison);
+    }
+}
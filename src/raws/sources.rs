@@ -0,0 +1,366 @@
+//! Finds the raw files and merges them into one set of raws.
+//!
+//! Every `.json` file under the raws directory holds any of the sections [`Raws`]
+//! has. Files are read in path order and their entries appended. Mods live in
+//! `mods/<name>/` beneath it, laid out the same way, and are applied in the order
+//! `mods/load_order.json` lists, or alphabetically without one. A mod entry
+//! replaces the entry with the same name in its section, or is added when there's
+//! none. A mod's `remove` section takes entries out by name:
+//! `"remove" : { "mobs" : [ "Rat" ] }`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{Raws, RawsError};
+
+/// The sections of a raw file, in the order the merged raws list them.
+pub const SECTIONS: &[&str] = &[
+    "spawn_table",
+    "loot_tables",
+    "faction_table",
+    "items",
+    "mobs",
+    "props",
+    "spells",
+    "weapon_traits",
+    "levels",
+    "prefabs",
+    "classes",
+    "perks",
+    "statuses",
+];
+
+const MODS_DIR: &str = "mods";
+const LOAD_ORDER_FILE: &str = "load_order.json";
+const REMOVE_SECTION: &str = "remove";
+
+/// Where one entry of the merged raws came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub section: &'static str,
+    pub name: String,
+    pub source: PathBuf,
+    /// The files that defined this entry before `source` replaced it.
+    pub overrides: Vec<PathBuf>,
+}
+
+/// Every entry of the merged raws, in order, with the file it came from.
+#[derive(Clone, Debug, Default)]
+pub struct RawIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl RawIndex {
+    /// The file the entry called `name` in `section` came from.
+    pub fn source_of(&self, section: &str, name: &str) -> Option<&Path> {
+        self.entries
+            .iter()
+            .find(|entry| entry.section == section && entry.name == name)
+            .map(|entry| entry.source.as_path())
+    }
+}
+
+struct Entry {
+    value: Value,
+    index: IndexEntry,
+}
+
+/// Collects raw files one at a time and merges them.
+pub struct RawMerger {
+    /// One list per entry in `SECTIONS`.
+    sections: Vec<Vec<Entry>>,
+}
+
+impl Default for RawMerger {
+    fn default() -> Self {
+        Self {
+            sections: SECTIONS.iter().map(|_| Vec::new()).collect(),
+        }
+    }
+}
+
+fn parse_error(path: &Path, message: impl std::fmt::Display) -> RawsError {
+    RawsError::Parse(path.to_path_buf(), serde::de::Error::custom(message))
+}
+
+/// Splits a raw file into its `remove` section and the rest, checking the rest
+/// parses as raws so mistakes are reported against the file they're in.
+fn parse_file(path: &Path, text: &str) -> Result<(Map<String, Value>, Option<Value>), RawsError> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|e| RawsError::Parse(path.to_path_buf(), e))?;
+    let Value::Object(mut sections) = value else {
+        return Err(parse_error(path, "a raw file should hold an object"));
+    };
+    let remove = sections.remove(REMOVE_SECTION);
+    Raws::deserialize(&Value::Object(sections.clone()))
+        .map_err(|e| RawsError::Parse(path.to_path_buf(), e))?;
+    Ok((sections, remove))
+}
+
+fn entry_name(path: &Path, section: &str, value: &Value) -> Result<String, RawsError> {
+    value["name"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| parse_error(path, format!("an entry in {section} has no name")))
+}
+
+impl RawMerger {
+    /// Appends every entry in the file at `path`.
+    pub fn add_base(&mut self, path: &Path, text: &str) -> Result<(), RawsError> {
+        let (mut sections, remove) = parse_file(path, text)?;
+        if remove.is_some() {
+            return Err(parse_error(path, "only mods can remove entries"));
+        }
+        for (i, &section) in SECTIONS.iter().enumerate() {
+            let Some(Value::Array(values)) = sections.remove(section) else {
+                continue;
+            };
+            for value in values {
+                let name = entry_name(path, section, &value)?;
+                self.sections[i].push(Entry {
+                    value,
+                    index: IndexEntry {
+                        section,
+                        name,
+                        source: path.to_path_buf(),
+                        overrides: Vec::new(),
+                    },
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the mod file at `path`: its removals first, then its entries.
+    pub fn add_overlay(&mut self, path: &Path, text: &str) -> Result<(), RawsError> {
+        let (mut sections, remove) = parse_file(path, text)?;
+        if let Some(remove) = remove {
+            self.remove(path, remove)?;
+        }
+        for (i, &section) in SECTIONS.iter().enumerate() {
+            let Some(Value::Array(values)) = sections.remove(section) else {
+                continue;
+            };
+            for value in values {
+                let name = entry_name(path, section, &value)?;
+                let entries = &mut self.sections[i];
+                let mut overrides = Vec::new();
+                let first = entries.iter().position(|e| e.index.name == name);
+                entries.retain(|e| {
+                    let keep = e.index.name != name;
+                    if !keep {
+                        overrides.push(e.index.source.clone());
+                    }
+                    keep
+                });
+                let entry = Entry {
+                    value,
+                    index: IndexEntry {
+                        section,
+                        name,
+                        source: path.to_path_buf(),
+                        overrides,
+                    },
+                };
+                match first {
+                    Some(position) => entries.insert(position, entry),
+                    None => entries.push(entry),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &Path, remove: Value) -> Result<(), RawsError> {
+        let removals: Map<String, Value> =
+            serde_json::from_value(remove).map_err(|e| RawsError::Parse(path.to_path_buf(), e))?;
+        for (section, names) in removals {
+            let Some(i) = SECTIONS.iter().position(|&s| s == section) else {
+                return Err(parse_error(
+                    path,
+                    format!("no raw section called {section}"),
+                ));
+            };
+            let names: Vec<String> = serde_json::from_value(names)
+                .map_err(|e| RawsError::Parse(path.to_path_buf(), e))?;
+            for name in names {
+                let entries = &mut self.sections[i];
+                let before = entries.len();
+                entries.retain(|e| e.index.name != name);
+                if entries.len() == before {
+                    return Err(RawsError::Overlay(
+                        path.to_path_buf(),
+                        format!("nothing called \"{name}\" in {section} to remove"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the raw files in `dir`, then applies the mods in `dir/mods`.
+    pub fn add_dir(&mut self, dir: &Path) -> Result<(), RawsError> {
+        for path in json_files(dir, true)? {
+            self.add_base(&path, &read(&path)?)?;
+        }
+        for mod_dir in mod_dirs(dir)? {
+            for path in json_files(&mod_dir, false)? {
+                self.add_overlay(&path, &read(&path)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The merged raws, and where each entry came from.
+    pub fn finish(self) -> (Raws, RawIndex) {
+        let mut merged = Map::new();
+        let mut index = RawIndex::default();
+        for (section, entries) in SECTIONS.iter().zip(self.sections) {
+            let mut values = Vec::new();
+            for entry in entries {
+                values.push(entry.value);
+                index.entries.push(entry.index);
+            }
+            merged.insert(section.to_string(), Value::Array(values));
+        }
+        let raws = serde_json::from_value(Value::Object(merged))
+            .expect("Entries that parsed in their own files should parse together");
+        (raws, index)
+    }
+}
+
+/// Reads and merges the raw files in `dir` and then the mods in `dir/mods`.
+pub fn read_raws_dir(dir: &Path) -> Result<(Raws, RawIndex), RawsError> {
+    let mut merger = RawMerger::default();
+    merger.add_dir(dir)?;
+    Ok(merger.finish())
+}
+
+fn read(path: &Path) -> Result<String, RawsError> {
+    fs::read_to_string(path).map_err(|e| RawsError::Io(path.to_path_buf(), e))
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, RawsError> {
+    let mut paths = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| RawsError::Io(dir.to_path_buf(), e))?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Every `.json` file under `dir`, in path order, leaving out the mods folder at
+/// the top when `skip_mods` is set.
+fn json_files(dir: &Path, skip_mods: bool) -> Result<Vec<PathBuf>, RawsError> {
+    let mut files = Vec::new();
+    for path in sorted_entries(dir)? {
+        if path.is_dir() {
+            if !(skip_mods && path.file_name().map_or(false, |name| name == MODS_DIR)) {
+                files.extend(json_files(&path, false)?);
+            }
+        } else if path.extension().map_or(false, |ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The mod folders to apply, in load order.
+fn mod_dirs(dir: &Path) -> Result<Vec<PathBuf>, RawsError> {
+    let mods = dir.join(MODS_DIR);
+    if !mods.is_dir() {
+        return Ok(Vec::new());
+    }
+    let load_order = mods.join(LOAD_ORDER_FILE);
+    if !load_order.exists() {
+        return Ok(sorted_entries(&mods)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .collect());
+    }
+    let names: Vec<String> = serde_json::from_str(&read(&load_order)?)
+        .map_err(|e| RawsError::Parse(load_order.clone(), e))?;
+    names
+        .iter()
+        .map(|name| {
+            let path = mods.join(name);
+            if path.is_dir() {
+                Ok(path)
+            } else {
+                Err(RawsError::Overlay(
+                    load_order.clone(),
+                    format!("no mod folder called {name}"),
+                ))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::raws::RawsError;
+    use crate::test_support::raw_files::{shipped_entry, shipped_raws};
+
+    #[test]
+    fn mods_replace_add_and_remove_entries() {
+        let mut rat = shipped_entry("mobs", "Rat");
+        rat["vision_range"] = 3.into();
+        let mut giant_rat = shipped_entry("mobs", "Rat");
+        giant_rat["name"] = "Giant Rat".into();
+        let overlay = json!({
+            "remove" : { "spawn_table" : [ "Goblin" ] },
+            "mobs" : [ rat, giant_rat ],
+        });
+
+        let mut merger = shipped_raws();
+        merger
+            .add_overlay(Path::new("test-mod.json"), &overlay.to_string())
+            .unwrap();
+        let (raws, index) = merger.finish();
+
+        let rats: Vec<_> = raws.mobs.iter().filter(|m| m.name == "Rat").collect();
+        assert_eq!(rats.len(), 1);
+        assert_eq!(rats[0].vision_range, 3);
+        assert!(raws.mobs.iter().any(|m| m.name == "Giant Rat"));
+        assert!(!raws.spawn_table.iter().any(|s| s.name == "Goblin"));
+        let rat_entry = index
+            .entries
+            .iter()
+            .find(|e| e.section == "mobs" && e.name == "Rat")
+            .unwrap();
+        assert_eq!(rat_entry.source, Path::new("test-mod.json"));
+        assert_eq!(rat_entry.overrides, vec![Path::new("raws/mobs.json")]);
+    }
+
+    #[test]
+    fn mods_removing_missing_entries_are_refused() {
+        let overlay = json!({ "remove" : { "mobs" : [ "Nobody" ] } });
+
+        let result = shipped_raws().add_overlay(Path::new("test-mod.json"), &overlay.to_string());
+
+        assert!(matches!(result, Err(RawsError::Overlay(..))));
+    }
+
+    #[test]
+    fn misspelled_effects_are_refused() {
+        let mut potion = shipped_entry("items", "Health Potion");
+        potion["consumable"]["effects"] = json!([{ "provides_healling" : 8 }]);
+
+        let result = shipped_raws().add_overlay(
+            Path::new("test-mod.json"),
+            &json!({ "items" : [ potion ] }).to_string(),
+        );
+
+        assert!(matches!(result, Err(RawsError::Parse(..))));
+    }
+}
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
-
 use serde::Deserialize;
 
+use crate::raws::effect_structs::Effect;
+
 #[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Spell {
     pub name: String,
-    pub effects: HashMap<String, String>,
+    pub effects: Vec<Effect>,
     pub mana_cost: i32,
 }
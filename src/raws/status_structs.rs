@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::components::{AttributeKind, DamageResponse, DamageType, StackingPolicy};
+
+/// A buff or debuff that items, spells, weapon traits and traps put on whatever
+/// they hit with a `status` effect.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Status {
+    pub name: String,
+    /// Shown beside the name on the HUD.
+    pub glyph: char,
+    /// A hex colour such as `#FF0000`.
+    pub color: String,
+    /// How many turns it lasts.
+    pub duration: i32,
+    #[serde(default)]
+    pub stacking: StackingPolicy,
+    /// The most stacks a `stack_intensity` status builds up to.
+    #[serde(default = "one_stack")]
+    pub max_stacks: i32,
+    /// What dispels and immunities call it, such as `"poison"` or `"magic"`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// What it does at the start of each of the player's turns.
+    #[serde(default)]
+    pub periodic: StatusPeriodic,
+    /// What it changes about its target while it lasts.
+    #[serde(default)]
+    pub modifiers: StatusModifiers,
+}
+
+fn one_stack() -> i32 {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StatusPeriodic {
+    #[serde(default)]
+    pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub healing: i32,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StatusModifiers {
+    /// Points added to the attributes, or taken away when negative.
+    #[serde(default)]
+    pub attributes: HashMap<AttributeKind, i32>,
+    /// Negative values hasten.
+    #[serde(default)]
+    pub initiative_penalty: f32,
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, DamageResponse>,
+    /// Loses its turns, as the confused do.
+    #[serde(default)]
+    pub skips_turn: bool,
+}
+
+impl Status {
+    /// A plain status for effects that don't name one from the raws.
+    pub fn plain(name: &str, duration: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            glyph: '*',
+            color: "#FF0000".to_string(),
+            duration,
+            stacking: StackingPolicy::default(),
+            max_stacks: 1,
+            tags: Vec::new(),
+            periodic: StatusPeriodic::default(),
+            modifiers: StatusModifiers::default(),
+        }
+    }
+}
@@ -0,0 +1,878 @@
+//! Checks parsed raws for mistakes that would otherwise only show up once the
+//! game tries to spawn something: names that don't refer to anything, slots and
+//! attributes the spawner doesn't know, and dice or colours that don't parse.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use rltk::RGB;
+
+use crate::components::{EquipmentSlot, StackingPolicy};
+use crate::dice::DiceRoll;
+use crate::raws::effect_structs::Effect;
+use crate::raws::item_structs::Renderable;
+use crate::raws::rawmaster::generated_item_names;
+use crate::raws::{Glyph, InitialBuilder, MetaBuilder, PrefabKind, Raws};
+
+/// One mistake in the raws, located by the entry and field it's in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawProblem {
+    /// The section the entry is in, such as `mobs` or `spawn_table`.
+    pub section: &'static str,
+    pub entry: String,
+    pub field: String,
+    pub message: String,
+    /// The file the entry came from, when it's known.
+    pub source: Option<PathBuf>,
+}
+
+impl fmt::Display for RawProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{}: ", source.display())?;
+        }
+        write!(
+            f,
+            "{} \"{}\", {}: {}",
+            self.section, self.entry, self.field, self.message
+        )
+    }
+}
+
+const ATTRIBUTES: &[&str] = &[
+    "Might",
+    "might",
+    "Fitness",
+    "fitness",
+    "Quickness",
+    "quickness",
+];
+const MAGIC_CLASSES: &[&str] = &["common", "rare", "legendary"];
+const SKILLS: &[&str] = &["Melee", "Defense", "Magic"];
+const MOVEMENTS: &[&str] = &["static", "random", "random_waypoint"];
+
+struct Validator<'a> {
+    raws: &'a Raws,
+    /// Includes the magic and traited items generated from templates.
+    items: HashSet<String>,
+    mobs: HashSet<&'a str>,
+    props: HashSet<&'a str>,
+    loot_tables: HashSet<&'a str>,
+    factions: HashSet<&'a str>,
+    spells: HashSet<&'a str>,
+    statuses: HashSet<&'a str>,
+    /// Every tag some status carries, for dispels and immunities to name.
+    status_tags: HashSet<&'a str>,
+    prefabs: HashMap<&'a str, PrefabKind>,
+    problems: Vec<RawProblem>,
+}
+
+/// Every problem found in `raws`, in the order the entries appear.
+pub fn validate(raws: &Raws) -> Vec<RawProblem> {
+    let mut validator = Validator {
+        raws,
+        items: raws
+            .items
+            .iter()
+            .map(|i| i.name.clone())
+            .chain(generated_item_names(raws))
+            .collect(),
+        mobs: raws.mobs.iter().map(|m| m.name.as_str()).collect(),
+        props: raws.props.iter().map(|p| p.name.as_str()).collect(),
+        loot_tables: raws.loot_tables.iter().map(|l| l.name.as_str()).collect(),
+        factions: raws.faction_table.iter().map(|f| f.name.as_str()).collect(),
+        spells: raws.spells.iter().map(|s| s.name.as_str()).collect(),
+        statuses: raws.statuses.iter().map(|s| s.name.as_str()).collect(),
+        status_tags: raws
+            .statuses
+            .iter()
+            .flat_map(|s| s.tags.iter().map(String::as_str))
+            .collect(),
+        prefabs: raws
+            .prefabs
+            .iter()
+            .map(|p| (p.name.as_str(), p.kind))
+            .collect(),
+        problems: Vec::new(),
+    };
+    validator.check_items();
+    validator.check_mobs();
+    validator.check_props();
+    validator.check_spawn_table();
+    validator.check_loot_tables();
+    validator.check_factions();
+    validator.check_spells();
+    validator.check_levels();
+    validator.check_prefabs();
+    validator.check_classes();
+    validator.check_perks();
+    validator.check_statuses();
+    validator.problems
+}
+
+impl<'a> Validator<'a> {
+    fn report(
+        &mut self,
+        section: &'static str,
+        entry: &str,
+        field: impl ToString,
+        message: impl ToString,
+    ) {
+        self.problems.push(RawProblem {
+            section,
+            entry: entry.to_string(),
+            field: field.to_string(),
+            message: message.to_string(),
+            source: None,
+        });
+    }
+
+    fn check_one_of(
+        &mut self,
+        section: &'static str,
+        entry: &str,
+        field: &str,
+        value: &str,
+        allowed: &[&str],
+    ) {
+        if !allowed.contains(&value) {
+            self.report(section, entry, field, format!("unknown value \"{value}\""));
+        }
+    }
+
+    fn check_dice(&mut self, section: &'static str, entry: &str, field: &str, dice: &str) {
+        if let Err(e) = dice.parse::<DiceRoll>() {
+            self.report(section, entry, field, format!("\"{dice}\": {e}"));
+        }
+    }
+
+    fn check_speed(&mut self, section: &'static str, entry: &str, field: &str, speed: Option<i32>) {
+        if speed.is_some_and(|speed| speed < 1) {
+            self.report(section, entry, field, "should be at least 1");
+        }
+    }
+
+    fn check_colour(&mut self, section: &'static str, entry: &str, field: &str, colour: &str) {
+        if RGB::from_hex(colour).is_err() {
+            self.report(
+                section,
+                entry,
+                field,
+                format!("\"{colour}\" isn't a hex colour"),
+            );
+        }
+    }
+
+    fn check_renderable(&mut self, section: &'static str, entry: &str, renderable: &Renderable) {
+        self.check_colour(section, entry, "renderable.fg", &renderable.fg);
+        self.check_colour(section, entry, "renderable.bg", &renderable.bg);
+        if renderable.glyph.chars().count() != 1 {
+            self.report(
+                section,
+                entry,
+                "renderable.glyph",
+                "should be a single character",
+            );
+        }
+    }
+
+    fn check_effects(
+        &mut self,
+        section: &'static str,
+        entry: &str,
+        field: &str,
+        effects: &[Effect],
+    ) {
+        for effect in effects {
+            match effect {
+                Effect::TeachSpell(spell) if !self.spells.contains(spell.as_str()) => {
+                    self.report(
+                        section,
+                        entry,
+                        format!("{field}.teach_spell"),
+                        format!("no spell named \"{spell}\""),
+                    );
+                }
+                Effect::Status(status) if !self.statuses.contains(status.as_str()) => {
+                    self.report(
+                        section,
+                        entry,
+                        format!("{field}.status"),
+                        format!("no status named \"{status}\""),
+                    );
+                }
+                Effect::Dispel(tag) if !self.status_tags.contains(tag.as_str()) => {
+                    self.report(
+                        section,
+                        entry,
+                        format!("{field}.dispel"),
+                        format!("no status is tagged \"{tag}\""),
+                    );
+                }
+                Effect::Particle(particle) | Effect::ParticleLine(particle) => {
+                    self.check_colour(
+                        section,
+                        entry,
+                        &format!("{field}.particle"),
+                        &particle.color,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_items(&mut self) {
+        let raws = self.raws;
+        for item in &raws.items {
+            let name = item.name.as_str();
+            if let Some(renderable) = &item.renderable {
+                self.check_renderable("items", name, renderable);
+            }
+            if let Some(consumable) = &item.consumable {
+                self.check_effects("items", name, "consumable.effects", &consumable.effects);
+            }
+            if let Some(weapon) = &item.weapon {
+                self.check_dice("items", name, "weapon.base_damage", &weapon.base_damage);
+                self.check_one_of(
+                    "items",
+                    name,
+                    "weapon.attribute",
+                    &weapon.attribute,
+                    ATTRIBUTES,
+                );
+                if weapon.range != "melee" && weapon.range.parse::<i32>().is_err() {
+                    self.report(
+                        "items",
+                        name,
+                        "weapon.range",
+                        "should be \"melee\" or a number",
+                    );
+                }
+                if let Some(effects) = &weapon.proc_effects {
+                    self.check_effects("items", name, "weapon.proc_effects", effects);
+                }
+                self.check_speed("items", name, "weapon.speed", weapon.speed);
+                if weapon
+                    .crit_range
+                    .is_some_and(|range| !(2..=20).contains(&range))
+                {
+                    self.report(
+                        "items",
+                        name,
+                        "weapon.crit_range",
+                        "should be between 2 and 20",
+                    );
+                }
+                if weapon
+                    .crit_multiplier
+                    .is_some_and(|multiplier| multiplier < 1)
+                {
+                    self.report(
+                        "items",
+                        name,
+                        "weapon.crit_multiplier",
+                        "should be at least 1",
+                    );
+                }
+                if weapon
+                    .armor_penetration
+                    .is_some_and(|penetration| penetration < 0)
+                {
+                    self.report(
+                        "items",
+                        name,
+                        "weapon.armor_penetration",
+                        "shouldn't be negative",
+                    );
+                }
+            }
+            if let Some(wearable) = &item.wearable {
+                if let Err(e) = wearable.slot.parse::<EquipmentSlot>() {
+                    self.report("items", name, "wearable.slot", e);
+                }
+                if wearable
+                    .damage_reduction
+                    .is_some_and(|reduction| reduction < 0)
+                {
+                    self.report(
+                        "items",
+                        name,
+                        "wearable.damage_reduction",
+                        "shouldn't be negative",
+                    );
+                }
+            }
+            if let Some(magic) = &item.magic {
+                self.check_one_of("items", name, "magic.class", &magic.class, MAGIC_CLASSES);
+            }
+            if item.template_magic.is_some() && item.weapon.is_none() && item.wearable.is_none() {
+                self.report(
+                    "items",
+                    name,
+                    "template_magic",
+                    "only weapons and armour can be templated",
+                );
+            }
+        }
+    }
+
+    fn check_mobs(&mut self) {
+        let raws = self.raws;
+        for mob in &raws.mobs {
+            let name = mob.name.as_str();
+            if let Some(renderable) = &mob.renderable {
+                self.check_renderable("mobs", name, renderable);
+            }
+            self.check_one_of("mobs", name, "movement", &mob.movement, MOVEMENTS);
+            self.check_speed("mobs", name, "speed", mob.speed);
+            for immunity in mob.status_immunities.iter().flatten() {
+                let immunity = immunity.as_str();
+                if !self.statuses.contains(immunity) && !self.status_tags.contains(immunity) {
+                    self.report(
+                        "mobs",
+                        name,
+                        "status_immunities",
+                        format!("no status is named or tagged \"{immunity}\""),
+                    );
+                }
+            }
+            for skill in mob.skills.iter().flat_map(HashMap::keys) {
+                self.check_one_of("mobs", name, "skills", skill, SKILLS);
+            }
+            for item in mob.equipped.iter().flatten() {
+                if !self.items.contains(item.as_str()) {
+                    self.report(
+                        "mobs",
+                        name,
+                        "equipped",
+                        format!("no item named \"{item}\""),
+                    );
+                }
+            }
+            for attack in mob.natural.iter().flat_map(|n| n.attacks.iter().flatten()) {
+                self.check_dice(
+                    "mobs",
+                    name,
+                    &format!("natural.attacks.{}.damage", attack.name),
+                    &attack.damage,
+                );
+            }
+            if let Some(loot_table) = &mob.loot_table {
+                if !self.loot_tables.contains(loot_table.as_str()) {
+                    self.report(
+                        "mobs",
+                        name,
+                        "loot_table",
+                        format!("no loot table named \"{loot_table}\""),
+                    );
+                }
+            }
+            if let Some(light) = &mob.light {
+                self.check_colour("mobs", name, "light.color", &light.color);
+            }
+            if let Some(faction) = &mob.faction {
+                if !self.factions.contains(faction.as_str()) {
+                    self.report(
+                        "mobs",
+                        name,
+                        "faction",
+                        format!("no faction named \"{faction}\" in the faction table"),
+                    );
+                }
+            }
+            if let Some(gold) = &mob.gold {
+                self.check_dice("mobs", name, "gold", gold);
+            }
+            let abilities = [("abilities", &mob.abilities), ("on_death", &mob.on_death)];
+            for (field, list) in abilities {
+                for ability in list.iter().flatten() {
+                    if !self.spells.contains(ability.spell.as_str()) {
+                        self.report(
+                            "mobs",
+                            name,
+                            field,
+                            format!("no spell named \"{}\"", ability.spell),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_props(&mut self) {
+        let raws = self.raws;
+        for prop in &raws.props {
+            let name = prop.name.as_str();
+            if let Some(renderable) = &prop.renderable {
+                self.check_renderable("props", name, renderable);
+            }
+            if let Some(trigger) = &prop.entry_trigger {
+                self.check_effects("props", name, "entry_trigger.effects", &trigger.effects);
+            }
+            if let Some(light) = &prop.light {
+                self.check_colour("props", name, "light.color", &light.color);
+            }
+        }
+    }
+
+    fn check_spawn_table(&mut self) {
+        let raws = self.raws;
+        for entry in &raws.spawn_table {
+            let name = entry.name.as_str();
+            let known =
+                self.items.contains(name) || self.mobs.contains(name) || self.props.contains(name);
+            if !known {
+                self.report(
+                    "spawn_table",
+                    name,
+                    "name",
+                    "no item, mob or prop has this name",
+                );
+            }
+            if entry.min_depth > entry.max_depth {
+                self.report("spawn_table", name, "min_depth", "is deeper than max_depth");
+            }
+        }
+    }
+
+    fn check_loot_tables(&mut self) {
+        let raws = self.raws;
+        for table in &raws.loot_tables {
+            for drop in &table.drops {
+                if !self.items.contains(drop.name.as_str()) {
+                    self.report(
+                        "loot_tables",
+                        &table.name,
+                        "drops",
+                        format!("no item named \"{}\"", drop.name),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_factions(&mut self) {
+        let raws = self.raws;
+        for faction in &raws.faction_table {
+            for (other, response) in &faction.responses {
+                if other != "Default" && !self.factions.contains(other.as_str()) {
+                    self.report(
+                        "faction_table",
+                        &faction.name,
+                        "responses",
+                        format!("no faction named \"{other}\""),
+                    );
+                }
+                let field = format!("responses.{other}");
+                self.check_one_of(
+                    "faction_table",
+                    &faction.name,
+                    &field,
+                    response,
+                    &["ignore", "flee", "attack"],
+                );
+            }
+        }
+    }
+
+    fn check_spells(&mut self) {
+        let raws = self.raws;
+        for spell in &raws.spells {
+            self.check_effects("spells", &spell.name, "effects", &spell.effects);
+        }
+        for weapon_trait in &raws.weapon_traits {
+            self.check_effects(
+                "weapon_traits",
+                &weapon_trait.name,
+                "effects",
+                &weapon_trait.effects,
+            );
+        }
+    }
+
+    fn check_levels(&mut self) {
+        let raws = self.raws;
+        for level in &raws.levels {
+            let name = level.name.as_str();
+            if level.min_depth > level.max_depth {
+                self.report("levels", name, "min_depth", "is deeper than max_depth");
+            }
+            if let InitialBuilder::Prefab(prefab) = &level.start {
+                self.check_prefab(name, "start.prefab", prefab, PrefabKind::Level);
+            }
+            for builder in &level.builders {
+                if let MetaBuilder::Sectional(section) = builder {
+                    self.check_prefab(name, "builders.sectional", section, PrefabKind::Section);
+                }
+            }
+        }
+    }
+
+    fn check_prefab(&mut self, level: &str, field: &str, prefab: &str, kind: PrefabKind) {
+        match self.prefabs.get(prefab) {
+            Some(&found) if found == kind => {}
+            Some(found) => self.report(
+                "levels",
+                level,
+                field,
+                format!("\"{prefab}\" is a {found:?} prefab, not a {kind:?}"),
+            ),
+            None => self.report(
+                "levels",
+                level,
+                field,
+                format!("no prefab named \"{prefab}\""),
+            ),
+        }
+    }
+
+    fn check_glyph(&mut self, prefab: &str, field: &str, glyph: &Glyph) {
+        if let Glyph::Spawn(spawn) = glyph {
+            let spawn = spawn.as_str();
+            let known = self.items.contains(spawn)
+                || self.mobs.contains(spawn)
+                || self.props.contains(spawn);
+            if !known {
+                self.report(
+                    "prefabs",
+                    prefab,
+                    field,
+                    format!("no item, mob or prop named \"{spawn}\""),
+                );
+            }
+        }
+    }
+
+    fn check_prefabs(&mut self) {
+        let raws = self.raws;
+        for prefab in &raws.prefabs {
+            let name = prefab.name.as_str();
+            if prefab.min_depth > prefab.max_depth {
+                self.report("prefabs", name, "min_depth", "is deeper than max_depth");
+            }
+            if prefab.weight < 1 {
+                self.report("prefabs", name, "weight", "should be at least 1");
+            }
+            if prefab.map.len() != prefab.height {
+                self.report(
+                    "prefabs",
+                    name,
+                    "map",
+                    format!("has {} rows, not {}", prefab.map.len(), prefab.height),
+                );
+            }
+            for (y, row) in prefab.map.iter().enumerate() {
+                let width = row.chars().count();
+                if width != prefab.width {
+                    self.report(
+                        "prefabs",
+                        name,
+                        "map",
+                        format!("row {y} is {width} wide, not {}", prefab.width),
+                    );
+                }
+            }
+            for (ch, glyph) in &prefab.legend {
+                self.check_glyph(name, &format!("legend.{ch}"), glyph);
+            }
+            let mut seen = HashSet::new();
+            for ch in prefab.map.iter().flat_map(|row| row.chars()) {
+                if !seen.insert(ch) || prefab.legend.contains_key(&ch) {
+                    continue;
+                }
+                match Glyph::default_for(ch) {
+                    Some(glyph) => self.check_glyph(name, "map", &glyph),
+                    None => self.report(
+                        "prefabs",
+                        name,
+                        "map",
+                        format!("'{ch}' isn't in the legend"),
+                    ),
+                }
+            }
+        }
+    }
+
+    fn check_classes(&mut self) {
+        let raws = self.raws;
+        if raws.classes.is_empty() {
+            self.report("classes", "", "", "the player needs at least one class");
+        }
+        for class in &raws.classes {
+            let name = class.name.as_str();
+            for skill in class.skills.keys() {
+                self.check_one_of("classes", name, "skills", skill, SKILLS);
+            }
+            for spell in &class.spells {
+                if !self.spells.contains(spell.as_str()) {
+                    self.report(
+                        "classes",
+                        name,
+                        "spells",
+                        format!("no spell named \"{spell}\""),
+                    );
+                }
+            }
+            for (field, items) in [("equipped", &class.equipped), ("carried", &class.carried)] {
+                for item in items {
+                    if !self.items.contains(item.as_str()) {
+                        self.report("classes", name, field, format!("no item named \"{item}\""));
+                    }
+                }
+            }
+            if !self.factions.contains(class.faction.as_str()) {
+                self.report(
+                    "classes",
+                    name,
+                    "faction",
+                    format!("no faction named \"{}\"", class.faction),
+                );
+            }
+        }
+    }
+
+    fn check_perks(&mut self) {
+        let raws = self.raws;
+        for perk in &raws.perks {
+            let name = perk.name.as_str();
+            if perk.min_level < 2 {
+                self.report("perks", name, "min_level", "is below 2, the first level up");
+            }
+            for spell in &perk.spells {
+                if !self.spells.contains(spell.as_str()) {
+                    self.report(
+                        "perks",
+                        name,
+                        "spells",
+                        format!("no spell named \"{spell}\""),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_statuses(&mut self) {
+        let raws = self.raws;
+        for status in &raws.statuses {
+            let name = status.name.as_str();
+            self.check_colour("statuses", name, "color", &status.color);
+            if status.duration < 1 {
+                self.report("statuses", name, "duration", "should be at least 1");
+            }
+            if status.max_stacks < 1 {
+                self.report("statuses", name, "max_stacks", "should be at least 1");
+            }
+            if status.max_stacks > 1 && status.stacking != StackingPolicy::StackIntensity {
+                self.report(
+                    "statuses",
+                    name,
+                    "max_stacks",
+                    "only stack_intensity statuses stack",
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::{json, Value};
+
+    use super::{validate, RawProblem};
+    use crate::raws::rawmaster::RawMaster;
+    use crate::raws::InitialBuilder;
+    use crate::test_support::raw_files::{shipped_entry, shipped_raws};
+
+    /// The problems in the shipped raws once `overlay` is applied to them as a mod.
+    fn problems_with(overlay: Value) -> Vec<RawProblem> {
+        let mut merger = shipped_raws();
+        merger
+            .add_overlay(Path::new("test-mod.json"), &overlay.to_string())
+            .unwrap();
+        validate(&merger.finish().0)
+    }
+
+    #[test]
+    fn the_shipped_raws_are_valid() {
+        let problems = problems_with(json!({}));
+
+        assert!(problems.is_empty(), "{problems:#?}");
+    }
+
+    #[test]
+    fn broken_references_are_reported_by_entry_and_field() {
+        let mut orc = shipped_entry("mobs", "Orc");
+        orc["faction"] = "Nobody".into();
+        let mut shield = shipped_entry("items", "Shield");
+        shield["wearable"]["slot"] = "Elbow".into();
+        let gobbo = json!({ "name" : "Gobbo", "weight" : 1, "min_depth" : 1, "max_depth" : 2 });
+
+        let found: Vec<String> = problems_with(json!({
+            "items" : [ shield ],
+            "mobs" : [ orc ],
+            "spawn_table" : [ gobbo ],
+        }))
+        .iter()
+        .map(|p| format!("{} {} {}", p.section, p.entry, p.field))
+        .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                "items Shield wearable.slot",
+                "mobs Orc faction",
+                "spawn_table Gobbo name",
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_dice_and_colours_are_reported() {
+        let mut longsword = shipped_entry("items", "Longsword");
+        longsword["weapon"]["base_damage"] = "1d".into();
+        let mut rat = shipped_entry("mobs", "Rat");
+        rat["renderable"]["fg"] = "#GG0000".into();
+
+        let problems = problems_with(json!({ "items" : [ longsword ], "mobs" : [ rat ] }));
+
+        assert_eq!(problems.len(), 2, "{problems:#?}");
+        assert_eq!(problems[0].field, "weapon.base_damage");
+        assert_eq!(problems[1].field, "renderable.fg");
+    }
+
+    #[test]
+    fn levels_are_validated_and_can_be_replaced_by_mods() {
+        let mut woods = shipped_entry("levels", "Into the Woods");
+        woods["start"] = "maze".into();
+        woods["builders"] = json!([{ "sectional" : "orc_fort" }]);
+
+        let problems = problems_with(json!({ "levels" : [ woods.clone() ] }));
+        assert_eq!(problems.len(), 1, "{problems:#?}");
+        assert_eq!(problems[0].field, "builders.sectional");
+
+        woods["builders"] = json!([]);
+        let mut merger = shipped_raws();
+        merger
+            .add_overlay(
+                Path::new("test-mod.json"),
+                &json!({ "levels" : [ woods ] }).to_string(),
+            )
+            .unwrap();
+        let mut master = RawMaster::default();
+        let (raws, index) = merger.finish();
+        master.load(raws, index);
+
+        let level = master.level_for_depth(2).unwrap();
+        assert_eq!(level.name, "Into the Woods");
+        assert!(matches!(level.start, InitialBuilder::Maze));
+    }
+
+    #[test]
+    fn prefabs_are_checked_against_their_size_and_legend() {
+        let mut camp = shipped_entry("prefabs", "orc_camp");
+        camp["legend"]["O"] = json!({ "spawn" : "Orc Chieftain" });
+        camp["map"][3] = " ≈ g     ≈ ".into();
+        camp["map"][4] = " ≈   ?    ≈ ".into();
+
+        let problems = problems_with(json!({ "prefabs" : [ camp ] }));
+
+        let found: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(found.len(), 3, "{found:#?}");
+        assert!(found[0].contains("row 3 is 11 wide, not 12"), "{found:#?}");
+        assert!(found[1].contains("legend.O"), "{found:#?}");
+        assert!(found[2].contains("'?' isn't in the legend"), "{found:#?}");
+    }
+
+    #[test]
+    fn classes_are_checked_against_the_raws() {
+        let mut warrior = shipped_entry("classes", "Warrior");
+        warrior["equipped"] = json!(["Rusty Longsword", "Cardboard Shield"]);
+        warrior["skills"] = json!({ "Juggling" : 3 });
+
+        let problems = problems_with(json!({ "classes" : [ warrior ] }));
+
+        let found: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "classes \"Warrior\", skills: unknown value \"Juggling\"",
+                "classes \"Warrior\", equipped: no item named \"Cardboard Shield\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn perks_are_checked_against_the_raws() {
+        let mut hedge_magic = shipped_entry("perks", "Hedge Magic");
+        hedge_magic["min_level"] = json!(1);
+        hedge_magic["spells"] = json!(["Web", "Meteor Swarm"]);
+
+        let problems = problems_with(json!({ "perks" : [ hedge_magic ] }));
+
+        let found: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "perks \"Hedge Magic\", min_level: is below 2, the first level up",
+                "perks \"Hedge Magic\", spells: no spell named \"Meteor Swarm\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn statuses_are_checked_where_they_are_used() {
+        let mut poisoned = shipped_entry("statuses", "Poisoned");
+        poisoned["color"] = "green".into();
+        poisoned["stacking"] = "refresh".into();
+        let mut antidote = shipped_entry("items", "Antidote");
+        antidote["consumable"]["effects"] =
+            json!([{ "dispel" : "rust" }, { "status" : "Petrified" }]);
+        let mut golem = shipped_entry("mobs", "Rock Golem");
+        golem["status_immunities"] = json!(["poison", "sleep"]);
+
+        let problems = problems_with(json!({
+            "items" : [ antidote ],
+            "mobs" : [ golem ],
+            "statuses" : [ poisoned ],
+        }));
+
+        let found: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "items \"Antidote\", consumable.effects.dispel: no status is tagged \"rust\"",
+                "items \"Antidote\", consumable.effects.status: no status named \"Petrified\"",
+                "mobs \"Rock Golem\", status_immunities: no status is named or tagged \"sleep\"",
+                "statuses \"Poisoned\", color: \"green\" isn't a hex colour",
+                "statuses \"Poisoned\", max_stacks: only stack_intensity statuses stack",
+            ]
+        );
+    }
+
+    #[test]
+    fn crit_ranges_and_armour_numbers_are_checked() {
+        let mut dagger = shipped_entry("items", "Dagger");
+        dagger["weapon"]["crit_range"] = json!(1);
+        dagger["weapon"]["crit_multiplier"] = json!(0);
+        dagger["weapon"]["armor_penetration"] = json!(-1);
+        let mut breastplate = shipped_entry("items", "Breastplate");
+        breastplate["wearable"]["damage_reduction"] = json!(-2);
+
+        let problems = problems_with(json!({ "items" : [ dagger, breastplate ] }));
+
+        let found: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "items \"Breastplate\", wearable.damage_reduction: shouldn't be negative",
+                "items \"Dagger\", weapon.crit_range: should be between 2 and 20",
+                "items \"Dagger\", weapon.crit_multiplier: should be at least 1",
+                "items \"Dagger\", weapon.armor_penetration: shouldn't be negative",
+            ]
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+
+use crate::raws::effect_structs::Effect;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct WeaponTrait {
     pub name: String,
-    pub effects: HashMap<String, String>,
+    pub effects: Vec<Effect>,
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use serde::{Deserialize, Serialize};
 
@@ -29,8 +29,8 @@ impl Rect {
         ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2)
     }
 
-    pub fn get_all_tiles(&self) -> HashSet<(i32, i32)> {
-        let mut result = HashSet::new();
+    pub fn get_all_tiles(&self) -> BTreeSet<(i32, i32)> {
+        let mut result = BTreeSet::new();
         for y in self.y1..self.y2 {
             for x in self.x1..self.x2 {
                 result.insert((x, y));
@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use specs::{Entity, Join, World, WorldExt};
+
+use crate::components::{LevelUpChoice, ParticleLifetime, Pools, Position};
+use crate::gui::CheatMenuResult;
+use crate::map::Map;
+use crate::player::{PlayerCommand, VendorMode};
+use crate::saveload_system::data_dir;
+use crate::spawner::NewCharacter;
+
+const REPLAY_FILE: &str = "replay.json";
+
+/// A choice the player made on a screen that waits for input. Entities are
+/// stored by id: a replay rebuilds the world from the same seed, so ids line up.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum Decision {
+    Command(PlayerCommand),
+    Cancel,
+    SelectItem { item: u32 },
+    Target { x: i32, y: i32 },
+    Cheat(CheatMenuResult),
+    VendorMode(VendorMode),
+    Sell { item: u32 },
+    Buy { tag: String, price: f32 },
+    LevelUp(LevelUpChoice),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    /// Who the run was started as.
+    pub character: NewCharacter,
+    pub decisions: Vec<Decision>,
+    /// Checksum of the world the run ended in, filled in when the replay is saved.
+    pub checksum: u64,
+}
+
+impl Replay {
+    pub const fn new(seed: u64, character: NewCharacter) -> Self {
+        Self {
+            seed,
+            character,
+            decisions: Vec::new(),
+            checksum: 0,
+        }
+    }
+}
+
+/// Where the last run's recording is kept, next to the saves.
+pub fn replay_path() -> PathBuf {
+    data_dir().join(REPLAY_FILE)
+}
+
+pub fn save_replay(replay: &Replay, path: &Path) {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("Unable to create the replay directory");
+    }
+    let data = serde_json::to_string(replay).expect("Unable to serialize replay");
+    fs::write(path, data).expect("Unable to write replay");
+}
+
+pub fn load_replay(path: &Path) -> Option<Replay> {
+    if !path.exists() {
+        return None;
+    }
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Reads the replay file given with `--replay [FILE]`, if any.
+pub fn replay_from_args(args: &[String]) -> Option<Replay> {
+    let flag = args.iter().position(|arg| arg == "--replay")?;
+    let path = args
+        .get(flag + 1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or_else(replay_path, PathBuf::from);
+    load_replay(&path)
+}
+
+/// Feeds the recorded decisions back in order.
+pub struct Playback {
+    decisions: VecDeque<Decision>,
+    checksum: u64,
+    verified: Option<bool>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            decisions: replay.decisions.into(),
+            checksum: replay.checksum,
+            verified: None,
+        }
+    }
+
+    pub fn next_decision(&mut self) -> Option<Decision> {
+        self.decisions.pop_front()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.decisions.is_empty()
+    }
+
+    /// Compares the world against the recorded checksum and remembers the outcome.
+    pub fn verify(&mut self, ecs: &World) -> bool {
+        let verified = checksum(ecs) == self.checksum;
+        self.verified = Some(verified);
+        verified
+    }
+
+    pub const fn verified(&self) -> Option<bool> {
+        self.verified
+    }
+}
+
+/// FNV-1a, so checksums don't change between builds the way `DefaultHasher` may.
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Hashes every `Pools`, every `Position` and the current `Map`. Two runs that
+/// ended with the same checksum ended in the same place.
+pub fn checksum(ecs: &World) -> u64 {
+    let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+
+    let entities = ecs.entities();
+    let pools = ecs.read_storage::<Pools>();
+    for (entity, pools) in (&entities, &pools).join() {
+        entity.id().hash(&mut hasher);
+        pools.hit_points.current.hash(&mut hasher);
+        pools.hit_points.max.hash(&mut hasher);
+        pools.mana.current.hash(&mut hasher);
+        pools.mana.max.hash(&mut hasher);
+        pools.xp.hash(&mut hasher);
+        pools.level.hash(&mut hasher);
+        pools.total_weight.to_bits().hash(&mut hasher);
+        pools.total_initiative_penalty.to_bits().hash(&mut hasher);
+        pools.gold.to_bits().hash(&mut hasher);
+        pools.god_mode.hash(&mut hasher);
+    }
+
+    // Particles move with the frame clock, so they are left out
+    let positions = ecs.read_storage::<Position>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    for (entity, pos, _) in (&entities, &positions, !&particles).join() {
+        entity.id().hash(&mut hasher);
+        pos.x.hash(&mut hasher);
+        pos.y.hash(&mut hasher);
+    }
+
+    let map = ecs.fetch::<Map>();
+    map.depth.hash(&mut hasher);
+    map.width.hash(&mut hasher);
+    map.height.hash(&mut hasher);
+    map.name.hash(&mut hasher);
+    map.tiles.hash(&mut hasher);
+    map.revealed_tiles.hash(&mut hasher);
+    let mut bloodstains: Vec<&usize> = map.bloodstains.iter().collect();
+    bloodstains.sort();
+    bloodstains.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Looks up an entity recorded by id in a replay.
+pub fn entity_by_id(ecs: &World, id: u32) -> Entity {
+    ecs.entities().entity(id)
+}
@@ -1,8 +1,6 @@
 use rltk::XpFile;
 
 rltk::embedded_resource!(SMALL_DUNGEON, "../resources/SmallDungeon_80x50.xp");
-rltk::embedded_resource!(WFC_DEMO_IMAGE1, "../resources/wfc-demo1.xp");
-rltk::embedded_resource!(WFC_POPULATED, "../resources/wfc-populated.xp");
 
 pub struct RexAssets {
     pub menu: XpFile,
@@ -11,8 +9,6 @@ pub struct RexAssets {
 impl RexAssets {
     pub fn new() -> Self {
         rltk::link_resource!(SMALL_DUNGEON, "../resources/SmallDungeon_80x50.xp");
-        rltk::link_resource!(WFC_DEMO_IMAGE1, "../resources/wfc-demo1.xp");
-        rltk::link_resource!(WFC_POPULATED, "../resources/wfc-populated.xp");
 
         Self {
             menu: XpFile::from_resource("../resources/SmallDungeon_80x50.xp").unwrap(),
@@ -1,19 +1,18 @@
-use lazy_static::lazy_static;
 use rltk::RandomNumberGenerator;
-use std::sync::Mutex;
-lazy_static! {
-    static ref RNG: Mutex<RandomNumberGenerator> = Mutex::new(RandomNumberGenerator::new());
-}
+use serde::{Deserialize, Serialize};
 
-#[allow(dead_code)]
-pub fn reseed(seed: u64) {
-    *RNG.lock().unwrap() = RandomNumberGenerator::seeded(seed);
-}
+/// The seed the current run was started with. Stored as a resource so the HUD,
+/// the game over screen and the save file can all get at it. The generator the
+/// run draws from lives next to it as a `RandomNumberGenerator` resource.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RunSeed(pub u64);
 
-pub fn roll_dice(n: i32, die_type: i32) -> i32 {
-    RNG.lock().unwrap().roll_dice(n, die_type)
-}
+/// Player turns taken since the run started. The "Turn" event starts over on
+/// every level; this never goes back, so a loaded run reseeds from it.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct TurnsPlayed(pub i32);
 
-pub fn range(min: i32, max: i32) -> i32 {
-    RNG.lock().unwrap().range(min, max)
+/// Picks a fresh seed from the system entropy source.
+pub fn new_seed() -> u64 {
+    RandomNumberGenerator::new().next_u64()
 }
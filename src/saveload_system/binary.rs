@@ -0,0 +1,194 @@
+//! The compact encoding for the component storages: the same values the JSON
+//! encoding writes, as tagged bytes with variable length integers instead of text.
+
+use serde_json::{Map, Number, Value};
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+const NEGATIVE: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const ARRAY: u8 = 7;
+const OBJECT: u8 = 8;
+
+/// Writes the number of values and then each value.
+pub fn encode(values: &[Value]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, values.len() as u64);
+    for value in values {
+        write_value(&mut out, value);
+    }
+    out
+}
+
+/// Reads back what `encode` wrote, refusing anything left over afterwards.
+pub fn decode(data: &[u8]) -> Result<Vec<Value>, String> {
+    let mut reader = Reader { data, pos: 0 };
+    let count = reader.len()?;
+    let values = (0..count)
+        .map(|_| reader.value())
+        .collect::<Result<Vec<_>, _>>()?;
+    if reader.pos != data.len() {
+        return Err(format!(
+            "{} bytes after the last storage",
+            data.len() - reader.pos
+        ));
+    }
+    Ok(values)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(NULL),
+        Value::Bool(false) => out.push(FALSE),
+        Value::Bool(true) => out.push(TRUE),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                out.push(UNSIGNED);
+                write_varint(out, u);
+            } else if let Some(i) = n.as_i64() {
+                // Negative, so its complement is a small positive number
+                out.push(NEGATIVE);
+                write_varint(out, !i as u64);
+            } else {
+                out.push(FLOAT);
+                out.extend_from_slice(&n.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Value::String(s) => {
+            out.push(STRING);
+            write_str(out, s);
+        }
+        Value::Array(items) => {
+            out.push(ARRAY);
+            write_varint(out, items.len() as u64);
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        Value::Object(fields) => {
+            out.push(OBJECT);
+            write_varint(out, fields.len() as u64);
+            for (key, item) in fields {
+                write_str(out, key);
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(count)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| "the file ends part way through a value".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(format!(
+            "an integer longer than 64 bits at byte {}",
+            self.pos
+        ))
+    }
+
+    /// A count of items, which can't be more than the bytes left to hold them.
+    fn len(&mut self) -> Result<usize, String> {
+        let len = self.varint()?;
+        if len > (self.data.len() - self.pos) as u64 {
+            return Err(format!("a length of {len} at byte {}", self.pos));
+        }
+        Ok(len as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string())
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        let tag = self.bytes(1)?[0];
+        Ok(match tag {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => Value::from(self.varint()?),
+            NEGATIVE => Value::from(!(self.varint()? as i64)),
+            FLOAT => {
+                let bytes = self.bytes(8)?.try_into().unwrap();
+                Number::from_f64(f64::from_le_bytes(bytes)).map_or(Value::Null, Value::Number)
+            }
+            STRING => Value::String(self.string()?),
+            ARRAY => {
+                let len = self.len()?;
+                Value::Array((0..len).map(|_| self.value()).collect::<Result<_, _>>()?)
+            }
+            OBJECT => {
+                let len = self.len()?;
+                let mut fields = Map::new();
+                for _ in 0..len {
+                    let key = self.string()?;
+                    fields.insert(key, self.value()?);
+                }
+                Value::Object(fields)
+            }
+            _ => return Err(format!("unknown tag {tag} at byte {}", self.pos - 1)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::saveload_system::{read_save, write_save, SaveEncoding, SaveError};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn damaged_binary_saves_are_refused() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let mut save = Vec::new();
+        write_save(&mut scenario.ecs, &mut save, SaveEncoding::Binary);
+
+        let truncated = read_save(&mut scenario.ecs, &save[..save.len() - 10]);
+
+        assert!(matches!(truncated, Err(SaveError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,402 @@
+//! Upgrades the component storages of saves written by older versions. A save
+//! holds one JSON value per registered component storage, in registry order.
+
+use serde_json::{Map, Value};
+
+use super::SaveError;
+use crate::dice::DiceRoll;
+
+type Migration = fn(&mut Vec<Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a save from format `n` to format `n + 1`. Any change
+/// to a saved component, or to the registry's order, needs a new entry here.
+const MIGRATIONS: &[Migration] = &[
+    add_equipped_storage,
+    compact_maps,
+    dice_expressions,
+    add_character_history_storage,
+    add_resistances_storage,
+    add_status_storages,
+];
+
+/// The format `write_save` produces.
+pub const SAVE_FORMAT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+// Where the storages the migrations touch sit in the registry. Storages are only
+// ever appended, so these hold for every format from the one that added them on.
+const INFLICTS_DAMAGE_STORAGE: usize = 10;
+const EQUIPPED_STORAGE: usize = 20;
+const NATURAL_ATTACK_DEFENSE_STORAGE: usize = 39;
+const WEAPON_STORAGE: usize = 79;
+const CHARACTER_HISTORY_STORAGE: usize = 82;
+const RESISTANCES_STORAGE: usize = 83;
+/// `StatusInfo`, the first of the five status storages.
+const STATUS_STORAGES: usize = 84;
+const STATUS_STORAGE_COUNT: usize = 5;
+
+pub fn migrate(from_version: u32, storages: &mut Vec<Value>) -> Result<(), SaveError> {
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        migration(storages).map_err(|reason| {
+            SaveError::Corrupt(format!("unable to upgrade from format {version}: {reason}"))
+        })?;
+    }
+    Ok(())
+}
+
+/// Format 0 predates `Equipped` being saved, so those saves lost what everyone
+/// was wearing. Its storage goes in straight after `Equippable`'s, empty.
+fn add_equipped_storage(storages: &mut Vec<Value>) -> Result<(), String> {
+    if storages.len() < EQUIPPED_STORAGE {
+        return Err(format!("only {} component storages", storages.len()));
+    }
+    storages.insert(EQUIPPED_STORAGE, Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// Format 1 saved every tile of every map one by one, along with the light and
+/// visibility the systems work out each turn anyway. Maps are found wherever
+/// they're nested, since the dungeon keeps its own copy of each level.
+fn compact_maps(storages: &mut Vec<Value>) -> Result<(), String> {
+    for storage in storages.iter_mut() {
+        compact_nested_maps(storage);
+    }
+    Ok(())
+}
+
+fn compact_nested_maps(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            if fields.contains_key("tiles") && fields.contains_key("revealed_tiles") {
+                for key in ["tiles", "revealed_tiles"] {
+                    if let Some(Value::Array(tiles)) = fields.get_mut(key) {
+                        *tiles = run_lengths(tiles);
+                    }
+                }
+                fields.remove("visible_tiles");
+                fields.remove("light");
+            } else {
+                fields.values_mut().for_each(compact_nested_maps);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(compact_nested_maps),
+        _ => {}
+    }
+}
+
+fn run_lengths(tiles: &[Value]) -> Vec<Value> {
+    let mut runs: Vec<(&Value, u64)> = Vec::new();
+    for tile in tiles {
+        match runs.last_mut() {
+            Some((last, count)) if *last == tile => *count += 1,
+            _ => runs.push((tile, 1)),
+        }
+    }
+    runs.into_iter()
+        .map(|(tile, count)| Value::Array(vec![tile.clone(), count.into()]))
+        .collect()
+}
+
+/// Format 2 saved weapon and natural attack damage as a dice count, die size and
+/// bonus, and the damage scrolls, spells and traps do as a plain number. Both are
+/// dice expressions now.
+fn dice_expressions(storages: &mut Vec<Value>) -> Result<(), String> {
+    if storages.len() <= WEAPON_STORAGE {
+        return Err(format!("only {} component storages", storages.len()));
+    }
+    for_each_object(&mut storages[INFLICTS_DAMAGE_STORAGE], &mut |fields| {
+        if let Some(Value::Number(damage)) = fields.get("damage") {
+            let damage = damage.to_string();
+            fields.insert("damage".to_string(), Value::String(damage));
+        }
+    });
+    for storage in [NATURAL_ATTACK_DEFENSE_STORAGE, WEAPON_STORAGE] {
+        for_each_object(&mut storages[storage], &mut |fields| {
+            let parts = ["damage_n_dice", "damage_die_type", "damage_bonus"]
+                .map(|key| fields.remove(key).and_then(|value| value.as_i64()));
+            if let [Some(n_dice), Some(die_type), Some(bonus)] = parts {
+                let damage = DiceRoll::dice(n_dice as i32, die_type as i32).plus(bonus as i32);
+                fields.insert("damage".to_string(), Value::String(damage.to_string()));
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Format 3 predates the player's level ups being kept. `CharacterHistory` is
+/// the last storage, so it goes on the end, empty.
+fn add_character_history_storage(storages: &mut Vec<Value>) -> Result<(), String> {
+    append_empty_storages(storages, CHARACTER_HISTORY_STORAGE, 1)
+}
+
+/// Format 4 predates damage types. `Resistances` is the last storage, so it
+/// goes on the end, empty; the new `damage_type` fields default to untyped.
+fn add_resistances_storage(storages: &mut Vec<Value>) -> Result<(), String> {
+    append_empty_storages(storages, RESISTANCES_STORAGE, 1)
+}
+
+/// Format 5 predates statuses from the raws. Their five storages go on the end,
+/// empty; statuses already running carry on without stacking or tags.
+fn add_status_storages(storages: &mut Vec<Value>) -> Result<(), String> {
+    append_empty_storages(storages, STATUS_STORAGES, STATUS_STORAGE_COUNT)
+}
+
+/// Appends `count` empty storages to a save that should hold the `expected`
+/// storages of the format being upgraded, refusing one that doesn't.
+fn append_empty_storages(
+    storages: &mut Vec<Value>,
+    expected: usize,
+    count: usize,
+) -> Result<(), String> {
+    if storages.len() != expected {
+        return Err(format!(
+            "expected {expected} component storages, found {}",
+            storages.len()
+        ));
+    }
+    storages.extend((0..count).map(|_| Value::Array(Vec::new())));
+    Ok(())
+}
+
+fn for_each_object(value: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    match value {
+        Value::Object(fields) => {
+            f(fields);
+            fields
+                .values_mut()
+                .for_each(|value| for_each_object(value, f));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| for_each_object(item, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use specs::{Join, WorldExt};
+
+    use super::{
+        CHARACTER_HISTORY_STORAGE, EQUIPPED_STORAGE, INFLICTS_DAMAGE_STORAGE,
+        NATURAL_ATTACK_DEFENSE_STORAGE, RESISTANCES_STORAGE, STATUS_STORAGES,
+        STATUS_STORAGE_COUNT, WEAPON_STORAGE,
+    };
+    use crate::component_registry::COMPONENT_NAMES;
+    use crate::components::{CharacterHistory, Name, Resistances, StatusEffect, StatusInfo, Weapon};
+    use crate::dice::{DiceRoll, Term};
+    use crate::map::Map;
+    use crate::rng::RunSeed;
+    use crate::saveload_system::{read_save, write_save, SaveEncoding, SaveError};
+    use crate::test_support::saves::{join, saved_scenario};
+    use crate::test_support::Scenario;
+
+    /// Writes every map in `value` the way format 1 did: a value per tile, with the
+    /// light and visibility saved alongside.
+    fn expand_maps(value: &mut Value) {
+        match value {
+            Value::Object(fields) if fields.contains_key("revealed_tiles") => {
+                for key in ["tiles", "revealed_tiles"] {
+                    let runs = fields[key].as_array().unwrap().clone();
+                    fields[key] = runs
+                        .iter()
+                        .flat_map(|run| {
+                            std::iter::repeat(run[0].clone()).take(run[1].as_u64().unwrap() as usize)
+                        })
+                        .collect();
+                }
+                let tile_count = fields["tiles"].as_array().unwrap().len();
+                fields.insert("visible_tiles".to_string(), json!(vec![false; tile_count]));
+                let black = json!({ "r": 0.0, "g": 0.0, "b": 0.0 });
+                fields.insert("light".to_string(), json!(vec![black; tile_count]));
+            }
+            Value::Object(fields) => fields.values_mut().for_each(expand_maps),
+            Value::Array(items) => items.iter_mut().for_each(expand_maps),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn unversioned_saves_are_migrated() {
+        let (mut scenario, mut values) = saved_scenario();
+        // What the game wrote before saves had a header or an `Equipped` storage
+        values.remove(0);
+        values.remove(20);
+        values.iter_mut().for_each(expand_maps);
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+    }
+
+    /// Made by hand in the shape the game wrote saves in before they were versioned
+    /// or runs were seeded: no header, every tile saved one by one and no seed. It
+    /// holds a player with a Hangover status on a 5x3 map.
+    const FORMAT_0_SAVE: &[u8] = include_bytes!("fixtures/format_0_save.json");
+
+    #[test]
+    fn saves_from_before_seeds_load() {
+        let (mut scenario, _) = saved_scenario();
+
+        read_save(&mut scenario.ecs, FORMAT_0_SAVE).unwrap();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert_eq!(scenario.ecs.fetch::<RunSeed>().0, 0);
+        assert_eq!(scenario.event_count("Turn"), 3);
+        assert!(scenario.log_contains("Rusty Roguelike"));
+        let statuses = scenario.ecs.read_storage::<StatusEffect>();
+        assert!(statuses.join().any(|status| status.target == player));
+    }
+
+    #[test]
+    fn format_1_maps_are_compacted() {
+        let (mut scenario, mut values) = saved_scenario();
+        let tiles = scenario.ecs.fetch::<Map>().tiles.clone();
+        values[0]["format_version"] = 1.into();
+        values.iter_mut().skip(1).for_each(expand_maps);
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        assert!(scenario.ecs.fetch::<Map>().tiles == tiles);
+    }
+
+    /// Writes weapon damage the way format 2 did, as a dice count, die size and bonus.
+    fn split_damage_dice(value: &mut Value) {
+        match value {
+            Value::Object(fields) if fields.contains_key("damage") => {
+                let roll: DiceRoll = serde_json::from_value(fields["damage"].clone()).unwrap();
+                let Term::Dice { count, sides, .. } = roll.terms[0].1 else {
+                    panic!("{roll} doesn't start with dice");
+                };
+                fields.remove("damage");
+                fields.insert("damage_n_dice".to_string(), count.into());
+                fields.insert("damage_die_type".to_string(), sides.into());
+                fields.insert("damage_bonus".to_string(), (roll.range().0 - count).into());
+            }
+            Value::Object(fields) => fields.values_mut().for_each(split_damage_dice),
+            Value::Array(items) => items.iter_mut().for_each(split_damage_dice),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn format_2_weapon_damage_becomes_dice() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        scenario.equip("Rusty Longsword", player);
+        let mut save = Vec::new();
+        write_save(&mut scenario.ecs, &mut save, SaveEncoding::Json);
+        let mut values = serde_json::Deserializer::from_slice(&save)
+            .into_iter::<Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let weapons = 1 + COMPONENT_NAMES
+            .iter()
+            .position(|&name| name == "Weapon")
+            .unwrap();
+        values[0]["format_version"] = 2.into();
+        split_damage_dice(&mut values[weapons]);
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        let names = scenario.ecs.read_storage::<Name>();
+        let weapons = scenario.ecs.read_storage::<Weapon>();
+        let damage = (&names, &weapons)
+            .join()
+            .find(|(name, _)| name.name == "Rusty Longsword")
+            .map(|(_, weapon)| weapon.damage.to_string());
+        assert_eq!(damage.as_deref(), Some("1d8-1"));
+    }
+
+    /// A save of the scenario as it was written before `first_new_storage` was
+    /// registered, without that storage or any after it.
+    fn saved_before(format_version: u32, first_new_storage: &str) -> (Scenario, Vec<Value>) {
+        let (scenario, mut values) = saved_scenario();
+        let storages = COMPONENT_NAMES
+            .iter()
+            .position(|&name| name == first_new_storage)
+            .unwrap();
+        values.truncate(1 + storages);
+        values[0]["format_version"] = format_version.into();
+        (scenario, values)
+    }
+
+    #[test]
+    fn format_3_saves_gain_a_character_history_storage() {
+        let (mut scenario, values) = saved_before(3, "CharacterHistory");
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert!(scenario
+            .ecs
+            .read_storage::<CharacterHistory>()
+            .get(player)
+            .is_none());
+    }
+
+    #[test]
+    fn format_4_saves_gain_a_resistances_storage() {
+        let (mut scenario, values) = saved_before(4, "Resistances");
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert_eq!(scenario.ecs.read_storage::<Resistances>().join().count(), 0);
+    }
+
+    #[test]
+    fn format_5_saves_gain_the_status_storages() {
+        let (mut scenario, values) = saved_before(5, "StatusInfo");
+
+        read_save(&mut scenario.ecs, &join(&values)).unwrap();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert_eq!(scenario.ecs.read_storage::<StatusInfo>().join().count(), 0);
+    }
+
+    #[test]
+    fn saves_with_the_wrong_storages_for_their_format_are_refused() {
+        let (mut scenario, mut values) = saved_before(5, "StatusInfo");
+        values.pop();
+
+        let result = read_save(&mut scenario.ecs, &join(&values));
+
+        let Err(SaveError::Corrupt(reason)) = result else {
+            panic!("a save missing a storage loaded");
+        };
+        assert!(reason.contains("format 5"), "{reason}");
+    }
+
+    #[test]
+    fn migrations_know_where_the_registry_keeps_each_storage() {
+        let position = |name| {
+            COMPONENT_NAMES
+                .iter()
+                .position(|&registered| registered == name)
+                .unwrap()
+        };
+
+        assert_eq!(position("InflictsDamage"), INFLICTS_DAMAGE_STORAGE);
+        assert_eq!(position("Equipped"), EQUIPPED_STORAGE);
+        assert_eq!(position("NaturalAttackDefense"), NATURAL_ATTACK_DEFENSE_STORAGE);
+        assert_eq!(position("Weapon"), WEAPON_STORAGE);
+        assert_eq!(position("CharacterHistory"), CHARACTER_HISTORY_STORAGE);
+        assert_eq!(position("Resistances"), RESISTANCES_STORAGE);
+        assert_eq!(position("StatusInfo"), STATUS_STORAGES);
+        assert_eq!(
+            COMPONENT_NAMES.len(),
+            STATUS_STORAGES + STATUS_STORAGE_COUNT,
+            "a new storage needs a migration to add it to older saves"
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use specs::{Builder, Entity, Join, World, WorldExt};
+
+use crate::component_registry;
+use crate::components::{
+    DMSerializationHelper, Player, Pools, Position, SerializationHelper, SerializeMe, Viewshed,
+};
+use crate::gamelog::{GameEvents, GameLog};
+use crate::map::dungeon::MasterDungeonMap;
+use crate::map::Map;
+use crate::rng::{RunSeed, TurnsPlayed};
+
+pub use migrations::SAVE_FORMAT_VERSION;
+pub use slots::*;
+
+mod binary;
+mod migrations;
+mod slots;
+
+/// Written ahead of the component storages so a loader knows which migrations
+/// the rest of the file needs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SaveHeader {
+    pub format_version: u32,
+    pub game_version: String,
+    #[serde(default)]
+    pub metadata: SaveMetadata,
+    #[serde(default)]
+    pub encoding: SaveEncoding,
+}
+
+/// How the component storages after the header are written.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveEncoding {
+    /// One JSON value per storage. Larger and slower, but readable.
+    #[default]
+    Json,
+    /// The same values in a compact binary form.
+    Binary,
+}
+
+/// A summary of the run for the load screen.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SaveMetadata {
+    pub level: i32,
+    pub depth: i32,
+    pub map_name: String,
+    pub turns: i32,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl SaveHeader {
+    fn current(ecs: &World, encoding: SaveEncoding) -> Self {
+        let player = *ecs.fetch::<Entity>();
+        let map = ecs.fetch::<Map>();
+        let metadata = SaveMetadata {
+            level: ecs
+                .read_storage::<Pools>()
+                .get(player)
+                .map_or(0, |pools| pools.level),
+            depth: map.depth,
+            map_name: map.name.clone(),
+            turns: ecs.fetch::<TurnsPlayed>().0,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        };
+        Self {
+            format_version: SAVE_FORMAT_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata,
+            encoding,
+        }
+    }
+}
+
+/// Why a save couldn't be loaded.
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Corrupt(String),
+    TooNew {
+        format_version: u32,
+        game_version: String,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Unable to read the save: {e}"),
+            Self::Corrupt(reason) => write!(f, "The save is damaged: {reason}"),
+            Self::TooNew {
+                format_version,
+                game_version,
+            } => write!(
+                f,
+                "The save is from version {game_version} (format {format_version}), \
+                 newer than this game understands"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Corrupt(e.to_string())
+    }
+}
+
+/// Saves the run into its slot.
+pub fn save_game(ecs: &mut World, encoding: SaveEncoding) {
+    let slot = ecs.fetch::<SaveSlot>().0.clone();
+    fs::create_dir_all(save_dir()).expect("Unable to create the save directory");
+    let writer = File::create(slot_path(&slot)).expect("Unable to create the save file");
+    write_save(ecs, BufWriter::new(writer), encoding);
+    delete_legacy_slot(&slot);
+}
+
+/// Writes the header as a line of JSON and then the whole run, map and dungeon
+/// included, to `writer`.
+pub fn write_save(ecs: &mut World, mut writer: impl Write, encoding: SaveEncoding) {
+    serde_json::to_writer(&mut writer, &SaveHeader::current(ecs, encoding))
+        .expect("Unable to write header");
+    writer.write_all(b"\n").expect("Unable to write header");
+
+    let mapcopy = ecs.get_mut::<Map>().unwrap().clone();
+    let dungeon_master = ecs.get_mut::<MasterDungeonMap>().unwrap().clone();
+    let savehelper = ecs
+        .create_entity()
+        .with(SerializationHelper { map: mapcopy })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    let savehelper2 = ecs
+        .create_entity()
+        .with(DMSerializationHelper {
+            map: dungeon_master,
+            log: ecs.fetch::<GameLog>().clone(),
+            events: ecs.fetch::<GameEvents>().clone(),
+            seed: ecs.fetch::<RunSeed>().0,
+            turns: ecs.fetch::<TurnsPlayed>().0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    let storages = component_registry::save_components(ecs);
+    ecs.delete_entity(savehelper).expect("Crash on cleanup");
+    ecs.delete_entity(savehelper2).expect("Crash on cleanup");
+
+    match encoding {
+        SaveEncoding::Json => {
+            for storage in &storages {
+                serde_json::to_writer(&mut writer, storage).expect("Unable to write save");
+            }
+        }
+        SaveEncoding::Binary => writer
+            .write_all(&binary::encode(&storages))
+            .expect("Unable to write save"),
+    }
+    writer.flush().expect("Unable to write save");
+}
+
+/// Loads the run saved in `slot`, which becomes the slot it saves back into.
+pub fn load_game(ecs: &mut World, slot: &str) -> Result<(), SaveError> {
+    let data = fs::read(existing_slot_path(slot))?;
+    read_save(ecs, &data)?;
+    ecs.insert(SaveSlot(slot.to_string()));
+    Ok(())
+}
+
+/// Splits a save into its header and component storages, upgrading storages
+/// written by older versions. Saves from before the header existed are format 0.
+fn parse_save(data: &[u8]) -> Result<(SaveHeader, Vec<Value>), SaveError> {
+    let mut stream = serde_json::Deserializer::from_slice(data).into_iter::<Value>();
+    let (header, body) = match stream.next() {
+        Some(Ok(header @ Value::Object(_))) => (
+            serde_json::from_value::<SaveHeader>(header)?,
+            &data[stream.byte_offset()..],
+        ),
+        Some(Ok(_)) => (
+            SaveHeader {
+                format_version: 0,
+                game_version: "unknown".to_string(),
+                metadata: SaveMetadata::default(),
+                encoding: SaveEncoding::Json,
+            },
+            data,
+        ),
+        Some(Err(e)) => return Err(e.into()),
+        None => return Err(SaveError::Corrupt("the file is empty".to_string())),
+    };
+    if header.format_version > SAVE_FORMAT_VERSION {
+        return Err(SaveError::TooNew {
+            format_version: header.format_version,
+            game_version: header.game_version,
+        });
+    }
+
+    let mut values = match header.encoding {
+        SaveEncoding::Json => serde_json::Deserializer::from_slice(body)
+            .into_iter::<Value>()
+            .collect::<Result<Vec<_>, _>>()?,
+        SaveEncoding::Binary => {
+            binary::decode(body.strip_prefix(b"\n").unwrap_or(body)).map_err(SaveError::Corrupt)?
+        }
+    };
+    migrations::migrate(header.format_version, &mut values)?;
+    if values.len() != component_registry::COMPONENT_NAMES.len() {
+        return Err(SaveError::Corrupt(format!(
+            "expected {} component storages, found {}",
+            component_registry::COMPONENT_NAMES.len(),
+            values.len()
+        )));
+    }
+
+    Ok((header, values))
+}
+
+/// Replaces everything in the world with the run `write_save` wrote. The save is
+/// parsed and migrated before anything is deleted, but a storage that fails to
+/// load part way through leaves the world half loaded, so callers should start
+/// over from a fresh world on an error.
+pub fn read_save(ecs: &mut World, data: &[u8]) -> Result<(), SaveError> {
+    let (_header, storages) = parse_save(data)?;
+
+    {
+        let mut to_delete = Vec::new();
+        for e in ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in &to_delete {
+            ecs.delete_entity(*del).expect("Deletion failed");
+        }
+    }
+
+    component_registry::load_components(ecs, &storages)?;
+
+    let mut deleteme: Option<Entity> = None;
+    let mut deleteme2: Option<Entity> = None;
+    {
+        let entities = ecs.entities();
+        let helper = ecs.read_storage::<SerializationHelper>();
+        let helper2 = ecs.read_storage::<DMSerializationHelper>();
+        let player = ecs.read_storage::<Player>();
+        let position = ecs.read_storage::<Position>();
+        for (e, h) in (&entities, &helper).join() {
+            let mut worldmap = ecs.write_resource::<Map>();
+            *worldmap = h.map.clone();
+            worldmap.restore_unsaved();
+            deleteme = Some(e);
+        }
+
+        for (e, h) in (&entities, &helper2).join() {
+            let mut dungeonmaster = ecs.write_resource::<MasterDungeonMap>();
+            *dungeonmaster = h.map.clone();
+            deleteme2 = Some(e);
+            *ecs.write_resource::<GameLog>() = h.log.clone();
+            *ecs.write_resource::<GameEvents>() = h.events.clone();
+            // The generator's internal state isn't serialisable, so a loaded run continues
+            // from a stream derived from its seed and the number of turns played.
+            *ecs.write_resource::<RunSeed>() = RunSeed(h.seed);
+            *ecs.write_resource::<TurnsPlayed>() = TurnsPlayed(h.turns);
+            *ecs.write_resource::<RandomNumberGenerator>() =
+                RandomNumberGenerator::seeded(h.seed.wrapping_add(h.turns as u64));
+        }
+
+        for (e, _p, pos) in (&entities, &player, &position).join() {
+            let mut ppos = ecs.write_resource::<rltk::Point>();
+            *ppos = rltk::Point::new(pos.x, pos.y);
+            let mut player_resource = ecs.write_resource::<Entity>();
+            *player_resource = e;
+        }
+    }
+    let (Some(deleteme), Some(deleteme2)) = (deleteme, deleteme2) else {
+        return Err(SaveError::Corrupt("the map is missing".to_string()));
+    };
+    ecs.delete_entity(deleteme)
+        .expect("Unable to delete helper");
+    ecs.delete_entity(deleteme2)
+        .expect("Unable to delete helper");
+
+    // Nothing is visible or lit until the viewsheds are worked out again
+    for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+        viewshed.dirty = true;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rltk::RandomNumberGenerator;
+    use specs::WorldExt;
+
+    use super::{
+        format_timestamp, read_save, write_save, SaveEncoding, SaveError, SaveHeader,
+        SAVE_FORMAT_VERSION,
+    };
+    use crate::map::Map;
+    use crate::rng::TurnsPlayed;
+    use crate::test_support::saves::{join, saved_scenario};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn loading_a_save_keeps_equipped_items() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        scenario.equip("Longsword", player);
+        scenario.give("Health Potion", player);
+
+        let mut save = Vec::new();
+        write_save(&mut scenario.ecs, &mut save, SaveEncoding::Json);
+        read_save(&mut scenario.ecs, &save).unwrap();
+        scenario.settle();
+
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert!(scenario.has_item(player, "Longsword"));
+        assert!(scenario.has_item(player, "Health Potion"));
+    }
+
+    #[test]
+    fn binary_saves_load_the_same_run() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        scenario.equip("Longsword", player);
+        let tiles = scenario.ecs.fetch::<Map>().tiles.clone();
+
+        let mut json_save = Vec::new();
+        let mut binary_save = Vec::new();
+        write_save(&mut scenario.ecs, &mut json_save, SaveEncoding::Json);
+        write_save(&mut scenario.ecs, &mut binary_save, SaveEncoding::Binary);
+        read_save(&mut scenario.ecs, &binary_save).unwrap();
+        scenario.settle();
+
+        assert!(binary_save.len() < json_save.len());
+        let player = scenario.player();
+        assert_eq!(scenario.position(player), Some((1, 1)));
+        assert!(scenario.has_item(player, "Longsword"));
+        let map = scenario.ecs.fetch::<Map>();
+        assert!(map.tiles == tiles);
+        assert_eq!(map.visible_tiles.len(), tiles.len());
+        assert!(map.visible_tiles[map.xy_idx(2, 1)]);
+    }
+
+    #[test]
+    fn loaded_runs_reseed_from_the_turns_played() {
+        let (mut scenario, _) = saved_scenario();
+        // Saves the run as if `turns` had been played, loads it back and rolls
+        let mut roll_after_loading = |turns| {
+            *scenario.ecs.write_resource::<TurnsPlayed>() = TurnsPlayed(turns);
+            let mut save = Vec::new();
+            write_save(&mut scenario.ecs, &mut save, SaveEncoding::Json);
+            *scenario.ecs.write_resource::<TurnsPlayed>() = TurnsPlayed(0);
+            read_save(&mut scenario.ecs, &save).unwrap();
+            assert_eq!(scenario.ecs.fetch::<TurnsPlayed>().0, turns);
+            scenario
+                .ecs
+                .write_resource::<RandomNumberGenerator>()
+                .next_u64()
+        };
+
+        // Neither save has a turn on its level, but they were made far apart in the run
+        assert_ne!(roll_after_loading(10), roll_after_loading(20));
+        assert_eq!(roll_after_loading(10), roll_after_loading(10));
+    }
+
+    #[test]
+    fn saves_start_with_the_current_header() {
+        let (_scenario, values) = saved_scenario();
+
+        let header: SaveHeader = serde_json::from_value(values[0].clone()).unwrap();
+        assert_eq!(header.format_version, SAVE_FORMAT_VERSION);
+        assert_eq!(header.game_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn save_headers_describe_the_run() {
+        let (_scenario, values) = saved_scenario();
+
+        let header: SaveHeader = serde_json::from_value(values[0].clone()).unwrap();
+        assert_eq!(header.metadata.level, 1);
+        assert_eq!(header.metadata.depth, 1);
+        assert_eq!(header.metadata.map_name, "Scenario");
+        assert_eq!(header.metadata.turns, 0);
+        assert!(header.metadata.timestamp > 0);
+    }
+
+    #[test]
+    fn timestamps_format_as_utc_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_782_400 + 3_661), "2000-02-29 01:01");
+    }
+
+    #[test]
+    fn saves_from_newer_versions_are_refused() {
+        let (mut scenario, mut values) = saved_scenario();
+        values[0]["format_version"] = (SAVE_FORMAT_VERSION + 1).into();
+
+        let result = read_save(&mut scenario.ecs, &join(&values));
+
+        assert!(matches!(result, Err(SaveError::TooNew { .. })));
+    }
+
+    #[test]
+    fn damaged_saves_are_refused() {
+        let (mut scenario, values) = saved_scenario();
+        let data = join(&values);
+
+        let truncated = read_save(&mut scenario.ecs, &data[..data.len() / 2]);
+        let missing_storage = read_save(&mut scenario.ecs, &join(&values[..10]));
+
+        assert!(matches!(truncated, Err(SaveError::Corrupt(_))));
+        assert!(matches!(missing_storage, Err(SaveError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,203 @@
+//! Save slots: one file per slot in the player's data directory, each starting
+//! with a header that can be read without loading the rest.
+
+#[cfg(test)]
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use super::SaveHeader;
+
+const GAME_DIR: &str = "rusty-roguelike";
+const SLOT_EXTENSION: &str = "sav";
+/// What slots were called before saves could be binary, whatever they hold.
+const LEGACY_SLOT_EXTENSION: &str = "json";
+
+/// The slot the current run saves into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveSlot(pub String);
+
+/// What the load screen knows about a slot without loading it.
+#[derive(Clone, Debug)]
+pub struct SlotSummary {
+    pub name: String,
+    /// `None` when the header can't be read; loading the slot says why.
+    pub header: Option<SaveHeader>,
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_DATA_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Points `data_dir` at `dir` for the tests running on this thread, so they never
+/// touch the player's files. `None` goes back to the real directory.
+#[cfg(test)]
+pub fn set_test_data_dir(dir: Option<PathBuf>) {
+    TEST_DATA_DIR.with(|test_dir| *test_dir.borrow_mut() = dir);
+}
+
+/// The per-user directory the game keeps its files in: `$XDG_DATA_HOME` or
+/// `~/.local/share` on Linux, Application Support on macOS and `%APPDATA%` on
+/// Windows. Falls back on the temp directory when none of those can be found.
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_DATA_DIR.with(|dir| dir.borrow().clone()) {
+        return dir;
+    }
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    base.unwrap_or_else(env::temp_dir).join(GAME_DIR)
+}
+
+pub fn save_dir() -> PathBuf {
+    data_dir().join("saves")
+}
+
+/// Where `name` gets saved.
+pub fn slot_path(name: &str) -> PathBuf {
+    save_dir().join(format!("{name}.{SLOT_EXTENSION}"))
+}
+
+fn legacy_slot_path(name: &str) -> PathBuf {
+    save_dir().join(format!("{name}.{LEGACY_SLOT_EXTENSION}"))
+}
+
+/// Where `name` was saved: its own file, or the one an older version left.
+pub fn existing_slot_path(name: &str) -> PathBuf {
+    let path = slot_path(name);
+    let legacy = legacy_slot_path(name);
+    if !path.exists() && legacy.exists() {
+        legacy
+    } else {
+        path
+    }
+}
+
+fn slot_names() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(save_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |ext| ext == SLOT_EXTENSION || ext == LEGACY_SLOT_EXTENSION)
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn any_slot_exists() -> bool {
+    !slot_names().is_empty()
+}
+
+/// Every slot on disk with its header, sorted by name.
+pub fn list_slots() -> Vec<SlotSummary> {
+    slot_names()
+        .into_iter()
+        .map(|name| {
+            let header = read_header(&name);
+            SlotSummary { name, header }
+        })
+        .collect()
+}
+
+fn read_header(name: &str) -> Option<SaveHeader> {
+    let file = File::open(existing_slot_path(name)).ok()?;
+    serde_json::Deserializer::from_reader(BufReader::new(file))
+        .into_iter::<SaveHeader>()
+        .next()?
+        .ok()
+}
+
+/// The first of `slot-1`, `slot-2`, ... that nothing is saved in.
+pub fn next_free_slot() -> SaveSlot {
+    let taken = slot_names();
+    let name = (1..)
+        .map(|n| format!("slot-{n}"))
+        .find(|name| !taken.contains(name))
+        .unwrap();
+    SaveSlot(name)
+}
+
+pub fn delete_slot(name: &str) {
+    delete_legacy_slot(name);
+    let path = slot_path(name);
+    if path.exists() {
+        fs::remove_file(path).expect("Unable to delete file");
+    }
+}
+
+/// Removes the file an older version saved `name` in, once it's been saved again.
+pub fn delete_legacy_slot(name: &str) {
+    let path = legacy_slot_path(name);
+    if path.exists() {
+        fs::remove_file(path).expect("Unable to delete file");
+    }
+}
+
+/// While a run is being played this file names its slot. Finding it at startup
+/// means the last session ended without returning to the menu.
+fn session_path() -> PathBuf {
+    data_dir().join("session")
+}
+
+pub fn mark_session(slot: &str) {
+    fs::create_dir_all(data_dir()).expect("Unable to create the data directory");
+    fs::write(session_path(), slot).expect("Unable to write the session file");
+}
+
+pub fn clear_session() {
+    let path = session_path();
+    if path.exists() {
+        fs::remove_file(path).expect("Unable to delete the session file");
+    }
+}
+
+/// The slot of a run that was still being played when the game last stopped,
+/// if that run has a save to go back to.
+pub fn interrupted_session() -> Option<SlotSummary> {
+    let slot = fs::read_to_string(session_path()).ok()?;
+    list_slots()
+        .into_iter()
+        .find(|summary| summary.name == slot.trim())
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Howard Hinnant's days-to-civil conversion
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
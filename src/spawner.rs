@@ -1,36 +1,66 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
-use rltk::{console, to_cp437, Point, BLACK, CYAN, RGB};
+use rltk::{console, to_cp437, Point, RandomNumberGenerator, BLACK, CYAN, RGB};
+use serde::{Deserialize, Serialize};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use specs::{Builder, Entity, World, WorldExt};
 
 use crate::components::{
-    Attribute, AttributeBonus, Attributes, Duration, EntryTrigger, EquipmentChanged, Faction,
-    HungerClock, HungerState, Initiative, KnownSpells, LightSource, Name, OtherLevelPosition,
-    Player, Pool, Pools, Position, Renderable, SerializeMe, SingleActivation, Skill, Skills,
-    StatusEffect, TeleportTo, Viewshed,
+    Attribute, Attributes, CharacterHistory, EntryTrigger, EquipmentChanged, Faction, HungerClock,
+    HungerState, Initiative, KnownSpells, LightSource, Name, OtherLevelPosition, Player, Pool,
+    Pools, Position, Renderable, SerializeMe, SingleActivation, TeleportTo, Viewshed,
 };
-use crate::gamesystem::{attr_bonus, mana_at_level, player_hp_at_level};
+use crate::effects::add_status;
+use crate::gamesystem::{attr_bonus, mana_at_level, player_hp_at_level, NORMAL_SPEED};
 use crate::map::dungeon::MasterDungeonMap;
 use crate::map::{tiletype::TileType, Map};
 use crate::random_table::MasterTable;
 use crate::raws::rawmaster::{
-    get_spawn_table_for_depth, spawn_all_spells, spawn_named_entity, SpawnType, RAWS,
+    get_spawn_table_for_depth, skills_from_raws, spawn_all_spells, spawn_named_entity, RawMaster,
+    SpawnType,
 };
+use crate::raws::{ClassAttributes, PlayerClass};
 use crate::rect::Rect;
-use crate::rng::roll_dice;
 
 const MAX_MONSTERS: i32 = 4;
 
-pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
-    spawn_all_spells(ecs);
+/// Who the player made on the character creation screen.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewCharacter {
+    pub name: String,
+    pub class: String,
+    pub attributes: ClassAttributes,
+}
+
+impl NewCharacter {
+    /// A character of `class` as it comes, without a name of their own.
+    pub fn of_class(class: &PlayerClass) -> Self {
+        Self {
+            name: "Player".to_string(),
+            class: class.name.clone(),
+            attributes: class.attributes,
+        }
+    }
+}
 
-    let mut skills = Skills {
-        skills: HashMap::new(),
-    };
-    skills.skills.insert(Skill::Melee, 1);
-    skills.skills.insert(Skill::Defense, 1);
-    skills.skills.insert(Skill::Magic, 1);
+/// Spawns the player as `character`, with their class's kit in hand.
+pub fn player(ecs: &mut World, player_x: i32, player_y: i32, character: &NewCharacter) -> Entity {
+    let raws = ecs.fetch::<Arc<RawMaster>>().clone();
+    let class = raws
+        .class(&character.class)
+        .unwrap_or_else(|| panic!("No class named {}", character.class));
+    spawn_all_spells(&raws, ecs);
+
+    let attributes = character.attributes;
+    let gold = class
+        .gold
+        .roll(&mut ecs.write_resource::<RandomNumberGenerator>());
+    let spells = class
+        .spells
+        .iter()
+        .filter_map(|spell| raws.known_spell(spell))
+        .collect();
 
     let player = ecs
         .create_entity()
@@ -51,126 +81,91 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             dirty: true,
         })
         .with(Name {
-            name: "Player".to_string(),
+            name: character.name.clone(),
         })
         .with(HungerClock {
             state: HungerState::WellFed,
             duration: 20,
         })
+        .with(CharacterHistory::default())
         .with(Attributes {
             might: Attribute {
-                base: 11,
+                base: attributes.might,
                 modifiers: 0,
-                bonus: attr_bonus(11),
+                bonus: attr_bonus(attributes.might),
             },
             fitness: Attribute {
-                base: 11,
+                base: attributes.fitness,
                 modifiers: 0,
-                bonus: attr_bonus(11),
+                bonus: attr_bonus(attributes.fitness),
             },
             quickness: Attribute {
-                base: 11,
+                base: attributes.quickness,
                 modifiers: 0,
-                bonus: attr_bonus(11),
+                bonus: attr_bonus(attributes.quickness),
             },
             intelligence: Attribute {
-                base: 11,
+                base: attributes.intelligence,
                 modifiers: 0,
-                bonus: attr_bonus(11),
+                bonus: attr_bonus(attributes.intelligence),
             },
         })
-        .with(skills)
+        .with(skills_from_raws(&class.skills))
         .with(Pools {
             hit_points: Pool {
-                current: player_hp_at_level(11, 1),
-                max: player_hp_at_level(11, 1),
+                current: player_hp_at_level(attributes.fitness, 1),
+                max: player_hp_at_level(attributes.fitness, 1),
             },
             mana: Pool {
-                current: mana_at_level(11, 1),
-                max: mana_at_level(11, 1),
+                current: mana_at_level(attributes.intelligence, 1),
+                max: mana_at_level(attributes.intelligence, 1),
             },
             xp: 0,
             level: 1,
             total_weight: 0.0,
             total_initiative_penalty: 0.0,
-            gold: 0.0,
+            gold: gold as f32,
             god_mode: false,
         })
         .with(LightSource {
             color: RGB::from_f32(1.0, 1.0, 0.5),
             range: 8,
         })
-        .with(Initiative { current: 0 })
+        .with(Initiative {
+            current: 0,
+            speed: NORMAL_SPEED,
+            spent: None,
+        })
         .with(EquipmentChanged {})
         .with(Faction {
-            name: "Player".to_string(),
+            name: class.faction.clone(),
         })
-        .with(KnownSpells { spells: vec![] })
+        .with(KnownSpells { spells })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    ecs.create_entity()
-        .with(StatusEffect { target: player })
-        .with(Duration { turns: 10 })
-        .with(Name {
-            name: "Hangover".into(),
-        })
-        .with(AttributeBonus {
-            might: Some(-1),
-            fitness: None,
-            quickness: Some(-1),
-            intelligence: Some(-1),
-        })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+    if let Some(hangover) = raws.status("Hangover") {
+        add_status(ecs, player, hangover);
+    }
 
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Rusty Longsword",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Dried Sausage",
-        SpawnType::Carried { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Beer",
-        SpawnType::Carried { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Stained Tunic",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Torn Trousers",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Old Boots",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Shortbow",
-        SpawnType::Carried { by: player },
-    );
+    for item in &class.equipped {
+        spawn_named_entity(&raws, ecs, item, SpawnType::Equipped { by: player });
+    }
+    for item in &class.carried {
+        spawn_named_entity(&raws, ecs, item, SpawnType::Carried { by: player });
+    }
 
     player
 }
 
-pub fn spawn_room(map: &Map, room: &Rect, map_depth: i32, spawn_list: &mut Vec<(usize, String)>) {
+pub fn spawn_room(
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+    map: &Map,
+    room: &Rect,
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
     let mut possible_targets: Vec<usize> = Vec::new();
     {
         for y in room.y1 + 1..room.y2 {
@@ -183,23 +178,25 @@ pub fn spawn_room(map: &Map, room: &Rect, map_depth: i32, spawn_list: &mut Vec<(
         }
     }
 
-    spawn_region(map, &possible_targets, map_depth, spawn_list);
+    spawn_region(raws, rng, map, &possible_targets, map_depth, spawn_list);
 }
 
 pub fn spawn_region(
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
     _map: &Map,
     area: &[usize],
     map_depth: i32,
     spawn_list: &mut Vec<(usize, String)>,
 ) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points = HashMap::new();
+    let spawn_table = room_table(raws, map_depth);
+    let mut spawn_points = BTreeMap::new();
     let mut areas = Vec::from(area);
 
     {
         let num_spawns = i32::min(
             areas.len() as i32,
-            roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
+            rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
         );
         if num_spawns == 0 {
             return;
@@ -209,10 +206,10 @@ pub fn spawn_region(
             let array_index = if areas.len() == 1 {
                 0usize
             } else {
-                (roll_dice(1, areas.len() as i32) - 1) as usize
+                (rng.roll_dice(1, areas.len() as i32) - 1) as usize
             };
             let map_idx = areas[array_index];
-            spawn_points.insert(map_idx, spawn_table.roll());
+            spawn_points.insert(map_idx, spawn_table.roll(rng));
             areas.remove(array_index);
         }
     }
@@ -228,12 +225,8 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     let y = (*spawn.0 / map.width as usize) as i32;
     std::mem::drop(map);
 
-    let spawn_result = spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        spawn.1,
-        SpawnType::AtPosition { x, y },
-    );
+    let raws = ecs.fetch::<Arc<RawMaster>>().clone();
+    let spawn_result = spawn_named_entity(&raws, ecs, spawn.1, SpawnType::AtPosition { x, y });
     if spawn_result.is_some() {
         return;
     }
@@ -246,8 +239,8 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     }
 }
 
-fn room_table(map_depth: i32) -> MasterTable {
-    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth)
+fn room_table(raws: &RawMaster, map_depth: i32) -> MasterTable {
+    get_spawn_table_for_depth(raws, map_depth)
 }
 
 pub fn spawn_town_portal(ecs: &mut World) {
@@ -297,3 +290,38 @@ pub fn spawn_town_portal(ecs: &mut World) {
         .with(SingleActivation {})
         .build();
 }
+
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use crate::components::{Attributes, KnownSpells};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn classes_start_with_their_own_kit_and_spells() {
+        let scenario = Scenario::with_class(
+            "
+            ####
+            #@.#
+            ####
+            ",
+            "Mage",
+        );
+        let player = scenario.player();
+
+        assert!(scenario.has_item(player, "Mana Potion"));
+        assert!(!scenario.has_item(player, "Rusty Longsword"));
+        let attributes = scenario.ecs.read_storage::<Attributes>();
+        assert_eq!(attributes.get(player).unwrap().intelligence.base, 15);
+        let known = scenario.ecs.read_storage::<KnownSpells>();
+        let spells: Vec<&str> = known
+            .get(player)
+            .unwrap()
+            .spells
+            .iter()
+            .map(|spell| spell.display_name.as_str())
+            .collect();
+        assert_eq!(spells, vec!["Zap"]);
+    }
+}
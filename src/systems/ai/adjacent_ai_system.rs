@@ -1,11 +1,12 @@
+use std::sync::Arc;
+
 use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
 
 use crate::components::{Faction, MyTurn, Position, TileSize, WantsToMelee};
 use crate::map::Map;
-use crate::raws::rawmaster::{faction_reaction, RAWS};
+use crate::raws::rawmaster::{faction_reaction, RawMaster};
 use crate::raws::Reaction;
 use crate::rect::Rect;
-use crate::spatial;
 
 pub struct AdjacentAI {}
 
@@ -19,10 +20,12 @@ impl<'a> System<'a> for AdjacentAI {
         Entities<'a>,
         ReadExpect<'a, Entity>,
         ReadStorage<'a, TileSize>,
+        ReadExpect<'a, Arc<RawMaster>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes) = data;
+        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes, raws) =
+            data;
 
         let mut turn_done = vec![];
         for (entity, _turn, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
@@ -51,15 +54,30 @@ impl<'a> System<'a> for AdjacentAI {
                                 &factions,
                                 &my_faction.name,
                                 &mut reactions,
+                                &raws,
                             );
                         }
                     });
             } else {
                 if pos.x > 0 {
-                    evaluate(idx - 1, &map, &factions, &my_faction.name, &mut reactions);
+                    evaluate(
+                        idx - 1,
+                        &map,
+                        &factions,
+                        &my_faction.name,
+                        &mut reactions,
+                        &raws,
+                    );
                 }
                 if pos.x < w - 1 {
-                    evaluate(idx + 1, &map, &factions, &my_faction.name, &mut reactions);
+                    evaluate(
+                        idx + 1,
+                        &map,
+                        &factions,
+                        &my_faction.name,
+                        &mut reactions,
+                        &raws,
+                    );
                 }
                 if pos.y > 0 {
                     evaluate(
@@ -68,6 +86,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
                 if pos.y < h - 1 {
@@ -77,6 +96,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
                 if pos.y > 0 && pos.x > 0 {
@@ -86,6 +106,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
                 if pos.y > 0 && pos.x < w - 1 {
@@ -95,6 +116,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
                 if pos.y < h - 1 && pos.x > 0 {
@@ -104,6 +126,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
                 if pos.y < h - 1 && pos.x < w - 1 {
@@ -113,6 +136,7 @@ impl<'a> System<'a> for AdjacentAI {
                         &factions,
                         &my_faction.name,
                         &mut reactions,
+                        &raws,
                     );
                 }
             }
@@ -139,16 +163,17 @@ impl<'a> System<'a> for AdjacentAI {
 
 fn evaluate(
     idx: usize,
-    _map: &Map,
+    map: &Map,
     factions: &ReadStorage<Faction>,
     my_faction: &str,
     reactions: &mut Vec<(Entity, Reaction)>,
+    raws: &RawMaster,
 ) {
-    spatial::for_each_tile_content(idx, |other_entity| {
+    map.spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 other_entity,
-                faction_reaction(my_faction, &faction.name, &RAWS.lock().unwrap()),
+                faction_reaction(my_faction, &faction.name, raws),
             ));
         }
     });
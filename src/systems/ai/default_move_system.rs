@@ -1,9 +1,9 @@
 use crate::astar::a_star_search;
-use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use rltk::RandomNumberGenerator;
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{ApplyMove, MoveMode, Movement, MyTurn, Position};
 use crate::map::Map;
-use crate::rng::roll_dice;
 
 pub struct DefaultMoveAI {}
 
@@ -15,10 +15,11 @@ impl<'a> System<'a> for DefaultMoveAI {
         ReadExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, ApplyMove>,
+        WriteExpect<'a, RandomNumberGenerator>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, mut move_mode, positions, map, entities, mut apply_move) = data;
+        let (mut turns, mut move_mode, positions, map, entities, mut apply_move, mut rng) = data;
 
         let mut turn_done = vec![];
         for (entity, pos, mode, _myturn) in (&entities, &positions, &mut move_mode, &turns).join() {
@@ -29,7 +30,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                 Movement::RandomWaypoint { path } => {
                     if let Some(path) = path {
                         if path.len() > 1 {
-                            if !crate::spatial::is_blocked(path[1]) {
+                            if !map.spatial.is_blocked(path[1]) {
                                 apply_move
                                     .insert(entity, ApplyMove { dest_idx: path[1] })
                                     .expect("Unable to insert");
@@ -40,8 +41,8 @@ impl<'a> System<'a> for DefaultMoveAI {
                             mode.mode = Movement::RandomWaypoint { path: None };
                         }
                     } else {
-                        let target_x = roll_dice(1, map.width - 2);
-                        let target_y = roll_dice(1, map.height - 2);
+                        let target_x = rng.roll_dice(1, map.width - 2);
+                        let target_y = rng.roll_dice(1, map.height - 2);
                         let idx = map.xy_idx(target_x, target_y);
                         if map.tiles[idx].is_walkable() {
                             let path = a_star_search(
@@ -60,7 +61,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                 Movement::Random => {
                     let mut x = pos.x;
                     let mut y = pos.x;
-                    let move_roll = roll_dice(1, 5);
+                    let move_roll = rng.roll_dice(1, 5);
                     match move_roll {
                         1 => x -= 1,
                         2 => x += 1,
@@ -71,7 +72,7 @@ impl<'a> System<'a> for DefaultMoveAI {
 
                     if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
                         let dest_idx = map.xy_idx(x, y);
-                        if !crate::spatial::is_blocked(dest_idx) {
+                        if !map.spatial.is_blocked(dest_idx) {
                             apply_move
                                 .insert(entity, ApplyMove { dest_idx })
                                 .expect("Unable to insert");
@@ -1,13 +1,13 @@
 use rltk::ORANGE;
 use std::collections::HashMap;
 
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
     AttributeBonus, Attributes, EquipmentChanged, Equipped, InBackpack, Item, Pools, Slow,
-    StatusEffect,
+    StatusEffect, StatusInfo,
 };
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 
 pub struct EncumbranceSystem {}
 
@@ -24,6 +24,8 @@ impl<'a> System<'a> for EncumbranceSystem {
         ReadStorage<'a, AttributeBonus>,
         ReadStorage<'a, StatusEffect>,
         ReadStorage<'a, Slow>,
+        ReadStorage<'a, StatusInfo>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -39,6 +41,8 @@ impl<'a> System<'a> for EncumbranceSystem {
             attr_bonus,
             statuses,
             slows,
+            infos,
+            mut log,
         ) = data;
 
         if equip_dirty.is_empty() {
@@ -84,20 +88,22 @@ impl<'a> System<'a> for EncumbranceSystem {
             }
         }
 
-        for (status, attr) in (&statuses, &attr_bonus).join() {
+        for (entity, status, attr) in (&entities, &statuses, &attr_bonus).join() {
             if to_update.contains_key(&status.target) {
                 let totals = to_update.get_mut(&status.target).unwrap();
-                totals.might += attr.might.unwrap_or(0);
-                totals.fitness += attr.fitness.unwrap_or(0);
-                totals.quickness += attr.quickness.unwrap_or(0);
-                totals.intelligence += attr.intelligence.unwrap_or(0);
+                let stacks = StatusInfo::stacks_of(infos.get(entity));
+                totals.might += attr.might.unwrap_or(0) * stacks;
+                totals.fitness += attr.fitness.unwrap_or(0) * stacks;
+                totals.quickness += attr.quickness.unwrap_or(0) * stacks;
+                totals.intelligence += attr.intelligence.unwrap_or(0) * stacks;
             }
         }
 
-        for (status, slow) in (&statuses, &slows).join() {
+        for (entity, status, slow) in (&entities, &statuses, &slows).join() {
             if to_update.contains_key(&status.target) {
                 let totals = to_update.get_mut(&status.target).unwrap();
-                totals.initiative += slow.initiative_penalty;
+                let stacks = StatusInfo::stacks_of(infos.get(entity));
+                totals.initiative += slow.initiative_penalty * stacks as f32;
             }
         }
 
@@ -126,7 +132,7 @@ impl<'a> System<'a> for EncumbranceSystem {
                                 .append(
                                     "You are overburdened, and suffering an initiative penalty.",
                                 )
-                                .log();
+                                .log(&mut log);
                         }
                     }
                 }
@@ -1,13 +1,9 @@
 use rltk::{DistanceAlg, Point};
 use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
-use crate::components::{
-    Attributes, DamageOverTime, Duration, EquipmentChanged, Initiative, MyTurn, Pools, Position,
-    StatusEffect,
-};
-use crate::effects::{add_effect, EffectType, Targets};
+use crate::components::{ActionKind, Attributes, Initiative, MyTurn, Pools, Position};
+use crate::gamesystem::action_time;
 use crate::player::RunState;
-use crate::rng::roll_dice;
 
 pub struct InitiativeSystem {}
 
@@ -22,10 +18,6 @@ impl<'a> System<'a> for InitiativeSystem {
         ReadExpect<'a, Entity>,
         ReadExpect<'a, Point>,
         ReadStorage<'a, Pools>,
-        WriteStorage<'a, Duration>,
-        WriteStorage<'a, EquipmentChanged>,
-        ReadStorage<'a, StatusEffect>,
-        ReadStorage<'a, DamageOverTime>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -39,10 +31,6 @@ impl<'a> System<'a> for InitiativeSystem {
             player,
             player_pos,
             pools,
-            mut durations,
-            mut dirty,
-            statuses,
-            dots,
         ) = data;
 
         if *runstate != RunState::Ticking {
@@ -52,19 +40,26 @@ impl<'a> System<'a> for InitiativeSystem {
         turns.clear();
 
         for (entity, initiative, pos) in (&entities, &mut initiatives, &positions).join() {
+            let quickness = attributes
+                .get(entity)
+                .map_or(0, |attr| attr.quickness.bonus);
+            let penalty = pools
+                .get(entity)
+                .map_or(0.0, |pools| pools.total_initiative_penalty);
+            let speed = initiative.speed;
+            let time = |action| action_time(action, speed, quickness, penalty);
+
+            // A turn is charged as a wait when it comes round; whatever the actor
+            // did with it instead settles the difference.
+            if let Some(action) = initiative.spent.take() {
+                initiative.current += time(action) - time(ActionKind::Wait);
+            }
+
             initiative.current -= 1;
             if initiative.current < 1 {
                 let mut myturn = true;
 
-                initiative.current = 6 + roll_dice(1, 6);
-
-                if let Some(attr) = attributes.get(entity) {
-                    initiative.current -= attr.quickness.bonus;
-                }
-
-                if let Some(pools) = pools.get(entity) {
-                    initiative.current += f32::floor(pools.total_initiative_penalty) as i32;
-                }
+                initiative.current = time(ActionKind::Wait);
 
                 if entity == *player {
                     *runstate = RunState::AwaitingInput;
@@ -83,32 +78,59 @@ impl<'a> System<'a> for InitiativeSystem {
                 }
             }
         }
+    }
+}
 
-        if *runstate == RunState::AwaitingInput {
-            for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
-                if !entities.is_alive(status.target) {
-                    continue;
-                }
-
-                duration.turns -= 1;
-
-                if let Some(dot) = dots.get(effect_entity) {
-                    add_effect(
-                        None,
-                        EffectType::Damage { amount: dot.damage },
-                        Targets::Single {
-                            target: status.target,
-                        },
-                    );
-                }
-
-                if duration.turns < 1 {
-                    dirty
-                        .insert(status.target, EquipmentChanged {})
-                        .expect("Unable to insert");
-                    entities.delete(effect_entity).expect("Unable to delete");
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use crate::components::{ActionKind, MyTurn};
+    use crate::gamesystem::{action_time, NORMAL_SPEED};
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn fast_mobs_get_more_turns_than_slow_ones() {
+        // Each mob is walled into its own room so nothing gets in anyone's way
+        let mut scenario = Scenario::new(
+            "
+            ###########
+            #@#...#...#
+            ###...#...#
+            ###########
+            ",
+        );
+        let bat = scenario.spawn("Bat", 4, 2);
+        let golem = scenario.spawn("Rock Golem", 8, 2);
+        let mut turns = [0, 0];
+        for _ in 0..120 {
+            scenario.run_ticks(1);
+            let my_turns = scenario.ecs.read_storage::<MyTurn>();
+            for (count, mob) in turns.iter_mut().zip([bat, golem]) {
+                if my_turns.contains(mob) {
+                    *count += 1;
                 }
             }
         }
+
+        let [bat_turns, golem_turns] = turns;
+        assert!(
+            bat_turns > golem_turns * 2,
+            "the bat had {bat_turns} turns to the golem's {golem_turns}"
+        );
+
+        let attack =
+            |weapon_speed| action_time(ActionKind::Melee { weapon_speed }, NORMAL_SPEED, 0, 0.0);
+        assert!(attack(125) < attack(NORMAL_SPEED));
+        assert!(attack(80) > attack(NORMAL_SPEED));
+        assert!(
+            action_time(ActionKind::Drop, NORMAL_SPEED, 0, 0.0)
+                < action_time(ActionKind::Equip, NORMAL_SPEED, 0, 0.0)
+        );
+        assert_eq!(
+            action_time(ActionKind::Move, NORMAL_SPEED, 0, 3.5),
+            action_time(ActionKind::Move, NORMAL_SPEED, 0, 0.0) + 3,
+            "armour and slows add their penalty"
+        );
     }
 }
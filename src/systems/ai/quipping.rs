@@ -1,9 +1,8 @@
-use rltk::Point;
-use specs::{Join, ReadExpect, ReadStorage, System, WriteStorage};
+use rltk::{Point, RandomNumberGenerator};
+use specs::{Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{MyTurn, Name, Quips, Viewshed};
-use crate::gamelog;
-use crate::rng::roll_dice;
+use crate::gamelog::{self, GameLog};
 
 pub struct QuipSystem {}
 
@@ -14,27 +13,29 @@ impl<'a> System<'a> for QuipSystem {
         ReadStorage<'a, MyTurn>,
         ReadExpect<'a, Point>,
         ReadStorage<'a, Viewshed>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut quips, names, turns, player_pos, viewsheds) = data;
+        let (mut quips, names, turns, player_pos, viewsheds, mut rng, mut log) = data;
 
         for (quip, name, viewshed, _turn) in (&mut quips, &names, &viewsheds, &turns).join() {
             if !quip.available.is_empty()
                 && viewshed.visible_tiles.contains(&player_pos)
-                && roll_dice(1, 6) == 1
+                && rng.roll_dice(1, 6) == 1
             {
                 let quip_index = if quip.available.len() == 1 {
                     0
                 } else {
-                    (roll_dice(1, quip.available.len() as i32) - 1) as usize
+                    (rng.roll_dice(1, quip.available.len() as i32) - 1) as usize
                 };
 
                 gamelog::Logger::new()
                     .npc_name(&name.name)
                     .append("says")
                     .npc_name(&quip.available[quip_index])
-                    .log();
+                    .log(&mut log);
                 quip.available.remove(quip_index);
             }
         }
@@ -1,10 +1,10 @@
 use std::collections::HashSet;
 
 use rltk::{to_cp437, BLACK, CYAN};
-use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
-use crate::components::{Confusion, MyTurn, StatusEffect};
-use crate::effects::{add_effect, EffectType, Targets};
+use crate::components::{Confusion, MyTurn, StatusEffect, StatusInfo};
+use crate::effects::{EffectQueue, EffectType, Targets};
 use crate::player::RunState;
 
 pub struct TurnStatusSystem {}
@@ -12,14 +12,16 @@ pub struct TurnStatusSystem {}
 impl<'a> System<'a> for TurnStatusSystem {
     type SystemData = (
         WriteStorage<'a, MyTurn>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, Confusion>,
         Entities<'a>,
         ReadExpect<'a, RunState>,
         ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, StatusInfo>,
+        WriteExpect<'a, EffectQueue>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, confusion, entities, runstate, statuses) = data;
+        let (mut turns, confusion, entities, runstate, statuses, infos, mut effects) = data;
 
         if *runstate != RunState::Ticking {
             return;
@@ -30,23 +32,28 @@ impl<'a> System<'a> for TurnStatusSystem {
             entity_turns.insert(entity);
         }
 
+        // Only statuses that skip turns, such as confusion, cost their target its turn
         let mut not_my_turn = vec![];
-        for (effect_entity, status_effect) in (&entities, &statuses).join() {
+        for (effect_entity, status_effect, _confusion) in (&entities, &statuses, &confusion).join()
+        {
             if entity_turns.contains(&status_effect.target) {
-                if confusion.get(effect_entity).is_some() {
-                    add_effect(
-                        None,
-                        EffectType::Particle {
-                            glyph: to_cp437('?'),
-                            fg: CYAN.into(),
-                            bg: BLACK.into(),
-                            lifespan: 200.0,
-                        },
-                        Targets::Single {
-                            target: status_effect.target,
-                        },
-                    );
-                }
+                let (glyph, fg) = infos
+                    .get(effect_entity)
+                    .map_or((to_cp437('?'), CYAN.into()), |info| {
+                        (info.glyph, info.color)
+                    });
+                effects.add(
+                    None,
+                    EffectType::Particle {
+                        glyph,
+                        fg,
+                        bg: BLACK.into(),
+                        lifespan: 200.0,
+                    },
+                    Targets::Single {
+                        target: status_effect.target,
+                    },
+                );
                 not_my_turn.push(status_effect.target);
             }
         }
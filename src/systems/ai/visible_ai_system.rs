@@ -1,14 +1,15 @@
-use rltk::{DistanceAlg, Point};
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use std::sync::Arc;
+
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
     Chasing, Equipped, Faction, MyTurn, Name, Position, SpecialAbilities, SpellTemplate, Viewshed,
     WantsToApproach, WantsToCastSpell, WantsToFlee, WantsToShoot, Weapon,
 };
 use crate::map::Map;
-use crate::raws::rawmaster::{faction_reaction, find_spell_entity_by_name, RAWS};
+use crate::raws::rawmaster::{faction_reaction, find_spell_entity_by_name, RawMaster};
 use crate::raws::Reaction;
-use crate::rng::roll_dice;
 
 pub struct VisibleAI {}
 
@@ -31,6 +32,8 @@ impl<'a> System<'a> for VisibleAI {
         ReadStorage<'a, Equipped>,
         ReadStorage<'a, Weapon>,
         WriteStorage<'a, WantsToShoot>,
+        ReadExpect<'a, Arc<RawMaster>>,
+        WriteExpect<'a, RandomNumberGenerator>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -52,6 +55,8 @@ impl<'a> System<'a> for VisibleAI {
             equipped,
             weapons,
             mut wants_shoot,
+            raws,
+            mut rng,
         ) = data;
 
         for (entity, _turn, my_faction, pos, viewshed) in
@@ -67,7 +72,14 @@ impl<'a> System<'a> for VisibleAI {
             for visible_tile in &viewshed.visible_tiles {
                 let idx = map.xy_idx(visible_tile.x, visible_tile.y);
                 if my_idx != idx {
-                    evaluate(idx, &map, &factions, &my_faction.name, &mut reactions);
+                    evaluate(
+                        idx,
+                        &map,
+                        &factions,
+                        &my_faction.name,
+                        &mut reactions,
+                        &raws,
+                    );
                 }
             }
 
@@ -86,7 +98,7 @@ impl<'a> System<'a> for VisibleAI {
                             for ability in &abilities.abilities {
                                 if range >= ability.min_range
                                     && range <= ability.range
-                                    && roll_dice(1, 100) >= (ability.chance * 100.0) as i32
+                                    && rng.roll_dice(1, 100) >= (ability.chance * 100.0) as i32
                                 {
                                     casting
                                         .insert(
@@ -160,16 +172,17 @@ impl<'a> System<'a> for VisibleAI {
 
 fn evaluate(
     idx: usize,
-    _map: &Map,
+    map: &Map,
     factions: &ReadStorage<Faction>,
     my_faction: &str,
     reactions: &mut Vec<(usize, Reaction, Entity)>,
+    raws: &RawMaster,
 ) {
-    crate::spatial::for_each_tile_content(idx, |other_entity| {
+    map.spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 idx,
-                faction_reaction(my_faction, &faction.name, &RAWS.lock().unwrap()),
+                faction_reaction(my_faction, &faction.name, raws),
                 other_entity,
             ));
         }
@@ -17,7 +17,7 @@ use crate::systems::{
     InitiativeSystem, ItemCollectionSystem, ItemDropSystem, ItemEquipOnUse,
     ItemIdentificationSystem, ItemRemoveSystem, ItemUseSystem, LightingSystem, MapIndexingSystem,
     MeleeCombatSystem, MovementSystem, ParticleSpawnSystem, QuipSystem, RangedCombatSystem,
-    SpellUseSystem, TriggerSystem, TurnStatusSystem, VisibilitySystem, VisibleAI,
+    SpellUseSystem, StatusSystem, TriggerSystem, TurnStatusSystem, VisibilitySystem, VisibleAI,
 };
 use specs::World;
 
@@ -30,6 +30,7 @@ construct_dispatcher!(
     (VisibilitySystem, "visibility", &[]),
     (EncumbranceSystem, "encumbrance", &[]),
     (InitiativeSystem, "initiative", &[]),
+    (StatusSystem, "statuses", &[]),
     (TurnStatusSystem, "turnstatus", &[]),
     (QuipSystem, "quips", &[]),
     (AdjacentAI, "adjacent", &[]),
@@ -25,6 +25,9 @@ macro_rules! construct_dispatcher {
         fn new_dispatch() -> Box<dyn UnifiedDispatcher + 'static> {
             use specs::DispatcherBuilder;
 
+            // Systems sharing a resource, such as the RNG or the effect queue, never run
+            // at the same time, and the builder always orders them the same way, so
+            // seeded runs stay reproducible without serializing the rest.
             let dispatcher = DispatcherBuilder::new()
             $(
                 .with($type{}, $name, $deps)
@@ -1,10 +1,10 @@
 use rltk::{ORANGE, RED};
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::HungerState::{Hungry, Normal, Starving, WellFed};
-use crate::components::{HungerClock, MyTurn};
-use crate::effects::{add_effect, EffectType, Targets};
-use crate::gamelog;
+use crate::components::{DamageType, HungerClock, MyTurn};
+use crate::effects::{EffectQueue, EffectType, Targets};
+use crate::gamelog::{self, GameLog};
 
 pub struct HungerSystem {}
 
@@ -14,10 +14,12 @@ impl<'a> System<'a> for HungerSystem {
         WriteStorage<'a, HungerClock>,
         ReadExpect<'a, Entity>,
         ReadStorage<'a, MyTurn>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, turns) = data;
+        let (entities, mut hunger_clock, player_entity, turns, mut effects, mut log) = data;
 
         for (entity, clock, _myturn) in (&entities, &mut hunger_clock, &turns).join() {
             clock.duration -= 1;
@@ -33,7 +35,7 @@ impl<'a> System<'a> for HungerSystem {
                         gamelog::Logger::new()
                             .color(ORANGE)
                             .append("You are no longer well fed")
-                            .log();
+                            .log(&mut log);
                     }
                 }
                 Normal => {
@@ -43,7 +45,7 @@ impl<'a> System<'a> for HungerSystem {
                         gamelog::Logger::new()
                             .color(ORANGE)
                             .append("You are hungry")
-                            .log();
+                            .log(&mut log);
                     }
                 }
                 Hungry => {
@@ -53,7 +55,7 @@ impl<'a> System<'a> for HungerSystem {
                         gamelog::Logger::new()
                             .color(RED)
                             .append("You are starving!")
-                            .log();
+                            .log(&mut log);
                     }
                 }
                 Starving => {
@@ -63,11 +65,14 @@ impl<'a> System<'a> for HungerSystem {
                             .append(
                                 "Your hunger pangs are getting painful! You suffer 1hp of damage.",
                             )
-                            .log();
+                            .log(&mut log);
                     }
-                    add_effect(
+                    effects.add(
                         None,
-                        EffectType::Damage { amount: 1 },
+                        EffectType::Damage {
+                            amount: 1,
+                            damage_type: DamageType::Untyped,
+                        },
                         Targets::Single { target: entity },
                     );
                 }
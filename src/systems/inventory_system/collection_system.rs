@@ -1,9 +1,10 @@
-use specs::{Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    EquipmentChanged, InBackpack, MagicItem, Name, ObfuscatedName, Position, WantsToPickupItem,
+    ActionKind, EquipmentChanged, InBackpack, Initiative, MagicItem, Name, ObfuscatedName,
+    Position, WantsToPickupItem,
 };
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 use crate::map::dungeon::MasterDungeonMap;
 
 pub struct ItemCollectionSystem {}
@@ -19,6 +20,8 @@ impl<'a> System<'a> for ItemCollectionSystem {
         ReadStorage<'a, MagicItem>,
         ReadStorage<'a, ObfuscatedName>,
         ReadExpect<'a, MasterDungeonMap>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -32,9 +35,14 @@ impl<'a> System<'a> for ItemCollectionSystem {
             magic_items,
             obfuscated_names,
             dm,
+            mut log,
+            mut initiatives,
         ) = data;
 
         for pickup in wants_pickup.join() {
+            if let Some(initiative) = initiatives.get_mut(pickup.collected_by) {
+                initiative.spend(ActionKind::PickUp);
+            }
             positions.remove(pickup.item);
             backpack
                 .insert(
@@ -58,7 +66,7 @@ impl<'a> System<'a> for ItemCollectionSystem {
                         &obfuscated_names,
                         &dm,
                     ))
-                    .log();
+                    .log(&mut log);
             }
         }
 
@@ -1,9 +1,10 @@
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    EquipmentChanged, InBackpack, MagicItem, Name, ObfuscatedName, Position, WantsToDropItem,
+    ActionKind, EquipmentChanged, InBackpack, Initiative, MagicItem, Name, ObfuscatedName,
+    Position, WantsToDropItem,
 };
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 use crate::inventory_system::obfuscate_name;
 use crate::map::dungeon::MasterDungeonMap;
 
@@ -21,6 +22,8 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, MagicItem>,
         ReadStorage<'a, ObfuscatedName>,
         ReadExpect<'a, MasterDungeonMap>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -35,9 +38,14 @@ impl<'a> System<'a> for ItemDropSystem {
             magic_items,
             obfuscated_names,
             dm,
+            mut log,
+            mut initiatives,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::Drop);
+            }
             let mut dropper_pos = Position { x: 0, y: 0 };
             {
                 let dropped_pos = positions.get(entity).unwrap();
@@ -68,7 +76,7 @@ impl<'a> System<'a> for ItemDropSystem {
                         &obfuscated_names,
                         &dm,
                     ))
-                    .log();
+                    .log(&mut log);
             }
         }
 
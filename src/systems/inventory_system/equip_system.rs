@@ -1,10 +1,10 @@
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    CursedItem, EquipmentChanged, Equippable, Equipped, IdentifiedItem, InBackpack, Name,
-    WantsToUseItem,
+    ActionKind, CursedItem, EquipmentChanged, Equippable, Equipped, IdentifiedItem, InBackpack,
+    Initiative, Name, WantsToUseItem,
 };
-use crate::gamelog;
+use crate::gamelog::{self, GameLog};
 
 pub struct ItemEquipOnUse {}
 
@@ -20,6 +20,8 @@ impl<'a> System<'a> for ItemEquipOnUse {
         WriteStorage<'a, EquipmentChanged>,
         WriteStorage<'a, IdentifiedItem>,
         ReadStorage<'a, CursedItem>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -34,6 +36,8 @@ impl<'a> System<'a> for ItemEquipOnUse {
             mut dirty,
             mut identified_item,
             cursed,
+            mut log,
+            mut initiatives,
         ) = data;
 
         let mut remove_use: Vec<Entity> = Vec::new();
@@ -53,7 +57,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                                 .append("You cannot unequip")
                                 .item_name(&name.name)
                                 .append("- it is cursed!")
-                                .log();
+                                .log(&mut log);
                             continue;
                         }
                         to_unequip.push(item_entity);
@@ -61,7 +65,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                             gamelog::Logger::new()
                                 .append("You unequip")
                                 .item_name(&name.name)
-                                .log();
+                                .log(&mut log);
                         }
                     }
                 }
@@ -119,10 +123,13 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     gamelog::Logger::new()
                         .append("You equip")
                         .item_name(&names.get(useitem.item).unwrap().name)
-                        .log();
+                        .log(&mut log);
                 }
 
                 // Done with item
+                if let Some(initiative) = initiatives.get_mut(target) {
+                    initiative.spend(ActionKind::Equip);
+                }
                 remove_use.push(target);
             }
         }
@@ -1,8 +1,10 @@
-use specs::{Entities, Join, ReadStorage, System, WriteExpect, WriteStorage};
+use std::sync::Arc;
+
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{IdentifiedItem, Item, Name, ObfuscatedName, Player};
 use crate::map::dungeon::MasterDungeonMap;
-use crate::raws::rawmaster::is_tag_magic;
+use crate::raws::rawmaster::{is_tag_magic, RawMaster};
 
 pub struct ItemIdentificationSystem {}
 
@@ -15,13 +17,15 @@ impl<'a> System<'a> for ItemIdentificationSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, ObfuscatedName>,
         Entities<'a>,
+        ReadExpect<'a, Arc<RawMaster>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player, mut identified, mut dm, items, names, mut obfuscated_names, entities) = data;
+        let (player, mut identified, mut dm, items, names, mut obfuscated_names, entities, raws) =
+            data;
 
         for (_p, id) in (&player, &identified).join() {
-            if !dm.identified_items.contains(&id.name) && is_tag_magic(&id.name) {
+            if !dm.identified_items.contains(&id.name) && is_tag_magic(&raws, &id.name) {
                 dm.identified_items.insert(id.name.clone());
 
                 for (entity, _item, name) in (&entities, &items, &names).join() {
@@ -1,8 +1,10 @@
 use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
-use crate::components::{AreaOfEffect, EquipmentChanged, IdentifiedItem, Name, WantsToUseItem};
+use crate::components::{
+    ActionKind, AreaOfEffect, EquipmentChanged, IdentifiedItem, Initiative, Name, WantsToUseItem,
+};
 use crate::effects;
-use crate::effects::{add_effect, EffectType, Targets};
+use crate::effects::{EffectQueue, EffectType, Targets};
 use crate::map::Map;
 
 pub struct ItemUseSystem {}
@@ -17,6 +19,8 @@ impl<'a> System<'a> for ItemUseSystem {
         ReadStorage<'a, AreaOfEffect>,
         WriteStorage<'a, EquipmentChanged>,
         WriteStorage<'a, IdentifiedItem>,
+        WriteExpect<'a, EffectQueue>,
+        WriteStorage<'a, Initiative>,
     );
 
     #[allow(clippy::cognitive_complexity)]
@@ -30,9 +34,14 @@ impl<'a> System<'a> for ItemUseSystem {
             aoe,
             mut dirty,
             mut identified_item,
+            mut effects,
+            mut initiatives,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::UseItem);
+            }
             dirty
                 .insert(entity, EquipmentChanged {})
                 .expect("Unable to insert");
@@ -48,7 +57,7 @@ impl<'a> System<'a> for ItemUseSystem {
                     .expect("Unable to insert");
             }
 
-            add_effect(
+            effects.add(
                 Some(entity),
                 EffectType::ItemUse { item: useitem.item },
                 useitem.target.map_or(
@@ -1,8 +1,10 @@
-use specs::{Entities, Join, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::{
-    components::{CursedItem, Equipped, InBackpack, Name, WantsToRemoveItem},
-    gamelog,
+    components::{
+        ActionKind, CursedItem, Equipped, InBackpack, Initiative, Name, WantsToRemoveItem,
+    },
+    gamelog::{self, GameLog},
 };
 
 pub struct ItemRemoveSystem {}
@@ -15,10 +17,21 @@ impl<'a> System<'a> for ItemRemoveSystem {
         WriteStorage<'a, InBackpack>,
         ReadStorage<'a, CursedItem>,
         ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack, cursed, names) = data;
+        let (
+            entities,
+            mut wants_remove,
+            mut equipped,
+            mut backpack,
+            cursed,
+            names,
+            mut log,
+            mut initiatives,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
             if cursed.get(to_remove.item).is_some() {
@@ -26,9 +39,12 @@ impl<'a> System<'a> for ItemRemoveSystem {
                     .append("You cannot remove")
                     .item_name(&names.get(to_remove.item).unwrap().name)
                     .append("it is cursed")
-                    .log();
+                    .log(&mut log);
                 continue;
             }
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::Equip);
+            }
             equipped.remove(to_remove.item);
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
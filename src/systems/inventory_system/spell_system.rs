@@ -1,7 +1,10 @@
 use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
-use crate::components::{AreaOfEffect, EquipmentChanged, IdentifiedItem, Name, WantsToCastSpell};
-use crate::effects::{add_effect, aoe_tiles, EffectType, Targets};
+use crate::components::{
+    ActionKind, AreaOfEffect, EquipmentChanged, IdentifiedItem, Initiative, Name, Skill,
+    WantsToCastSpell,
+};
+use crate::effects::{aoe_tiles, EffectQueue, EffectType, Targets};
 use crate::map::Map;
 
 pub struct SpellUseSystem {}
@@ -16,6 +19,8 @@ impl<'a> System<'a> for SpellUseSystem {
         ReadStorage<'a, AreaOfEffect>,
         WriteStorage<'a, EquipmentChanged>,
         WriteStorage<'a, IdentifiedItem>,
+        WriteExpect<'a, EffectQueue>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -28,9 +33,14 @@ impl<'a> System<'a> for SpellUseSystem {
             aoe,
             mut dirty,
             mut identified_item,
+            mut effects,
+            mut initiatives,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::Cast);
+            }
             dirty
                 .insert(entity, EquipmentChanged {})
                 .expect("Unable to insert");
@@ -46,7 +56,7 @@ impl<'a> System<'a> for SpellUseSystem {
                     .expect("Unable to insert");
             }
 
-            add_effect(
+            effects.add(
                 Some(entity),
                 EffectType::SpellUse {
                     spell: useitem.spell,
@@ -68,6 +78,13 @@ impl<'a> System<'a> for SpellUseSystem {
                     }
                 },
             );
+            effects.add(
+                Some(entity),
+                EffectType::TrainSkill {
+                    skill: Skill::Magic,
+                },
+                Targets::Single { target: entity },
+            );
         }
 
         wants_use.clear();
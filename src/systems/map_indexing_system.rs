@@ -4,7 +4,6 @@ use crate::components::{Pools, TileSize};
 use crate::{
     components::{BlocksTile, Position},
     map::Map,
-    spatial,
 };
 
 pub struct MapIndexingSystem {}
@@ -20,10 +19,10 @@ impl<'a> System<'a> for MapIndexingSystem {
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (map, position, blockers, pools, sizes, entities) = data;
+        let (mut map, position, blockers, pools, sizes, entities) = data;
 
-        spatial::clear();
-        spatial::populate_blocked_from_map(&map);
+        map.clear_content_index();
+        map.populate_blocked();
         for (entity, position) in (&entities, &position).join() {
             let mut alive = true;
             if let Some(pools) = pools.get(entity) {
@@ -40,13 +39,15 @@ impl<'a> System<'a> for MapIndexingSystem {
                     for x in position.x..position.x + size.x {
                         if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
                             let idx = map.xy_idx(x, y);
-                            spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                            map.spatial
+                                .index_entity(entity, idx, blockers.get(entity).is_some());
                         }
                     }
                 }
             } else {
                 let idx = map.xy_idx(position.x, position.y);
-                spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                map.spatial
+                    .index_entity(entity, idx, blockers.get(entity).is_some());
             }
         }
     }
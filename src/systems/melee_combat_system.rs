@@ -1,14 +1,16 @@
-use rltk::{to_cp437, BLACK, BLUE, CYAN};
-use specs::{Entities, Entity, Join, ReadStorage, System, WriteStorage};
+use rltk::{to_cp437, RandomNumberGenerator, BLACK, BLUE, CYAN, GOLD, GRAY, ORANGE};
+use specs::{Entities, Entity, Join, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Name, NaturalAttackDefense,
-    Pools, Skill, Skills, WantsToMelee, Weapon, WeaponAttribute, Wearable,
+    ActionKind, Attributes, CursedItem, EquipmentChanged, EquipmentSlot, Equipped, HungerClock,
+    HungerState, Initiative, Name, NaturalAttackDefense, Pools, Position, Skill, Skills,
+    WantsToMelee, Weapon, WeaponAttribute, Wearable,
 };
-use crate::effects::{add_effect, EffectType, Targets};
-use crate::gamelog;
-use crate::gamesystem::skill_bonus;
-use crate::rng::roll_dice;
+use crate::dice::DiceRoll;
+use crate::effects::{EffectQueue, EffectType, Targets};
+use crate::gamelog::{self, GameLog};
+use crate::gamesystem::{reduce_damage, skill_bonus};
+use crate::map::Map;
 
 pub struct MeleeCombatSystem {}
 
@@ -21,10 +23,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Skills>,
         ReadStorage<'a, HungerClock>,
         ReadStorage<'a, Pools>,
-        ReadStorage<'a, Equipped>,
+        WriteStorage<'a, Equipped>,
         ReadStorage<'a, Weapon>,
         ReadStorage<'a, Wearable>,
         ReadStorage<'a, NaturalAttackDefense>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, EquipmentChanged>,
+        ReadStorage<'a, CursedItem>,
+        WriteExpect<'a, Map>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,10 +46,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
             skills,
             hunger_clock,
             pools,
-            equipped_items,
+            mut equipped_items,
             meleeweapons,
             wearables,
             natural,
+            mut rng,
+            mut effects,
+            mut log,
+            mut initiatives,
+            mut positions,
+            mut dirty,
+            cursed,
+            mut map,
         ) = data;
 
         for (entity, wants_melee, name, attacker_attributes, attacker_skills, attacker_pools) in (
@@ -60,8 +78,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
                 let mut weapon_info = Weapon {
                     attribute: WeaponAttribute::Might,
-                    damage_n_dice: 1,
-                    damage_die_type: 4,
+                    damage: DiceRoll::dice(1, 4),
                     ..Default::default()
                 };
 
@@ -70,12 +87,11 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         let attack_index = if nat.attacks.len() == 1 {
                             0
                         } else {
-                            roll_dice(1, nat.attacks.len() as i32) - 1
+                            rng.roll_dice(1, nat.attacks.len() as i32) - 1
                         } as usize;
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
-                        weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
-                        weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
-                        weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
+                        weapon_info.damage = nat.attacks[attack_index].damage.clone();
+                        weapon_info.damage_type = nat.attacks[attack_index].damage_type;
                     }
                 }
 
@@ -89,7 +105,13 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 }
 
-                let natural_roll = roll_dice(1, 20);
+                if let Some(initiative) = initiatives.get_mut(entity) {
+                    initiative.spend(ActionKind::Melee {
+                        weapon_speed: weapon_info.speed,
+                    });
+                }
+
+                let natural_roll = rng.roll_dice(1, 20);
                 let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
                     attacker_attributes.might.bonus
                 } else {
@@ -119,6 +141,11 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         }
                     })
                     .sum();
+                let armor_damage_reduction: i32 = (&equipped_items, &wearables)
+                    .join()
+                    .filter(|(wielded, _)| wielded.owner == wants_melee.target)
+                    .map(|(_, armor)| armor.damage_reduction)
+                    .sum();
                 let base_armor_class = natural
                     .get(wants_melee.target)
                     .map_or(10, |nat| nat.armor_class.unwrap_or(10));
@@ -128,39 +155,89 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 let armor_class =
                     base_armor_class + armor_quickness_bonus + armor_skill_bonus + armor_item_bonus;
 
-                if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
-                    let base_damage =
-                        roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                // A roll in the weapon's crit range always hits, as a natural 20 does
+                let critical = natural_roll != 1 && natural_roll >= weapon_info.crit_range;
+                if natural_roll != 1
+                    && (critical || natural_roll == 20 || modified_hit_roll > armor_class)
+                {
+                    let mut base_damage = weapon_info.damage.roll(&mut rng);
+                    if critical {
+                        base_damage *= weapon_info.crit_multiplier;
+                    }
                     let attr_damage_bonus = attacker_attributes.might.bonus;
                     let skill_damage_bonus = skill_bonus(Skill::Melee, attacker_skills);
-                    let weapon_damage_bonus = weapon_info.damage_bonus;
 
                     let damage = i32::max(
                         0,
-                        base_damage
-                            + attr_damage_bonus
-                            + skill_hit_bonus
-                            + skill_damage_bonus
-                            + weapon_damage_bonus,
+                        base_damage + attr_damage_bonus + skill_hit_bonus + skill_damage_bonus,
                     );
-                    add_effect(
+                    let damage = reduce_damage(
+                        damage,
+                        armor_damage_reduction,
+                        weapon_info.armor_penetration,
+                    );
+                    if damage == 0 && armor_damage_reduction > 0 {
+                        gamelog::Logger::new()
+                            .npc_name(&name.name)
+                            .append("hits")
+                            .npc_name(&target_name.name)
+                            .color(GRAY)
+                            .append("but the blow glances off their armour.")
+                            .log(&mut log);
+                    } else {
+                        effects.add(
+                            Some(entity),
+                            EffectType::Damage {
+                                amount: damage,
+                                damage_type: weapon_info.damage_type,
+                            },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
+                        if critical {
+                            gamelog::Logger::new()
+                                .npc_name(&name.name)
+                                .color(GOLD)
+                                .append("critically hits")
+                                .npc_name(&target_name.name)
+                                .append("for")
+                                .damage(damage)
+                                .append(format!("{}!", weapon_info.damage_type.noun()))
+                                .log(&mut log);
+                            effects.add(
+                                None,
+                                EffectType::Particle {
+                                    glyph: to_cp437('☼'),
+                                    fg: GOLD.into(),
+                                    bg: BLACK.into(),
+                                    lifespan: 400.0,
+                                },
+                                Targets::Single {
+                                    target: wants_melee.target,
+                                },
+                            );
+                        } else {
+                            gamelog::Logger::new()
+                                .npc_name(&name.name)
+                                .append("hits")
+                                .npc_name(&target_name.name)
+                                .append("for")
+                                .damage(damage)
+                                .append(format!("{}.", weapon_info.damage_type.noun()))
+                                .log(&mut log);
+                        }
+                    }
+                    effects.add(
                         Some(entity),
-                        EffectType::Damage { amount: damage },
-                        Targets::Single {
-                            target: wants_melee.target,
+                        EffectType::TrainSkill {
+                            skill: Skill::Melee,
                         },
+                        Targets::Single { target: entity },
                     );
-                    gamelog::Logger::new()
-                        .npc_name(&name.name)
-                        .append("hits")
-                        .npc_name(&target_name.name)
-                        .append("for")
-                        .damage(damage)
-                        .append("hp.")
-                        .log();
 
                     if let Some(chance) = &weapon_info.proc_chance {
-                        if roll_dice(1, 100) <= (chance * 100.0) as i32 {
+                        if rng.roll_dice(1, 100) <= (chance * 100.0) as i32 {
                             let effect_target = if weapon_info.proc_target.unwrap() == "Self" {
                                 Targets::Single { target: entity }
                             } else {
@@ -168,7 +245,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                                     target: wants_melee.target,
                                 }
                             };
-                            add_effect(
+                            effects.add(
                                 Some(entity),
                                 EffectType::ItemUse {
                                     item: weapon.unwrap(),
@@ -180,12 +257,44 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 } else if natural_roll == 1 {
                     gamelog::Logger::new()
                         .npc_name(&name.name)
-                        .append("considers attacking")
+                        .color(ORANGE)
+                        .append("fumbles the attack on")
                         .npc_name(&target_name.name)
-                        .append("but misjudges the timing!")
-                        .log();
+                        .append("and is thrown off balance!")
+                        .log(&mut log);
+                    effects.add(
+                        Some(entity),
+                        EffectType::Status {
+                            name: "Off Balance".to_string(),
+                        },
+                        Targets::Single { target: entity },
+                    );
 
-                    add_effect(
+                    // One fumble in four sends the weapon flying, unless it's cursed to the hand
+                    if let Some(weapon) = weapon {
+                        if cursed.get(weapon).is_none() && rng.roll_dice(1, 4) == 1 {
+                            if let Some(pos) = positions.get(entity).cloned() {
+                                equipped_items.remove(weapon);
+                                // Indexed straight away, so it's on the ground for the
+                                // rest of this tick and not just from the next one
+                                let idx = map.xy_idx(pos.x, pos.y);
+                                map.spatial.index_entity(weapon, idx, false);
+                                positions
+                                    .insert(weapon, pos)
+                                    .expect("Unable to insert position");
+                                dirty
+                                    .insert(entity, EquipmentChanged {})
+                                    .expect("Unable to insert");
+                                gamelog::Logger::new()
+                                    .npc_name(&name.name)
+                                    .color(ORANGE)
+                                    .append("drops their weapon!")
+                                    .log(&mut log);
+                            }
+                        }
+                    }
+
+                    effects.add(
                         None,
                         EffectType::Particle {
                             glyph: to_cp437('‼'),
@@ -193,9 +302,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                             bg: BLACK.into(),
                             lifespan: 200.0,
                         },
-                        Targets::Single {
-                            target: wants_melee.target,
-                        },
+                        Targets::Single { target: entity },
                     );
                 } else {
                     gamelog::Logger::new()
@@ -203,9 +310,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         .append("attacks")
                         .npc_name(&target_name.name)
                         .append("but can't connect.")
-                        .log();
+                        .log(&mut log);
+                    effects.add(
+                        Some(wants_melee.target),
+                        EffectType::TrainSkill {
+                            skill: Skill::Defense,
+                        },
+                        Targets::Single {
+                            target: wants_melee.target,
+                        },
+                    );
 
-                    add_effect(
+                    effects.add(
                         None,
                         EffectType::Particle {
                             glyph: to_cp437('‼'),
@@ -223,3 +339,111 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use specs::WorldExt;
+
+    use crate::components::{Pools, Weapon};
+    use crate::gamesystem::reduce_damage;
+    use crate::map::Map;
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn orc_with_longsword_kills_rat_within_ten_turns() {
+        // The player sits behind a wall, close enough for the mobs to get turns
+        let mut scenario = Scenario::new(
+            "
+            ##########
+            #@#......#
+            #.#......#
+            ##########
+            ",
+        );
+        let orc = scenario.spawn("Orc", 4, 1);
+        scenario.equip("Longsword", orc);
+        let rat = scenario.spawn("Rat", 5, 1);
+
+        let turns = scenario.run_until(10, |s| !s.is_alive(rat));
+
+        assert!(turns.is_some(), "the rat survived ten turns");
+        assert!(scenario.is_alive(orc));
+        assert_eq!(scenario.position(orc), Some((4, 1)));
+    }
+
+    #[test]
+    fn critical_hits_land_in_the_weapon_crit_range_and_armour_absorbs_damage() {
+        assert_eq!(reduce_damage(5, 2, 0), 3);
+        assert_eq!(reduce_damage(5, 2, 1), 4);
+        assert_eq!(reduce_damage(5, 2, 3), 5);
+        assert_eq!(reduce_damage(1, 3, 0), 0);
+
+        let mut scenario = Scenario::new(
+            "
+            ######
+            #@...#
+            ######
+            ",
+        );
+        let player = scenario.player();
+        let sword = scenario.equip("Longsword", player);
+        scenario
+            .ecs
+            .write_storage::<Weapon>()
+            .get_mut(sword)
+            .unwrap()
+            .crit_range = 2;
+        let rat = scenario.spawn("Rat", 2, 1);
+
+        for _ in 0..10 {
+            if scenario.log_contains("critically hits") || !scenario.is_alive(rat) {
+                break;
+            }
+            scenario.move_player(1, 0);
+        }
+
+        assert!(scenario.log_contains("critically hits"));
+    }
+
+    #[test]
+    fn fumbled_weapons_land_on_the_ground_and_can_be_picked_up() {
+        let mut scenario = Scenario::new(
+            "
+            ######
+            #@...#
+            ######
+            ",
+        );
+        let player = scenario.player();
+        let sword = scenario.equip("Longsword", player);
+        let rat = scenario.spawn("Rat", 2, 1);
+        {
+            let mut pools = scenario.ecs.write_storage::<Pools>();
+            pools.get_mut(player).unwrap().god_mode = true;
+            let rat_pools = pools.get_mut(rat).unwrap();
+            rat_pools.hit_points.max = 1_000_000;
+            rat_pools.hit_points.current = 1_000_000;
+        }
+
+        for _ in 0..1000 {
+            if scenario.log_contains("drops their weapon!") {
+                break;
+            }
+            scenario.move_player(1, 0);
+        }
+
+        assert!(scenario.log_contains("drops their weapon!"));
+        assert_eq!(scenario.position(sword), Some((1, 1)));
+        assert!(!scenario.has_item(player, "Longsword"));
+        {
+            let map = scenario.ecs.fetch::<Map>();
+            let idx = map.xy_idx(1, 1);
+            assert!(map.spatial.get_tile_content_clone(idx).contains(&sword));
+        }
+
+        scenario.pick_up();
+
+        assert!(scenario.has_item(player, "Longsword"));
+        assert_eq!(scenario.position(sword), None);
+    }
+}
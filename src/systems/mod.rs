@@ -26,6 +26,8 @@ use inventory_system::{
 };
 mod hunger_system;
 use hunger_system::HungerSystem;
+mod status_system;
+use status_system::StatusSystem;
 pub mod particle_system;
 use particle_system::ParticleSpawnSystem;
 mod lighting_system;
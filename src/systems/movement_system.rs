@@ -1,11 +1,11 @@
 use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    ApplyMove, ApplyTeleport, BlocksTile, EntityMoved, OtherLevelPosition, Position, Viewshed,
+    ActionKind, ApplyMove, ApplyTeleport, BlocksTile, EntityMoved, Initiative, OtherLevelPosition,
+    Position, Viewshed,
 };
 use crate::map::Map;
 use crate::player::RunState;
-use crate::spatial;
 
 pub struct MovementSystem {}
 
@@ -22,11 +22,12 @@ impl<'a> System<'a> for MovementSystem {
         WriteStorage<'a, Viewshed>,
         ReadExpect<'a, Entity>,
         WriteExpect<'a, RunState>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
-            map,
+            mut map,
             mut position,
             _blockers,
             entities,
@@ -37,6 +38,7 @@ impl<'a> System<'a> for MovementSystem {
             mut viewsheds,
             player_entity,
             mut runstate,
+            mut initiatives,
         ) = data;
 
         for (entity, teleport) in (&entities, &apply_teleport).join() {
@@ -58,7 +60,7 @@ impl<'a> System<'a> for MovementSystem {
             } else if let Some(pos) = position.get(entity) {
                 let idx = map.xy_idx(pos.x, pos.y);
                 let dest_idx = map.xy_idx(teleport.dest_x, teleport.dest_y);
-                spatial::move_entity(entity, idx, dest_idx);
+                map.spatial.move_entity(entity, idx, dest_idx);
                 other_level
                     .insert(
                         entity,
@@ -77,12 +79,15 @@ impl<'a> System<'a> for MovementSystem {
         for (entity, movement, pos) in (&entities, &apply_move, &mut position).join() {
             let start_idx = map.xy_idx(pos.x, pos.y);
             let dest_idx = movement.dest_idx;
-            spatial::move_entity(entity, start_idx, dest_idx);
+            map.spatial.move_entity(entity, start_idx, dest_idx);
             pos.x = movement.dest_idx as i32 % map.width;
             pos.y = movement.dest_idx as i32 / map.width;
             if let Some(vs) = viewsheds.get_mut(entity) {
                 vs.dirty = true;
             }
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::Move);
+            }
             moved
                 .insert(entity, EntityMoved {})
                 .expect("Unable to insert");
@@ -1,9 +1,9 @@
-use rltk::{FontCharType, Rltk, RGB};
-use specs::{Entities, Join, System, World, WorldExt, WriteExpect, WriteStorage};
+use rltk::{FontCharType, RGB};
+use specs::{Entities, Entity, Join, System, World, WorldExt, WriteExpect, WriteStorage};
 
 use crate::components::{ParticleLifetime, Position, Renderable};
 
-pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
+pub fn update_particles(ecs: &mut World, frame_time_ms: f32) {
     let mut dead_particles = Vec::new();
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
@@ -11,7 +11,7 @@ pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
 
         for (entity, particle) in (&entities, &mut particles).join() {
             if let Some(animation) = &mut particle.animation {
-                animation.timer += ctx.frame_time_ms;
+                animation.timer += frame_time_ms;
                 if animation.timer > animation.step_time
                     && animation.current_step < animation.path.len() - 2
                 {
@@ -23,15 +23,31 @@ pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
                     }
                 }
             }
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
         }
     }
 
+    // Expired particles are only hidden here: deleting them on the frame clock would make
+    // entity ids depend on frame timing. `cull_particles` removes them between turns.
+    let mut renderables = ecs.write_storage::<Renderable>();
     for dead in &dead_particles {
-        ecs.delete_entity(*dead).expect("Unable to delete");
+        renderables.remove(*dead);
+    }
+}
+
+/// Deletes every particle, expired or not, in entity order.
+pub fn cull_particles(ecs: &mut World) {
+    let particles: Vec<Entity> = {
+        let entities = ecs.entities();
+        let lifetimes = ecs.read_storage::<ParticleLifetime>();
+        (&entities, &lifetimes).join().map(|(e, _)| e).collect()
+    };
+
+    for particle in &particles {
+        ecs.delete_entity(*particle).expect("Unable to delete");
     }
 }
 
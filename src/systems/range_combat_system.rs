@@ -1,15 +1,16 @@
-use rltk::{to_cp437, Point, BLACK, CYAN};
-use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use rltk::{to_cp437, Point, RandomNumberGenerator, BLACK, CYAN};
+use specs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{
-    Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Name, NaturalAttackDefense,
-    Pools, Position, Skill, Skills, WantsToShoot, Weapon, WeaponAttribute, Wearable,
+    ActionKind, Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Initiative, Name,
+    NaturalAttackDefense, Pools, Position, Skill, Skills, WantsToShoot, Weapon, WeaponAttribute,
+    Wearable,
 };
-use crate::effects::{add_effect, EffectType, Targets};
-use crate::gamelog;
+use crate::dice::DiceRoll;
+use crate::effects::{EffectQueue, EffectType, Targets};
+use crate::gamelog::{self, GameLog};
 use crate::gamesystem::skill_bonus;
 use crate::map::Map;
-use crate::rng::roll_dice;
 
 pub struct RangedCombatSystem {}
 
@@ -28,6 +29,10 @@ impl<'a> System<'a> for RangedCombatSystem {
         ReadStorage<'a, NaturalAttackDefense>,
         ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Initiative>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -45,6 +50,10 @@ impl<'a> System<'a> for RangedCombatSystem {
             natural,
             positions,
             map,
+            mut rng,
+            mut effects,
+            mut log,
+            mut initiatives,
         ) = data;
 
         let comps = (
@@ -68,7 +77,7 @@ impl<'a> System<'a> for RangedCombatSystem {
             let target_name = names.get(wants_shoot.target).unwrap();
             let apos = positions.get(entity).unwrap();
             let dpos = positions.get(wants_shoot.target).unwrap();
-            add_effect(
+            effects.add(
                 None,
                 EffectType::ParticleProjectile {
                     glyph: to_cp437('*'),
@@ -88,8 +97,7 @@ impl<'a> System<'a> for RangedCombatSystem {
 
             let mut weapon_info = Weapon {
                 attribute: WeaponAttribute::Might,
-                damage_n_dice: 1,
-                damage_die_type: 4,
+                damage: DiceRoll::dice(1, 4),
                 ..Default::default()
             };
 
@@ -98,12 +106,11 @@ impl<'a> System<'a> for RangedCombatSystem {
                     let attack_index = if nat.attacks.len() == 1 {
                         0
                     } else {
-                        roll_dice(1, nat.attacks.len() as i32) as usize - 1
+                        rng.roll_dice(1, nat.attacks.len() as i32) as usize - 1
                     };
                     weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
-                    weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
-                    weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
-                    weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
+                    weapon_info.damage = nat.attacks[attack_index].damage.clone();
+                    weapon_info.damage_type = nat.attacks[attack_index].damage_type;
                 }
             }
 
@@ -115,7 +122,13 @@ impl<'a> System<'a> for RangedCombatSystem {
                 }
             }
 
-            let natural_roll = roll_dice(1, 20);
+            if let Some(initiative) = initiatives.get_mut(entity) {
+                initiative.spend(ActionKind::Shoot {
+                    weapon_speed: weapon_info.speed,
+                });
+            }
+
+            let natural_roll = rng.roll_dice(1, 20);
             let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
                 attacker_attributes.might.bonus
             } else {
@@ -155,19 +168,18 @@ impl<'a> System<'a> for RangedCombatSystem {
 
             if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                 // Target hit! Until we support weapons, we're going with 1d4
-                let base_damage = roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                let base_damage = weapon_info.damage.roll(&mut rng);
                 let attr_damage_bonus = attacker_attributes.might.bonus;
                 let skill_damage_bonus = skill_bonus(Skill::Melee, attacker_skills);
-                let weapon_damage_bonus = weapon_info.damage_bonus;
 
-                let damage = i32::max(
-                    0,
-                    base_damage + attr_damage_bonus + skill_damage_bonus + weapon_damage_bonus,
-                );
+                let damage = i32::max(0, base_damage + attr_damage_bonus + skill_damage_bonus);
 
-                add_effect(
+                effects.add(
                     Some(entity),
-                    EffectType::Damage { amount: damage },
+                    EffectType::Damage {
+                        amount: damage,
+                        damage_type: weapon_info.damage_type,
+                    },
                     Targets::Single {
                         target: wants_shoot.target,
                     },
@@ -178,12 +190,19 @@ impl<'a> System<'a> for RangedCombatSystem {
                     .npc_name(&target_name.name)
                     .append("for")
                     .damage(damage)
-                    .append("hp.")
-                    .log();
+                    .append(format!("{}.", weapon_info.damage_type.noun()))
+                    .log(&mut log);
+                effects.add(
+                    Some(entity),
+                    EffectType::TrainSkill {
+                        skill: Skill::Melee,
+                    },
+                    Targets::Single { target: entity },
+                );
 
                 // Proc effects
                 if let Some(chance) = &weapon_info.proc_chance {
-                    let roll = roll_dice(1, 100);
+                    let roll = rng.roll_dice(1, 100);
                     if roll <= (chance * 100.0) as i32 {
                         let effect_target = if weapon_info.proc_target.unwrap() == "Self" {
                             Targets::Single { target: entity }
@@ -192,7 +211,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                                 target: wants_shoot.target,
                             }
                         };
-                        add_effect(
+                        effects.add(
                             Some(entity),
                             EffectType::ItemUse {
                                 item: weapon_entity.unwrap(),
@@ -208,9 +227,9 @@ impl<'a> System<'a> for RangedCombatSystem {
                     .append("considers attacking")
                     .npc_name(&target_name.name)
                     .append("but misjudges the timing.")
-                    .log();
+                    .log(&mut log);
 
-                add_effect(
+                effects.add(
                     None,
                     EffectType::Particle {
                         glyph: rltk::to_cp437('‼'),
@@ -229,9 +248,18 @@ impl<'a> System<'a> for RangedCombatSystem {
                     .append("attacks")
                     .npc_name(&target_name.name)
                     .append("but can't connect.")
-                    .log();
+                    .log(&mut log);
+                effects.add(
+                    Some(wants_shoot.target),
+                    EffectType::TrainSkill {
+                        skill: Skill::Defense,
+                    },
+                    Targets::Single {
+                        target: wants_shoot.target,
+                    },
+                );
 
-                add_effect(
+                effects.add(
                     None,
                     EffectType::Particle {
                         glyph: rltk::to_cp437('‼'),
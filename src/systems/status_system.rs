@@ -0,0 +1,85 @@
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+
+use crate::components::{
+    DamageOverTime, Duration, EquipmentChanged, HealingOverTime, StatusEffect, StatusInfo,
+};
+use crate::effects::{EffectQueue, EffectType, Targets};
+use crate::player::RunState;
+
+/// Counts statuses down and fires their periodic effects, once each time the
+/// player's turn comes round.
+pub struct StatusSystem {}
+
+impl<'a> System<'a> for StatusSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Duration>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, StatusInfo>,
+        ReadStorage<'a, DamageOverTime>,
+        ReadStorage<'a, HealingOverTime>,
+        WriteStorage<'a, EquipmentChanged>,
+        WriteExpect<'a, EffectQueue>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            runstate,
+            mut durations,
+            statuses,
+            infos,
+            dots,
+            hots,
+            mut dirty,
+            mut effects,
+        ) = data;
+
+        // Initiative hands the player their turn in the same run as this, and
+        // the systems don't run again until the player has acted.
+        if *runstate != RunState::AwaitingInput {
+            return;
+        }
+
+        for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
+            if !entities.is_alive(status.target) {
+                continue;
+            }
+
+            duration.turns -= 1;
+            let stacks = StatusInfo::stacks_of(infos.get(effect_entity));
+            let target = Targets::Single {
+                target: status.target,
+            };
+
+            if let Some(dot) = dots.get(effect_entity) {
+                effects.add(
+                    None,
+                    EffectType::Damage {
+                        amount: dot.damage * stacks,
+                        damage_type: dot.damage_type,
+                    },
+                    target.clone(),
+                );
+            }
+
+            if let Some(hot) = hots.get(effect_entity) {
+                effects.add(
+                    None,
+                    EffectType::Healing {
+                        amount: hot.healing * stacks,
+                    },
+                    target,
+                );
+            }
+
+            if duration.turns < 1 {
+                dirty
+                    .insert(status.target, EquipmentChanged {})
+                    .expect("Unable to insert");
+                entities.delete(effect_entity).expect("Unable to delete");
+            }
+        }
+    }
+}
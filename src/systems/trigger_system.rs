@@ -1,9 +1,9 @@
 use rltk::{Point, RED, WHITE};
-use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
 
 use crate::components::{AreaOfEffect, EntityMoved, EntryTrigger, Name, Position};
-use crate::effects::{add_effect, aoe_tiles, EffectType, Targets};
-use crate::gamelog;
+use crate::effects::{aoe_tiles, EffectQueue, EffectType, Targets};
+use crate::gamelog::{self, GameLog};
 use crate::map::Map;
 
 pub struct TriggerSystem {}
@@ -17,15 +17,26 @@ impl<'a> System<'a> for TriggerSystem {
         ReadStorage<'a, Name>,
         Entities<'a>,
         ReadStorage<'a, AreaOfEffect>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (map, mut entity_moved, position, entry_trigger, names, entities, area_of_effect) =
-            data;
+        let (
+            map,
+            mut entity_moved,
+            position,
+            entry_trigger,
+            names,
+            entities,
+            area_of_effect,
+            mut effects,
+            mut log,
+        ) = data;
 
         for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &position).join() {
             let idx = map.xy_idx(pos.x, pos.y);
-            crate::spatial::for_each_tile_content(idx, |entity_id| {
+            map.spatial.for_each_tile_content(idx, |entity_id| {
                 if entity == entity_id {
                     return;
                 } // Do not bother to check yourself for being a trap!
@@ -41,11 +52,12 @@ impl<'a> System<'a> for TriggerSystem {
                                 .append(&name.name)
                                 .color(WHITE)
                                 .append("triggers!")
-                                .log();
+                                .log(&mut log);
                         }
 
-                        add_effect(
-                            Some(entity),
+                        // The trap is the one doing the damage, not whoever stepped on it
+                        effects.add(
+                            Some(entity_id),
                             EffectType::TriggerFire { trigger: entity_id },
                             if let Some(aoe) = area_of_effect.get(entity_id) {
                                 Targets::Tiles {
@@ -65,3 +77,30 @@ impl<'a> System<'a> for TriggerSystem {
         entity_moved.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::Scenario;
+
+    #[test]
+    fn bear_trap_deals_six_and_deactivates() {
+        let mut scenario = Scenario::new(
+            "
+            #####
+            #@..#
+            #####
+            ",
+        );
+        let player = scenario.player();
+        let trap = scenario.spawn("Bear Trap", 2, 1);
+        let starting_hp = scenario.hit_points(player);
+
+        scenario.move_player(1, 0);
+
+        assert_eq!(scenario.position(player), Some((2, 1)));
+        assert_eq!(scenario.hit_points(player), starting_hp - 6);
+        assert_eq!(scenario.event_count("Damage Taken"), 6);
+        assert!(scenario.log_contains("Bear Trap triggers!"));
+        assert!(!scenario.is_alive(trap));
+    }
+}
@@ -1,13 +1,11 @@
-use rltk::{field_of_view, Point, RED};
+use rltk::{field_of_view, Point, RandomNumberGenerator, RED};
 use specs::prelude::*;
 
 use crate::components::{BlocksVisibility, Hidden, Name};
-use crate::rng::roll_dice;
 use crate::{
     components::{Player, Position, Viewshed},
-    gamelog,
+    gamelog::{self, GameLog},
     map::Map,
-    spatial,
 };
 
 pub struct VisibilitySystem {}
@@ -22,11 +20,23 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, name, blocks_visibility) =
-            data;
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            name,
+            blocks_visibility,
+            mut rng,
+            mut log,
+        ) = data;
 
         map.view_blocked.clear();
         for (block_pos, _block) in (&pos, &blocks_visibility).join() {
@@ -55,17 +65,17 @@ impl<'a> System<'a> for VisibilitySystem {
                     map.revealed_tiles[idx] = true;
                     map.visible_tiles[idx] = true;
 
-                    spatial::for_each_tile_content(idx, |e| {
+                    map.spatial.for_each_tile_content(idx, |e| {
                         let maybe_hidden = hidden.get(e);
                         if let Some(_hidden) = maybe_hidden {
-                            if roll_dice(1, 24) == 1 {
+                            if rng.roll_dice(1, 24) == 1 {
                                 let name = name.get(e);
                                 if let Some(name) = name {
                                     gamelog::Logger::new()
                                         .append("You spotted:")
                                         .color(RED)
                                         .append(&name.name)
-                                        .log();
+                                        .log(&mut log);
                                 }
                                 hidden.remove(e);
                             }
@@ -0,0 +1,258 @@
+//! Small hand-drawn worlds for regression tests of combat, effects and AI.
+//!
+//! A scenario registers every component, loads the raws and builds a map
+//! from ASCII art, so tests can place mobs, props and items by raw name and run the
+//! same dispatcher the game uses.
+
+use std::sync::{Arc, OnceLock};
+
+use rltk::{Point, RandomNumberGenerator};
+use specs::{Entity, Join, World, WorldExt};
+
+use crate::components::{Equipped, InBackpack, Name, Player, Pools, Position};
+use crate::gamelog::{GameEvents, GameLog};
+use crate::map::dungeon::MasterDungeonMap;
+use crate::map::tiletype::TileType;
+use crate::map::Map;
+use crate::player::RunState;
+use crate::raws::rawmaster::{spawn_named_entity, RawMaster, SpawnType};
+use crate::spawner::NewCharacter;
+use crate::systems::particle_system::ParticleBuilder;
+use crate::systems::UnifiedDispatcher;
+use crate::{damage_system, level_up, raws, rng, spawner, systems};
+
+pub mod raw_files;
+pub mod saves;
+
+const SEED: u64 = 1;
+/// Gives up on a turn that never hands control back to the player.
+const MAX_TICKS_PER_TURN: i32 = 100;
+
+// Parsed once and shared, every scenario reads the same raws.
+static RAWS: OnceLock<Arc<RawMaster>> = OnceLock::new();
+
+pub fn shared_raws() -> Arc<RawMaster> {
+    RAWS.get_or_init(|| Arc::new(raws::load_raws())).clone()
+}
+
+pub struct Scenario {
+    pub ecs: World,
+    dispatcher: Box<dyn UnifiedDispatcher + 'static>,
+}
+
+impl Scenario {
+    /// Builds a world from an ASCII map: `#` is a wall, `.` floor, `>` and `<`
+    /// stairs and `@` the player, who must be somewhere on the map. The player
+    /// starts as the first class in the raws.
+    pub fn new(layout: &str) -> Self {
+        let class = shared_raws().classes()[0].name.clone();
+        Self::with_class(layout, &class)
+    }
+
+    /// Like [`Scenario::new`], with the player started as `class`.
+    pub fn with_class(layout: &str, class: &str) -> Self {
+        let raws = shared_raws();
+        let class = raws
+            .class(class)
+            .unwrap_or_else(|| panic!("No class named {class}"));
+        Self::with_character(layout, &NewCharacter::of_class(class))
+    }
+
+    /// Like [`Scenario::new`], with the player started as `character`.
+    pub fn with_character(layout: &str, character: &NewCharacter) -> Self {
+        let rows: Vec<&str> = layout
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let height = rows.len() as i32;
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+
+        let mut map = Map::new(1, width, height, "Scenario");
+        let mut player_pos = None;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, glyph) in row.chars().enumerate() {
+                let idx = map.xy_idx(x as i32, y as i32);
+                map.tiles[idx] = match glyph {
+                    '#' => TileType::Wall,
+                    '>' => TileType::DownStairs,
+                    '<' => TileType::UpStairs,
+                    '.' => TileType::Floor,
+                    '@' => {
+                        player_pos = Some(Point::new(x as i32, y as i32));
+                        TileType::Floor
+                    }
+                    _ => panic!("Unknown map glyph {glyph:?}"),
+                };
+            }
+        }
+        let player_pos = player_pos.expect("The scenario map needs a player (@)");
+
+        let raws = shared_raws();
+        let mut rng = RandomNumberGenerator::seeded(SEED);
+        let mut ecs = crate::new_world();
+        ecs.insert(rng::RunSeed(SEED));
+        ecs.insert(MasterDungeonMap::new(&raws, &mut rng));
+        ecs.insert(rng);
+        ecs.insert(raws);
+        ecs.insert(map);
+        ecs.insert(player_pos);
+        ecs.insert(ParticleBuilder::new());
+        let player = spawner::player(&mut ecs, player_pos.x, player_pos.y, character);
+        ecs.insert(player);
+
+        let mut scenario = Self {
+            ecs,
+            dispatcher: systems::build(),
+        };
+        scenario.settle();
+        scenario
+    }
+
+    pub fn player(&self) -> Entity {
+        *self.ecs.fetch::<Entity>()
+    }
+
+    /// Places a mob, prop or item by its raw name.
+    pub fn spawn(&mut self, name: &str, x: i32, y: i32) -> Entity {
+        self.spawn_as(name, SpawnType::AtPosition { x, y })
+    }
+
+    /// Spawns an item by raw name straight into `owner`'s hands.
+    pub fn equip(&mut self, name: &str, owner: Entity) -> Entity {
+        self.spawn_as(name, SpawnType::Equipped { by: owner })
+    }
+
+    /// Spawns an item by raw name into `owner`'s backpack.
+    pub fn give(&mut self, name: &str, owner: Entity) -> Entity {
+        self.spawn_as(name, SpawnType::Carried { by: owner })
+    }
+
+    fn spawn_as(&mut self, name: &str, spawn_type: SpawnType) -> Entity {
+        let raws = self.ecs.fetch::<Arc<RawMaster>>().clone();
+        let entity = spawn_named_entity(&raws, &mut self.ecs, name, spawn_type)
+            .unwrap_or_else(|| panic!("No raw named {name}"));
+        self.settle();
+        entity
+    }
+
+    /// Runs the systems without passing time, the way the game does before handing
+    /// control to the player, so newcomers are indexed and can see.
+    pub fn settle(&mut self) {
+        self.ecs.insert(RunState::PreRun);
+        self.tick();
+    }
+
+    /// Runs the dispatcher once, then clears out the dead, like one frame of the game loop.
+    pub fn tick(&mut self) {
+        self.dispatcher.run_now(&mut self.ecs);
+        self.ecs.maintain();
+        damage_system::delete_the_dead(&mut self.ecs);
+        self.ecs.maintain();
+    }
+
+    /// Runs `count` dispatcher ticks.
+    pub fn run_ticks(&mut self, count: i32) {
+        for _ in 0..count {
+            *self.ecs.write_resource::<RunState>() = RunState::Ticking;
+            self.tick();
+        }
+    }
+
+    /// Ticks until it's the player's turn again, or the level up screen if they
+    /// gained a level. The player just waits.
+    pub fn run_turn(&mut self) {
+        *self.ecs.write_resource::<RunState>() = RunState::Ticking;
+        for _ in 0..MAX_TICKS_PER_TURN {
+            self.tick();
+            let runstate = *self.ecs.fetch::<RunState>();
+            if runstate != RunState::Ticking {
+                let runstate = level_up::after_turn(&self.ecs, runstate);
+                *self.ecs.write_resource::<RunState>() = runstate;
+                return;
+            }
+        }
+        panic!("The player never got another turn");
+    }
+
+    /// Plays turns until `done` holds, returning how many it took, or `None` if
+    /// it still doesn't hold after `max_turns`.
+    pub fn run_until(&mut self, max_turns: i32, done: impl Fn(&Self) -> bool) -> Option<i32> {
+        for turn in 1..=max_turns {
+            self.run_turn();
+            if done(self) {
+                return Some(turn);
+            }
+        }
+        None
+    }
+
+    /// Steps the player, then plays out the turn it costs.
+    pub fn move_player(&mut self, dx: i32, dy: i32) {
+        let runstate = Player::try_move_player(dx, dy, &self.ecs);
+        if runstate != RunState::AwaitingInput {
+            self.run_turn();
+        }
+    }
+
+    /// Picks up whatever lies under the player, then plays out the turn it costs.
+    pub fn pick_up(&mut self) {
+        Player::get_item(&self.ecs);
+        self.run_turn();
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.ecs.is_alive(entity)
+    }
+
+    pub fn hit_points(&self, entity: Entity) -> i32 {
+        self.pools(entity).hit_points.current
+    }
+
+    pub fn max_hit_points(&self, entity: Entity) -> i32 {
+        self.pools(entity).hit_points.max
+    }
+
+    pub fn pools(&self, entity: Entity) -> Pools {
+        self.ecs
+            .read_storage::<Pools>()
+            .get(entity)
+            .cloned()
+            .expect("Entity has no pools")
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<(i32, i32)> {
+        self.ecs
+            .read_storage::<Position>()
+            .get(entity)
+            .map(|pos| (pos.x, pos.y))
+    }
+
+    pub fn event_count(&self, event: &str) -> i32 {
+        self.ecs.fetch::<GameEvents>().count(event)
+    }
+
+    /// Whether any game log line contains `text`.
+    pub fn log_contains(&self, text: &str) -> bool {
+        self.ecs.fetch::<GameLog>().entries.iter().any(|line| {
+            line.iter()
+                .map(|fragment| fragment.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+                .contains(text)
+        })
+    }
+
+    /// Whether `owner` carries or wields an item with this name.
+    pub fn has_item(&self, owner: Entity, name: &str) -> bool {
+        let entities = self.ecs.entities();
+        let names = self.ecs.read_storage::<Name>();
+        let backpack = self.ecs.read_storage::<InBackpack>();
+        let equipped = self.ecs.read_storage::<Equipped>();
+        (&entities, &names).join().any(|(item, item_name)| {
+            item_name.name == name
+                && (backpack.get(item).map_or(false, |b| b.owner == owner)
+                    || equipped.get(item).map_or(false, |e| e.owner == owner))
+        })
+    }
+}
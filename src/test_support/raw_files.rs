@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::raws::sources::{self, RawMerger};
+
+const RAWS_DIR: &str = "raws";
+
+/// The raws the game ships with, ready for a test mod to go on top.
+pub fn shipped_raws() -> RawMerger {
+    let mut merger = RawMerger::default();
+    merger.add_dir(Path::new(RAWS_DIR)).unwrap();
+    merger
+}
+
+/// The entry called `name` as it's written in the shipped files.
+pub fn shipped_entry(section: &str, name: &str) -> Value {
+    let (_, index) = sources::read_raws_dir(Path::new(RAWS_DIR)).unwrap();
+    let path = index
+        .source_of(section, name)
+        .unwrap_or_else(|| panic!("No {section} entry named {name}"));
+    let file: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    file[section]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["name"] == name)
+        .unwrap()
+        .clone()
+}
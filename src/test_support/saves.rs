@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::{env, process};
+
+use serde_json::Value;
+
+use crate::saveload_system::{self, SaveEncoding};
+
+use super::Scenario;
+
+/// A small scenario, and the values of the JSON save it writes: the header,
+/// then a value per component storage.
+pub fn saved_scenario() -> (Scenario, Vec<Value>) {
+    let mut scenario = Scenario::new(
+        "
+        #####
+        #@..#
+        #####
+        ",
+    );
+    let mut save = Vec::new();
+    saveload_system::write_save(&mut scenario.ecs, &mut save, SaveEncoding::Json);
+    let values = serde_json::Deserializer::from_slice(&save)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    (scenario, values)
+}
+
+/// Writes `values` back out as a save.
+pub fn join(values: &[Value]) -> Vec<u8> {
+    values
+        .iter()
+        .map(Value::to_string)
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// A fresh, empty data directory that stands in for the player's until it's
+/// dropped, so tests can write saves, options and replays.
+pub struct TempDataDir(PathBuf);
+
+pub fn temp_data_dir() -> TempDataDir {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    let dir = env::temp_dir().join(format!(
+        "rusty-roguelike-test-{}-{}",
+        process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    saveload_system::set_test_data_dir(Some(dir.clone()));
+    TempDataDir(dir)
+}
+
+impl Drop for TempDataDir {
+    fn drop(&mut self) {
+        saveload_system::set_test_data_dir(None);
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}